@@ -1,11 +1,14 @@
-use criterion::{Criterion, criterion_group, criterion_main};
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
 use fake::{Fake, uuid::UUIDv7};
 use lib::{DataType, KafkaRecord, SearchQuery};
 use mock_json::mock;
+use rdkafka::{Timestamp, message::OwnedMessage};
 use serde_json::json;
 use std::hint::black_box;
-use std::{collections::BTreeMap, env::temp_dir};
-use yozefu_app::search::{Search, SearchContext};
+use std::{collections::BTreeMap, env::temp_dir, thread::available_parallelism};
+use yozefu_app::search::{
+    Search, SearchContext, deserializer::Deserializers, engine::SearchEngine, transform::Transforms,
+};
 
 fn generate_mock_value() -> serde_json::Value {
     mock(&json!({
@@ -71,6 +74,7 @@ fn generate_mock_kafka_record() -> KafkaRecord {
         size: (0..18000).fake::<usize>(),
         key_as_string: serde_json::to_string(&key).unwrap(),
         value_as_string,
+        tombstone: false,
     }
 }
 
@@ -114,5 +118,66 @@ fn value_contains_string(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, read_all, even_offset, value_contains_string);
+fn generate_mock_message(partition: i32, offset: i64) -> OwnedMessage {
+    let key: String = UUIDv7.fake();
+    let value = serde_json::to_vec(&generate_mock_value()).unwrap();
+    OwnedMessage::new(
+        Some(value),
+        Some(key.into_bytes()),
+        "bench".to_string(),
+        Timestamp::NotAvailable,
+        partition,
+        offset,
+        None,
+    )
+}
+
+fn parallel_search_engine(c: &mut Criterion) {
+    const RECORDS: i64 = 2_000;
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let messages = (0..RECORDS)
+        .map(|offset| generate_mock_message(i32::try_from(offset % 16).unwrap(), offset))
+        .collect::<Vec<_>>();
+    let mut group = c.benchmark_group("search engine");
+    group.throughput(Throughput::Elements(RECORDS as u64));
+    let cores = available_parallelism().map_or(1, std::num::NonZeroUsize::get);
+    for workers in [1, cores] {
+        group.bench_with_input(
+            BenchmarkId::new("workers", workers),
+            &workers,
+            |b, workers| {
+                b.to_async(&runtime).iter(|| async {
+                    let (_, search_query) =
+                        SearchQuery::parse("from begin value contains 'fff' or key contains '34'")
+                            .unwrap();
+                    let (engine, mut rx) = SearchEngine::start(
+                        search_query,
+                        temp_dir(),
+                        None,
+                        Transforms::default(),
+                        Deserializers::default(),
+                        *workers,
+                    )
+                    .unwrap();
+                    for message in messages.clone() {
+                        engine.send(message).unwrap();
+                    }
+                    drop(engine);
+                    while let Some(evaluation) = rx.recv().await {
+                        black_box(evaluation);
+                    }
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    read_all,
+    even_offset,
+    value_contains_string,
+    parallel_search_engine
+);
 criterion_main!(benches);
@@ -17,7 +17,7 @@ use crate::{
     AdminClient,
     configuration::{Configuration, ConsumerConfig, InternalConfig, YozefuConfig},
     consumer::Consumer,
-    search::ValidSearchQuery,
+    search::{ValidSearchQuery, deserializer::Deserializers, transform::Transforms},
 };
 
 /// Struct exposing different functions for consuming kafka records.
@@ -44,6 +44,25 @@ impl App {
         }
     }
 
+    /// Loads the transformers of the search query and the ones configured for the topics of the cluster.
+    pub fn transforms(&self) -> Result<Transforms, Error> {
+        Transforms::load(
+            &self.search_query.query().transforms,
+            &self.config.topic_transforms()?,
+            &self.config.workspace().filters_dir(),
+            &self.config.filter_limits(),
+        )
+    }
+
+    /// Loads the deserializer plugins configured for the topics of the cluster.
+    pub fn deserializers(&self) -> Result<Deserializers, Error> {
+        Deserializers::load(
+            &self.config.topic_deserializers(),
+            &self.config.workspace().filters_dir(),
+            &self.config.filter_limits(),
+        )
+    }
+
     pub fn create_consumer_2(&self, topics: &Vec<String>) -> Result<Consumer, Error> {
         Consumer::new(
             self.config.specific.clone(),
@@ -99,6 +118,7 @@ impl App {
     }
 
     /// Calculates an estimate of the number of records that are going to be read.
+    /// When the search query samples records, only the sampled records are counted.
     /// This function is used to render a progress bar.
     pub fn estimate_number_of_records_to_read(
         &self,
@@ -107,6 +127,10 @@ impl App {
         let count = self
             .admin_client()?
             .estimate_number_of_records_to_read(topic_partition_list)?;
+        let count = match &self.search_query.query().sample {
+            Some(sample) => sample.estimate(count),
+            None => count,
+        };
         info!(
             "{} records are about to be consumed from the following topic partitions: [{}]",
             count.separate_with_underscores(),
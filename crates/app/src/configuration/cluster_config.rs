@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, path::PathBuf};
 use url::Url;
 
-use crate::configuration::{ConsumerConfig, YozefuConfig};
+use crate::configuration::{ConsumerConfig, SavedQuery, YozefuConfig};
 
 use super::Configuration;
 
@@ -31,6 +31,10 @@ impl Default for ClusterConfig {
             schema_registry: None,
             kafka: IndexMap::new(),
             consumer: None,
+            custom_partitioner: false,
+            saved_queries: IndexMap::new(),
+            transforms: IndexMap::new(),
+            deserializers: IndexMap::new(),
         }
     }
 }
@@ -48,6 +52,21 @@ pub struct ClusterConfig {
     pub kafka: IndexMap<String, String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub consumer: Option<ConsumerConfig>,
+    /// Set it to `true` if producers of this cluster don't use the default partitioner of Kafka.
+    /// Otherwise, yozefu deduces the partition of a record from its key and only consumes that partition when you search for a given key.
+    /// The partition is computed from the current number of partitions: set it to `true` as well if partitions were added to a topic,
+    /// records produced before that may be in another partition and would not be found.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub custom_partitioner: bool,
+    /// Named search queries specific to this cluster, referenced in the query language with `@name`
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub saved_queries: IndexMap<String, SavedQuery>,
+    /// Transformers applied to the kafka records of a topic, in order, such as `decrypt('k1')`
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub transforms: IndexMap<String, Vec<String>>,
+    /// Deserializer plugins of the topics whose records use a proprietary format, by topic
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub deserializers: IndexMap<String, String>,
 }
 
 impl ClusterConfig {
@@ -106,6 +125,10 @@ impl ClusterConfig {
             schema_registry: None,
             kafka: indexmap::IndexMap::from_iter(kafka_properties),
             consumer: self.consumer,
+            custom_partitioner: self.custom_partitioner,
+            saved_queries: self.saved_queries,
+            transforms: self.transforms,
+            deserializers: self.deserializers,
         }
     }
 
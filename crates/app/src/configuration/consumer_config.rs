@@ -1,3 +1,5 @@
+use std::{num::NonZeroUsize, thread::available_parallelism};
+
 use serde::{Deserialize, Serialize};
 
 /// Configuration for the kafka consumer
@@ -6,6 +8,19 @@ use serde::{Deserialize, Serialize};
 pub struct ConsumerConfig {
     pub buffer_capacity: usize,
     pub timeout_in_ms: u64,
+    /// Number of workers deserializing and matching records in parallel.
+    /// Defaults to the number of available cores.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workers: Option<usize>,
+}
+
+impl ConsumerConfig {
+    /// Returns the number of workers of the search engine, at least 1.
+    pub fn workers(&self) -> usize {
+        self.workers
+            .unwrap_or_else(|| available_parallelism().map_or(1, NonZeroUsize::get))
+            .max(1)
+    }
 }
 
 impl Default for ConsumerConfig {
@@ -13,6 +28,7 @@ impl Default for ConsumerConfig {
         Self {
             buffer_capacity: 1000,
             timeout_in_ms: 10,
+            workers: None,
         }
     }
 }
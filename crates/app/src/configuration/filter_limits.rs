@@ -0,0 +1,65 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Size of a WebAssembly memory page.
+const WASM_PAGE_SIZE_IN_BYTES: u32 = 64 * 1024;
+
+/// Resource limits of a search filter.
+/// A search filter exceeding them is disabled until yozefu is restarted.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+pub struct FilterLimits {
+    /// Maximum number of WebAssembly instructions executed by a call. Unlimited by default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fuel: Option<u64>,
+    /// Maximum memory of an instance of the search filter, in MiB
+    #[serde(default = "default_max_memory_in_mb")]
+    pub max_memory_in_mb: u32,
+    /// Maximum duration of a call, in milliseconds
+    #[serde(default = "default_timeout_in_ms")]
+    pub timeout_in_ms: u64,
+}
+
+fn default_max_memory_in_mb() -> u32 {
+    64
+}
+
+fn default_timeout_in_ms() -> u64 {
+    1000
+}
+
+impl FilterLimits {
+    /// Returns the maximum memory as a number of WebAssembly pages.
+    pub fn max_memory_in_pages(&self) -> u32 {
+        self.max_memory_in_mb
+            .saturating_mul(1024 * 1024 / WASM_PAGE_SIZE_IN_BYTES)
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_in_ms)
+    }
+}
+
+impl Default for FilterLimits {
+    fn default() -> Self {
+        Self {
+            fuel: None,
+            max_memory_in_mb: default_max_memory_in_mb(),
+            timeout_in_ms: default_timeout_in_ms(),
+        }
+    }
+}
+
+#[test]
+fn test_partial_filter_limits() {
+    let limits: FilterLimits = serde_json::from_str(r#"{ "fuel": 1000000 }"#).unwrap();
+    assert_eq!(
+        limits,
+        FilterLimits {
+            fuel: Some(1_000_000),
+            ..Default::default()
+        }
+    );
+    assert_eq!(limits.max_memory_in_pages(), 1024);
+}
@@ -12,10 +12,10 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     APPLICATION_NAME,
-    configuration::{ClusterConfig, ConsumerConfig},
+    configuration::{ClusterConfig, ConsumerConfig, FilterLimits, HistoryEntry, SavedQuery},
 };
 
-use super::cluster_config::SchemaRegistryConfig;
+use super::{cluster_config::SchemaRegistryConfig, history::deserialize_history};
 
 const EXAMPLE_PROMPTS: &[&str] = &[
     r#"timestamp between "2 hours ago" and "1 hour ago" limit 100 from beginning"#,
@@ -56,7 +56,14 @@ pub struct GlobalConfig {
     /// The default kafka properties inherited for every cluster
     pub default_kafka_config: IndexMap<String, String>,
     /// History of past search queries
-    pub history: Vec<String>,
+    #[serde(deserialize_with = "deserialize_history")]
+    pub history: Vec<HistoryEntry>,
+    /// Named search queries, referenced in the query language with `@name`
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub saved_queries: IndexMap<String, SavedQuery>,
+    /// Resource limits of the search filters, by name
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub filter_limits: IndexMap<String, FilterLimits>,
     /// Show shortcuts
     #[serde(default = "default_show_shortcuts")]
     pub show_shortcuts: bool,
@@ -96,9 +103,11 @@ impl GlobalConfig {
             default_url_template: default_url_template(),
             history: EXAMPLE_PROMPTS
                 .iter()
-                .map(|e| (*e).to_string())
+                .map(|e| HistoryEntry::from(*e))
                 .collect_vec(),
             initial_query: "from end - 10".to_string(),
+            saved_queries: IndexMap::default(),
+            filter_limits: IndexMap::default(),
             clusters: IndexMap::default(),
             default_kafka_config: IndexMap::default(),
             theme: default_theme(),
@@ -155,6 +164,21 @@ impl GlobalConfig {
             .unwrap_or(self.consumer.clone())
     }
 
+    /// Saved queries available for a given cluster.
+    /// Saved queries of the cluster take precedence over the global ones with the same name.
+    pub fn saved_queries_of(&self, cluster: &str) -> IndexMap<String, SavedQuery> {
+        let mut saved_queries = self.saved_queries.clone();
+        if let Some(config) = self.clusters.get(cluster) {
+            saved_queries.extend(config.saved_queries.clone());
+        }
+        saved_queries
+    }
+
+    /// Resource limits of a given search filter.
+    pub fn filter_limits_of(&self, filter: &str) -> FilterLimits {
+        self.filter_limits.get(filter).cloned().unwrap_or_default()
+    }
+
     /// Returns the schema registry configuration for the given cluster.
     pub fn schema_registry_config_of(&self, cluster: &str) -> Option<SchemaRegistryConfig> {
         self.clusters
@@ -0,0 +1,160 @@
+//! History of the search queries.
+//! Each entry records the cluster and the topics the search query was run against,
+//! so that the history of a cluster doesn't show up when browsing another one.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Once the history exceeds this number of entries, it is trimmed when the configuration is saved.
+const MAX_HISTORY_ENTRIES: usize = 1000;
+
+/// Number of entries kept per cluster when the history is trimmed.
+const KEPT_HISTORY_ENTRIES_PER_CLUSTER: usize = 500;
+
+/// Number of entries kept, all clusters included, when the history is trimmed.
+/// It is lower than [`MAX_HISTORY_ENTRIES`] so the history is not trimmed again on the next save.
+const KEPT_HISTORY_ENTRIES: usize = 800;
+
+/// A search query of the history
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+pub struct HistoryEntry {
+    /// The search query
+    pub query: String,
+    /// The cluster the search query was run against
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cluster: Option<String>,
+    /// The topics the search query was run against
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub topics: Vec<String>,
+    /// When the search query was run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Number of kafka records that matched the search query
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub results: Option<usize>,
+}
+
+impl HistoryEntry {
+    pub fn new(query: &str, cluster: &str, topics: &[String]) -> Self {
+        Self {
+            query: query.to_string(),
+            cluster: Some(cluster.to_string()),
+            topics: topics.to_vec(),
+            timestamp: Some(Utc::now()),
+            results: None,
+        }
+    }
+
+    /// Returns `true` if the entry belongs to the cluster.
+    /// Entries written by older versions of yozefu don't have a cluster, they belong to every cluster.
+    pub fn is_of_cluster(&self, cluster: &str) -> bool {
+        self.cluster.as_deref().is_none_or(|c| c == cluster)
+    }
+}
+
+impl From<&str> for HistoryEntry {
+    fn from(query: &str) -> Self {
+        Self {
+            query: query.to_string(),
+            cluster: None,
+            topics: vec![],
+            timestamp: None,
+            results: None,
+        }
+    }
+}
+
+/// Older versions of yozefu stored the history as a list of search queries.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum HistoryEntryFormat {
+    Query(String),
+    Entry(HistoryEntry),
+}
+
+/// Deserializes the history, whether entries are search queries or objects.
+pub(crate) fn deserialize_history<'de, D>(deserializer: D) -> Result<Vec<HistoryEntry>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let entries = Vec::<HistoryEntryFormat>::deserialize(deserializer)?;
+    Ok(entries
+        .into_iter()
+        .map(|entry| match entry {
+            HistoryEntryFormat::Query(query) => HistoryEntry::from(query.as_str()),
+            HistoryEntryFormat::Entry(entry) => entry,
+        })
+        .collect())
+}
+
+/// Keeps the newest entries of each cluster once the history is too long.
+/// The history never exceeds [`KEPT_HISTORY_ENTRIES`] entries once trimmed, whatever the number of clusters.
+pub(crate) fn trim_history(history: &mut Vec<HistoryEntry>) {
+    if history.len() <= MAX_HISTORY_ENTRIES {
+        return;
+    }
+    let mut counts: HashMap<Option<String>, usize> = HashMap::new();
+    let mut kept = history
+        .drain(..)
+        .rev()
+        .filter(|entry| {
+            let count = counts.entry(entry.cluster.clone()).or_default();
+            *count += 1;
+            *count <= KEPT_HISTORY_ENTRIES_PER_CLUSTER
+        })
+        .take(KEPT_HISTORY_ENTRIES)
+        .collect::<Vec<_>>();
+    kept.reverse();
+    *history = kept;
+}
+
+#[test]
+fn test_deserialize_history() {
+    #[derive(Deserialize)]
+    struct Config {
+        #[serde(deserialize_with = "deserialize_history")]
+        history: Vec<HistoryEntry>,
+    }
+    let config: Config = serde_json::from_str(
+        r#"{"history": ["from end - 10", {"query": "key == 'a'", "cluster": "prod", "results": 3}]}"#,
+    )
+    .unwrap();
+    assert_eq!(config.history[0], HistoryEntry::from("from end - 10"));
+    assert_eq!(config.history[1].cluster.as_deref(), Some("prod"));
+    assert_eq!(config.history[1].results, Some(3));
+    assert!(config.history[0].is_of_cluster("dev"));
+    assert!(!config.history[1].is_of_cluster("dev"));
+}
+
+#[test]
+fn test_trim_history_per_cluster() {
+    let mut history = (0..900)
+        .map(|i| HistoryEntry::new(&format!("offset == {i}"), "prod", &[]))
+        .chain((0..200).map(|i| HistoryEntry::new(&format!("offset == {i}"), "dev", &[])))
+        .collect::<Vec<_>>();
+    trim_history(&mut history);
+    assert_eq!(history.len(), 700);
+    assert_eq!(history[0].query, "offset == 400");
+    assert_eq!(
+        history.iter().filter(|e| e.is_of_cluster("dev")).count(),
+        200
+    );
+}
+
+#[test]
+fn test_trim_history_of_many_clusters() {
+    let mut history = ["prod", "dev", "staging"]
+        .iter()
+        .flat_map(|cluster| {
+            (0..600).map(move |i| HistoryEntry::new(&format!("offset == {i}"), cluster, &[]))
+        })
+        .collect::<Vec<_>>();
+    trim_history(&mut history);
+    assert_eq!(history.len(), KEPT_HISTORY_ENTRIES);
+    assert!(history.iter().all(|e| !e.is_of_cluster("prod")));
+    assert_eq!(history[0].query, "offset == 300");
+    assert!(history[0].is_of_cluster("dev"));
+}
@@ -3,9 +3,14 @@
 use std::{collections::HashMap, fs, path::PathBuf};
 
 use chrono::Local;
-use lib::Error;
+use lib::{Error, search::filter::Filter};
 
-use crate::configuration::{ConsumerConfig, SchemaRegistryConfig, Workspace};
+use indexmap::IndexMap;
+
+use crate::configuration::{
+    ConsumerConfig, FilterLimits, HistoryEntry, SavedQuery, SchemaRegistryConfig, Workspace,
+    history::trim_history,
+};
 
 use super::{Configuration, yozefu_config::YozefuConfig};
 
@@ -90,13 +95,69 @@ impl InternalConfig {
         &self.workspace
     }
 
-    pub fn history(&self) -> &[String] {
+    pub fn history(&self) -> &[HistoryEntry] {
         &self.workspace.config().history
     }
 
-    pub fn push_history(&mut self, prompt: &str) {
-        self.workspace.config.history.push(prompt.to_string());
-        self.workspace.config.history.dedup();
+    /// Saved queries available for the current cluster.
+    pub fn saved_queries(&self) -> IndexMap<String, SavedQuery> {
+        self.workspace.config().saved_queries_of(self.cluster())
+    }
+
+    /// Transformers configured for each topic of the current cluster.
+    pub fn topic_transforms(&self) -> Result<IndexMap<String, Vec<Filter>>, Error> {
+        self.specific
+            .config()
+            .transforms
+            .iter()
+            .map(|(topic, transforms)| {
+                let transforms = transforms
+                    .iter()
+                    .map(|t| {
+                        t.parse::<Filter>().map_err(|e| {
+                            Error::Error(format!(
+                                "Invalid transformer '{t}' for topic '{topic}': {e}"
+                            ))
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok((topic.clone(), transforms))
+            })
+            .collect()
+    }
+
+    /// Deserializer plugins configured for each topic of the current cluster.
+    pub fn topic_deserializers(&self) -> IndexMap<String, String> {
+        self.specific.config().deserializers.clone()
+    }
+
+    /// Resource limits of the search filters, by name.
+    pub fn filter_limits(&self) -> IndexMap<String, FilterLimits> {
+        self.workspace.config().filter_limits.clone()
+    }
+
+    /// Adds the search query to the history of the current cluster.
+    /// If the search query was already run against this cluster, the entry is moved to the end.
+    pub fn push_history(&mut self, prompt: &str, topics: &[String]) {
+        let cluster = self.cluster().to_string();
+        let history = &mut self.workspace.config.history;
+        history.retain(|e| e.query != prompt || e.cluster.as_deref() != Some(cluster.as_str()));
+        history.push(HistoryEntry::new(prompt, &cluster, topics));
+    }
+
+    /// Records the number of kafka records that matched the last run of the search query on the current cluster.
+    pub fn set_history_results(&mut self, prompt: &str, results: usize) {
+        let cluster = self.cluster().to_string();
+        if let Some(entry) = self
+            .workspace
+            .config
+            .history
+            .iter_mut()
+            .rev()
+            .find(|e| e.query == prompt && e.cluster.as_deref() == Some(cluster.as_str()))
+        {
+            entry.results = Some(results);
+        }
     }
 
     pub fn initial_query(&self) -> &str {
@@ -108,10 +169,7 @@ impl InternalConfig {
     }
 
     pub fn save_config(&mut self) -> Result<(), Error> {
-        let history = &self.workspace.config.history;
-        if history.len() > 1000 {
-            self.workspace.config.history = history.iter().skip(500).cloned().collect();
-        }
+        trim_history(&mut self.workspace.config.history);
         fs::write(
             &self.workspace.config.path,
             serde_json::to_string_pretty(&self.workspace.config)?,
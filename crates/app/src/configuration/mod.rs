@@ -5,8 +5,11 @@ use rdkafka::{ClientConfig, config::FromClientConfig};
 
 mod cluster_config;
 mod consumer_config;
+mod filter_limits;
 mod global_config;
+mod history;
 mod internal_config;
+mod saved_query;
 mod workspace;
 mod yozefu_config;
 
@@ -15,9 +18,12 @@ pub use cluster_config::KAFKA_PROPERTIES_WITH_LOCATIONS;
 pub use cluster_config::SENSITIVE_KAFKA_PROPERTIES;
 pub use cluster_config::SchemaRegistryConfig;
 pub use consumer_config::ConsumerConfig;
+pub use filter_limits::FilterLimits;
 pub use global_config::GlobalConfig;
 pub use global_config::TimestampFormat;
+pub use history::HistoryEntry;
 pub use internal_config::InternalConfig;
+pub use saved_query::SavedQuery;
 use tracing::debug;
 use tracing::enabled;
 pub use workspace::Workspace;
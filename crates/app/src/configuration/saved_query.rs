@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+/// A named search query, referenced in the query language with `@name`
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+pub struct SavedQuery {
+    /// The search query
+    pub query: String,
+    /// What the search query is about
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The topics the saved query is about.
+    /// They are consumed when the saved query is run with `--saved` and no topic is given.
+    /// In the TUI, the saved query is only suggested when one of them is selected.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub topics: Vec<String>,
+}
+
+impl SavedQuery {
+    /// Returns `true` when the saved query is about one of the given topics.
+    /// A saved query without topics is about any topic, and any saved query applies when no topic is given.
+    pub fn applies_to(&self, topics: &[String]) -> bool {
+        self.topics.is_empty()
+            || topics.is_empty()
+            || self.topics.iter().any(|t| topics.contains(t))
+    }
+}
+
+#[test]
+fn test_applies_to() {
+    let saved_query = SavedQuery {
+        query: "value.status == \"FAILED\"".to_string(),
+        description: None,
+        topics: vec!["payments".to_string()],
+    };
+    assert!(saved_query.applies_to(&[]));
+    assert!(saved_query.applies_to(&["orders".to_string(), "payments".to_string()]));
+    assert!(!saved_query.applies_to(&["orders".to_string()]));
+    assert!(
+        SavedQuery {
+            topics: vec![],
+            ..saved_query
+        }
+        .applies_to(&["orders".to_string()])
+    );
+}
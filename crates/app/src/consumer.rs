@@ -5,21 +5,27 @@ use std::time::Duration;
 
 use futures::{StreamExt, future};
 use futures_batch::TryChunksTimeoutStreamExt;
-use lib::{Error, SearchQuery, search::offset::FromOffset};
+use itertools::Itertools;
+use lib::{Error, SearchQuery};
 use rdkafka::{
-    Offset, TopicPartitionList,
+    TopicPartitionList,
     consumer::{Consumer as _, stream_consumer::StreamConsumer},
     message::BorrowedMessage,
 };
+use tracing::{info, warn};
 
 use crate::{
     configuration::{Configuration, ConsumerConfig, YozefuConfig},
-    search::Search,
+    search::{
+        Search,
+        planner::{QueryPlanner, StopOffsets},
+    },
 };
 
 pub struct Consumer {
     consumer_config: ConsumerConfig,
     consumer: StreamConsumer,
+    stop_offsets: StopOffsets,
 }
 
 impl Consumer {
@@ -30,12 +36,13 @@ impl Consumer {
         topics: &Vec<String>,
     ) -> Result<Self, Error> {
         let consumer: StreamConsumer = config.create_kafka_consumer()?;
-        let assignments = Self::create_assignments(&config, query, topics)?;
+        let (assignments, stop_offsets) = Self::create_assignments(&config, query, topics)?;
         consumer.assign(&assignments)?;
 
         Ok(Self {
             consumer_config,
             consumer,
+            stop_offsets,
         })
     }
 
@@ -65,6 +72,11 @@ impl Consumer {
         self.consumer
     }
 
+    /// Returns the offsets from which partitions can be stopped according to the search query.
+    pub fn stop_offsets(&self) -> StopOffsets {
+        self.stop_offsets.clone()
+    }
+
     pub fn assignment(&self) -> Result<TopicPartitionList, rdkafka::error::KafkaError> {
         self.consumer.assignment()
     }
@@ -73,34 +85,16 @@ impl Consumer {
         config: &YozefuConfig,
         query: SearchQuery,
         topics: &Vec<String>,
-    ) -> Result<TopicPartitionList, Error> {
-        let offset = query.offset().unwrap_or(FromOffset::End);
+    ) -> Result<(TopicPartitionList, StopOffsets), Error> {
+        let planner = QueryPlanner::new(&query);
+        let mut stop_offsets = StopOffsets::default();
         let mut assignments = TopicPartitionList::new();
         for topic in topics {
             let consumer: StreamConsumer = config.create_kafka_consumer()?;
             let metadata = consumer.fetch_metadata(Some(topic), Duration::from_secs(10))?;
-            let assignments_for_topic = match offset {
-                FromOffset::Beginning => {
-                    Self::assign_partitions(topic, &metadata, Offset::Beginning)
-                }
-                FromOffset::End => Self::assign_partitions(topic, &metadata, Offset::End),
-                FromOffset::Offset(o) => {
-                    Self::assign_partitions(topic, &metadata, Offset::Offset(o))
-                }
-                FromOffset::OffsetTail(o) => {
-                    Self::assign_partitions(topic, &metadata, Offset::OffsetTail(o))
-                }
-                FromOffset::Timestamp(timestamp) => {
-                    let mut assignments = TopicPartitionList::new();
-                    for m in metadata.topics() {
-                        for p in m.partitions() {
-                            assignments.add_partition(m.name(), p.id());
-                        }
-                    }
-                    assignments.set_all_offsets(Offset::Offset(timestamp))?;
-                    consumer.offsets_for_times(assignments, Duration::from_secs(60))?
-                }
-            };
+            let partitions = Self::partitions_to_consume(config, &query, topic, &metadata);
+            let assignments_for_topic =
+                planner.plan(&consumer, topic, &partitions, &mut stop_offsets)?;
 
             for elem in assignments_for_topic.elements() {
                 assignments
@@ -111,25 +105,50 @@ impl Consumer {
             }
         }
 
-        Ok(assignments)
+        if assignments.count() == 0 && !topics.is_empty() {
+            warn!(
+                "According to the search query, none of the partitions of [{}] can contain matching records",
+                topics.iter().join(", ")
+            );
+        }
+        stop_offsets.set_partitions(assignments.count());
+
+        Ok((assignments, stop_offsets))
     }
 
-    /// Assigns topics to a consumer
-    fn assign_partitions(
+    /// Returns the partitions of the topic that may contain records matching the search query.
+    /// Partitions that can't match, for instance because of a `partition == 3` predicate, are not consumed at all.
+    /// Unless the cluster uses a custom partitioner, a `key == "abc"` predicate restricts the consumption to the partition of the key.
+    /// This only happens when keys are plain strings: no schema registry, no deserializer plugin and no transformer.
+    fn partitions_to_consume(
+        config: &YozefuConfig,
+        query: &SearchQuery,
         topic: &str,
         metadata: &rdkafka::metadata::Metadata,
-        offset: Offset,
-    ) -> TopicPartitionList {
-        let mut assignments = TopicPartitionList::new();
-        for m in metadata.topics() {
-            for p in m.partitions() {
-                assignments
-                    .add_partition_offset(topic, p.id(), offset)
-                    .expect(
-                        "Failed to add partition to assignment in 'assign_partitions' function",
-                    );
-            }
+    ) -> Vec<i32> {
+        let partitions = metadata
+            .topics()
+            .iter()
+            .flat_map(|m| m.partitions().iter().map(|p| p.id()))
+            .collect_vec();
+        let cluster = config.config();
+        let default_partitioner = !cluster.custom_partitioner;
+        let raw_keys = cluster.schema_registry.is_none()
+            && !cluster.deserializers.contains_key(topic)
+            && !cluster.transforms.contains_key(topic)
+            && topic != "__consumer_offsets";
+        let partitions_to_consume =
+            query.partitions(topic, &partitions, default_partitioner, raw_keys);
+        if partitions_to_consume.len() != partitions.len() {
+            info!(
+                "Partitions [{}] of topic '{}' are skipped since they can't contain records matching the search query",
+                partitions
+                    .iter()
+                    .filter(|p| !partitions_to_consume.contains(p))
+                    .join(", "),
+                topic
+            );
         }
-        assignments
+        partitions_to_consume
     }
 }
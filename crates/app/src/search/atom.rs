@@ -1,6 +1,11 @@
-use lib::search::{atom::Atom, filter::Filter, offset::FromOffset};
+use lib::{
+    kafka::RecordFields,
+    search::{atom::Atom, filter::Filter, offset::FromOffset},
+};
 
-use super::{Search, SearchContext};
+use super::{PartitionContext, Search, SearchContext, cost, planner::TimestampRange};
+
+// References to saved queries are replaced with their definition by `ValidSearchQuery`.
 
 impl Search for Atom {
     fn offset(&self) -> Option<FromOffset> {
@@ -8,7 +13,16 @@ impl Search for Atom {
             Atom::Symbol(_) => None,
             Atom::Compare(c) => c.offset(),
             Atom::Parenthesis(c) => c.offset(),
-            Atom::Filter(_) => None,
+            Atom::Filter(_) | Atom::SavedQuery(_) => None,
+        }
+    }
+
+    fn timestamp_range(&self) -> TimestampRange {
+        match self {
+            Atom::Symbol(_) => TimestampRange::empty(),
+            Atom::Compare(e) => e.timestamp_range(),
+            Atom::Parenthesis(e) => e.timestamp_range(),
+            Atom::Filter(_) | Atom::SavedQuery(_) => TimestampRange::default(),
         }
     }
 
@@ -18,15 +32,50 @@ impl Search for Atom {
             Atom::Compare(e) => e.matches(context),
             Atom::Parenthesis(e) => e.matches(context),
             Atom::Filter(f) => f.matches(context),
+            Atom::SavedQuery(_) => false,
+        }
+    }
+
+    fn matches_partition(&self, context: &PartitionContext) -> Option<bool> {
+        match self {
+            Atom::Symbol(_) => Some(false),
+            Atom::Compare(e) => e.matches_partition(context),
+            Atom::Parenthesis(e) => e.matches_partition(context),
+            Atom::Filter(_) | Atom::SavedQuery(_) => None,
         }
     }
 
     fn filters(&self) -> Vec<Filter> {
         match self {
-            Atom::Symbol(_) => vec![],
+            Atom::Symbol(_) | Atom::SavedQuery(_) => vec![],
             Atom::Compare(e) => e.filters(),
             Atom::Parenthesis(e) => e.filters(),
             Atom::Filter(f) => vec![f.clone()],
         }
     }
+
+    fn cost(&self) -> u64 {
+        match self {
+            Atom::Symbol(_) | Atom::SavedQuery(_) => cost::METADATA_COST,
+            Atom::Compare(e) => e.cost(),
+            Atom::Parenthesis(e) => e.cost(),
+            Atom::Filter(f) => f.cost(),
+        }
+    }
+
+    fn fields(&self) -> RecordFields {
+        match self {
+            Atom::Symbol(_) | Atom::SavedQuery(_) => RecordFields::NONE,
+            Atom::Compare(e) => e.fields(),
+            Atom::Parenthesis(e) => e.fields(),
+            Atom::Filter(f) => f.fields(),
+        }
+    }
+
+    fn matches_metadata(&self, context: &SearchContext) -> Option<bool> {
+        match self {
+            Atom::Parenthesis(e) => e.matches_metadata(context),
+            _ => self.fields().is_empty().then(|| self.matches(context)),
+        }
+    }
 }
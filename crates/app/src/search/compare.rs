@@ -1,14 +1,16 @@
 use lib::{
-    kafka::Comparable,
+    kafka::{Comparable, RecordFields},
     search::{
         compare::{CompareExpression, NumberOperator, StringOperator},
         filter::Filter,
+        function::{Function, FunctionValue},
         offset::FromOffset,
+        symbol::Symbol,
     },
 };
 
-use super::SearchContext;
-use crate::search::Search;
+use super::{PartitionContext, SearchContext, cost, planner::TimestampRange};
+use crate::search::{Search, partitioner::default_partition};
 
 impl Search for CompareExpression {
     fn offset(&self) -> Option<FromOffset> {
@@ -39,14 +41,7 @@ impl Search for CompareExpression {
         let record = context.record;
         match self {
             CompareExpression::OffsetTail(_) => true,
-            CompareExpression::Partition(op, p) => match op {
-                &NumberOperator::GreaterThan => record.partition > *p,
-                NumberOperator::GreaterOrEqual => record.partition >= *p,
-                NumberOperator::LowerThan => record.partition < *p,
-                NumberOperator::LowerOrEqual => record.partition <= *p,
-                NumberOperator::Equal => record.partition == *p,
-                NumberOperator::NotEqual => record.partition != *p,
-            },
+            CompareExpression::Partition(op, p) => compare_number(op, record.partition, *p),
             CompareExpression::Offset(op, p) => match op {
                 NumberOperator::GreaterThan => record.offset > *p,
                 NumberOperator::GreaterOrEqual => record.offset >= *p,
@@ -55,12 +50,7 @@ impl Search for CompareExpression {
                 NumberOperator::Equal => record.offset == *p,
                 NumberOperator::NotEqual => record.offset != *p,
             },
-            CompareExpression::Topic(op, t) => match op {
-                StringOperator::Equal => record.topic == *t,
-                StringOperator::NotEqual => record.topic != *t,
-                StringOperator::Contain => record.topic.contains(t),
-                StringOperator::StartWith => record.topic.starts_with(t),
-            },
+            CompareExpression::Topic(op, t) => compare_string(op, &record.topic, t),
             CompareExpression::Size(op, s) => match op {
                 NumberOperator::GreaterThan => record.size > *s,
                 NumberOperator::GreaterOrEqual => record.size >= *s,
@@ -100,19 +90,132 @@ impl Search for CompareExpression {
                 let ts = record.timestamp_as_local_date_time().unwrap();
                 from <= &ts && &ts <= to
             }
+            CompareExpression::StringFunction(function, op, t) => match function.evaluate(record) {
+                Some(FunctionValue::String(s)) => compare_string(op, &s, t),
+                _ => false,
+            },
+            CompareExpression::NumberFunction(function, op, n) => match function.evaluate(record) {
+                Some(FunctionValue::Number(v)) => compare_number(op, v, *n),
+                _ => false,
+            },
+        }
+    }
+
+    fn matches_partition(&self, context: &PartitionContext) -> Option<bool> {
+        match self {
+            CompareExpression::Partition(op, p) => Some(compare_number(op, context.partition, *p)),
+            CompareExpression::Topic(op, t) => Some(compare_string(op, context.topic, t)),
+            // The partition is computed from the current number of partitions, records produced
+            // before partitions were added may be elsewhere: `custom_partitioner` disables the pruning.
+            CompareExpression::Key(StringOperator::Equal, key)
+                if context.default_partitioner && context.raw_keys && is_raw_key(key) =>
+            {
+                Some(default_partition(key.as_bytes(), context.partitions) == context.partition)
+            }
+            _ => None,
+        }
+    }
+
+    fn timestamp_range(&self) -> TimestampRange {
+        match self {
+            CompareExpression::Timestamp(op, t) => {
+                let t = t.timestamp_millis();
+                match op {
+                    NumberOperator::GreaterThan | NumberOperator::GreaterOrEqual => {
+                        TimestampRange::new(Some(t), None)
+                    }
+                    NumberOperator::LowerThan | NumberOperator::LowerOrEqual => {
+                        TimestampRange::new(None, Some(t))
+                    }
+                    NumberOperator::Equal => TimestampRange::new(Some(t), Some(t)),
+                    NumberOperator::NotEqual => TimestampRange::default(),
+                }
+            }
+            CompareExpression::TimestampBetween(from, to) => {
+                TimestampRange::new(Some(from.timestamp_millis()), Some(to.timestamp_millis()))
+            }
+            _ => TimestampRange::default(),
         }
     }
 
     fn filters(&self) -> Vec<Filter> {
         vec![]
     }
+
+    fn cost(&self) -> u64 {
+        match self {
+            CompareExpression::Key(_, _) | CompareExpression::Value(None, _, _) => {
+                cost::STRING_COST
+            }
+            CompareExpression::Value(Some(_), _, _) => cost::JSON_PATH_COST,
+            CompareExpression::StringFunction(function, _, _)
+            | CompareExpression::NumberFunction(function, _, _) => {
+                match function_fields(function).is_empty() {
+                    true => cost::METADATA_COST,
+                    false => cost::JSON_PATH_COST,
+                }
+            }
+            _ => cost::METADATA_COST,
+        }
+    }
+
+    fn fields(&self) -> RecordFields {
+        match self {
+            CompareExpression::Key(_, _) => RecordFields::KEY,
+            CompareExpression::Value(_, _, _) => RecordFields::VALUE,
+            CompareExpression::StringFunction(function, _, _)
+            | CompareExpression::NumberFunction(function, _, _) => function_fields(function),
+            _ => RecordFields::NONE,
+        }
+    }
+}
+
+/// Returns the fields the argument of the function reads.
+fn function_fields(function: &Function) -> RecordFields {
+    match function {
+        Function::Lower(symbol)
+        | Function::Upper(symbol)
+        | Function::Length(symbol)
+        | Function::JsonType(symbol) => match symbol {
+            Symbol::Key => RecordFields::KEY,
+            Symbol::Value(_) => RecordFields::VALUE,
+            _ => RecordFields::NONE,
+        },
+        Function::HeaderCount => RecordFields::NONE,
+    }
+}
+
+/// Returns `true` when the records whose key equals `key` have exactly the bytes of `key` as key.
+/// A key that is valid JSON is compared once re-serialized, its bytes may differ from the ones hashed by the producer.
+/// An empty key may be a null key or a key that is not valid UTF-8.
+fn is_raw_key(key: &str) -> bool {
+    !key.is_empty() && serde_json::from_str::<serde_json::Value>(key).is_err()
+}
+
+fn compare_number<T: PartialOrd>(op: &NumberOperator, left: T, right: T) -> bool {
+    match op {
+        NumberOperator::GreaterThan => left > right,
+        NumberOperator::GreaterOrEqual => left >= right,
+        NumberOperator::LowerThan => left < right,
+        NumberOperator::LowerOrEqual => left <= right,
+        NumberOperator::Equal => left == right,
+        NumberOperator::NotEqual => left != right,
+    }
+}
+
+fn compare_string(op: &StringOperator, left: &str, right: &str) -> bool {
+    match op {
+        StringOperator::Equal => left == right,
+        StringOperator::NotEqual => left != right,
+        StringOperator::Contain => left.contains(right),
+        StringOperator::StartWith => left.starts_with(right),
+    }
 }
 
 #[test]
 fn test_matches() {
-    use crate::search::filter::CACHED_FILTERS;
     use lib::kafka::KafkaRecord;
-    use std::path::PathBuf;
+    use std::path::Path;
 
     let compare = CompareExpression::Offset(NumberOperator::Equal, 42);
     let record = KafkaRecord {
@@ -128,12 +231,9 @@ fn test_matches() {
         size: 12,
         key_as_string: "key".to_string(),
         value_as_string: "value".to_string(),
+        tombstone: false,
     };
-    let context = SearchContext {
-        record: &record,
-        filters: &CACHED_FILTERS,
-        filters_directory: PathBuf::from("."),
-    };
+    let context = SearchContext::new(&record, Path::new("."));
 
     assert!(compare.matches(&context))
 }
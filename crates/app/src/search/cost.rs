@@ -0,0 +1,177 @@
+//! Cost-based ordering of the predicates of a search query.
+//!
+//! `and` and `or` short-circuit, so the cheapest predicates of a group are evaluated first:
+//! metadata of the record, then string comparisons on the key and the value,
+//! then JSON paths and functions, and finally search filters that call a WASM module.
+//! A search filter that fails is `false`, so reordering predicates doesn't change the result of the search query.
+//! Search filters are therefore not called for the records that cheaper predicates already reject,
+//! whatever the order they are written in.
+
+use std::fmt::Write;
+
+use lib::{
+    SearchQuery,
+    search::{
+        atom::Atom,
+        expression::{AndExpression, Expression, OrExpression},
+        term::Term,
+    },
+};
+
+use super::Search;
+
+/// Cost of a predicate on the metadata of the record: topic, partition, offset, timestamp, size or headers.
+pub const METADATA_COST: u64 = 1;
+/// Cost of a string comparison on the key or the value.
+pub const STRING_COST: u64 = 10;
+/// Cost of walking a JSON path of the value or of calling a built-in function.
+pub const JSON_PATH_COST: u64 = 20;
+/// Cost of calling a search filter, a WASM module.
+pub const FILTER_COST: u64 = 1000;
+
+/// Returns the expression with the predicates of every `and` and `or` group ordered by cost.
+/// Predicates with the same cost keep the order they were written in, see [`sort_by_cost`].
+pub fn order_by_cost(expression: Expression) -> Expression {
+    match expression {
+        OrExpression::OrTerm(e) => OrExpression::OrTerm(order_and_expression(e)),
+        OrExpression::OrExpression(e) => {
+            let mut e = e.into_iter().map(order_and_expression).collect::<Vec<_>>();
+            sort_by_cost(&mut e);
+            OrExpression::OrExpression(e)
+        }
+    }
+}
+
+fn order_and_expression(expression: AndExpression) -> AndExpression {
+    match expression {
+        AndExpression::AndTerm(t) => AndExpression::AndTerm(order_term(t)),
+        AndExpression::AndExpression(t) => {
+            let mut t = t.into_iter().map(order_term).collect::<Vec<_>>();
+            sort_by_cost(&mut t);
+            AndExpression::AndExpression(t)
+        }
+    }
+}
+
+/// Sorts predicates by cost, the sort is stable.
+fn sort_by_cost<T: Search>(predicates: &mut [T]) {
+    predicates.sort_by_cached_key(Search::cost);
+}
+
+fn order_term(term: Term) -> Term {
+    match term {
+        Term::Not(atom) => Term::Not(order_atom(atom)),
+        Term::Atom(atom) => Term::Atom(order_atom(atom)),
+    }
+}
+
+fn order_atom(atom: Atom) -> Atom {
+    match atom {
+        Atom::Parenthesis(e) => Atom::Parenthesis(Box::new(order_by_cost(*e))),
+        atom => atom,
+    }
+}
+
+/// Describes how the search query is evaluated: the predicates in evaluation order with their cost,
+/// and the fields of the records that are deserialized.
+pub fn explain(query: &SearchQuery, plan: &Expression) -> String {
+    let fields = plan.fields();
+    let fields = match (fields.key, fields.value) {
+        (true, true) => "key, value",
+        (true, false) => "key",
+        (false, true) => "value",
+        (false, false) => "none, only records matching the search query are deserialized",
+    };
+    let mut output = String::new();
+    let _ = writeln!(output, "Search query: {query}");
+    let _ = writeln!(output, "Deserialized fields: {fields}");
+    let _ = writeln!(output, "Evaluation plan:");
+    explain_expression(plan, 1, &mut output);
+    output
+}
+
+fn explain_expression(expression: &Expression, depth: usize, output: &mut String) {
+    match expression {
+        OrExpression::OrTerm(e) => explain_and_expression(e, depth, output),
+        OrExpression::OrExpression(e) if e.is_empty() => line("true", 0, depth, output),
+        OrExpression::OrExpression(e) => {
+            line("or", expression.cost(), depth, output);
+            for ee in e {
+                explain_and_expression(ee, depth + 1, output);
+            }
+        }
+    }
+}
+
+fn explain_and_expression(expression: &AndExpression, depth: usize, output: &mut String) {
+    match expression {
+        AndExpression::AndTerm(t) => explain_term(t, depth, output),
+        AndExpression::AndExpression(t) => {
+            line("and", expression.cost(), depth, output);
+            for tt in t {
+                explain_term(tt, depth + 1, output);
+            }
+        }
+    }
+}
+
+fn explain_term(term: &Term, depth: usize, output: &mut String) {
+    match term {
+        Term::Not(Atom::Parenthesis(e)) => {
+            line("not", term.cost(), depth, output);
+            explain_expression(e, depth + 1, output);
+        }
+        Term::Atom(Atom::Parenthesis(e)) => explain_expression(e, depth, output),
+        term => line(&term.to_string(), term.cost(), depth, output),
+    }
+}
+
+fn line(label: &str, cost: u64, depth: usize, output: &mut String) {
+    let indent = "  ".repeat(depth);
+    let _ = writeln!(output, "{indent}{label}  [cost {cost}]");
+}
+
+#[test]
+fn test_order_by_cost() {
+    let (_, query) = SearchQuery::parse(
+        r#"value.status == "FAILED" and key contains "a" and partition == 1 or (value contains "b" and offset > 10)"#,
+    )
+    .unwrap();
+    assert_eq!(
+        order_by_cost(query.expression).to_string(),
+        r#"(offset > 10 && value contains "b") || partition == 1 && key contains "a" && value.status == "FAILED""#
+    );
+}
+
+#[test]
+fn test_order_by_cost_moves_filters_last() {
+    let (_, query) = SearchQuery::parse(
+        r#"value.status == "FAILED" and partition == 1 and count_calls() and key contains "a" and offset > 10 and (first() or partition == 2)"#,
+    )
+    .unwrap();
+    assert_eq!(
+        order_by_cost(query.expression).to_string(),
+        r#"partition == 1 && offset > 10 && key contains "a" && value.status == "FAILED" && count_calls() && (partition == 2 || first())"#
+    );
+}
+
+#[test]
+fn test_explain() {
+    let (_, query) =
+        SearchQuery::parse(r#"value.status == "FAILED" and !(partition == 1 or topic == "a")"#)
+            .unwrap();
+    let plan = order_by_cost(query.expression.clone());
+    assert_eq!(
+        explain(&query, &plan),
+        r#"Search query: value.status == "FAILED" && !(partition == 1 || topic == "a") order by timestamp asc
+Deserialized fields: value
+Evaluation plan:
+  and  [cost 22]
+    not  [cost 2]
+      or  [cost 2]
+        partition == 1  [cost 1]
+        topic == "a"  [cost 1]
+    value.status == "FAILED"  [cost 20]
+"#
+    );
+}
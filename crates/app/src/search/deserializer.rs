@@ -0,0 +1,139 @@
+//! Deserializer plugins decode the keys and the values of topics using a format no schema registry knows about.
+//! A deserializer is a wasm module, imported like a search filter, that exports a `deserialize` function:
+//! it receives a [`DeserializerInput`] and returns either a JSON document or a string.
+//!
+//! Deserializers are configured per topic in the cluster configuration.
+
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use indexmap::IndexMap;
+use lib::{
+    DataType,
+    kafka::{DeserializerInput, PayloadDeserializer},
+};
+
+use super::filter::{DESERIALIZE_FUNCTION_NAME, FilterPool, ModuleKind, load_filter};
+use crate::configuration::FilterLimits;
+
+/// A deserializer plugin.
+#[derive(Clone)]
+pub struct WasmDeserializer {
+    pool: Arc<FilterPool>,
+}
+
+impl WasmDeserializer {
+    fn load(
+        name: &str,
+        filters_directory: &Path,
+        filter_limits: &IndexMap<String, FilterLimits>,
+    ) -> Result<Self, lib::Error> {
+        let limits = filter_limits.get(name).cloned().unwrap_or_default();
+        let pool = load_filter(name, filters_directory, &limits, ModuleKind::Deserializer)?;
+        if !pool.function_exists(DESERIALIZE_FUNCTION_NAME) {
+            return Err(lib::Error::Error(format!(
+                "'{name}' is not a deserializer, the wasm module doesn't export a '{DESERIALIZE_FUNCTION_NAME}' function"
+            )));
+        }
+        Ok(Self { pool })
+    }
+}
+
+impl PayloadDeserializer for WasmDeserializer {
+    /// The output of the plugin is parsed as JSON, it is a string otherwise.
+    fn deserialize(&self, input: &DeserializerInput) -> Result<DataType, lib::Error> {
+        let input = serde_json::to_vec(input)?;
+        let output = self
+            .pool
+            .deserialize(&input)
+            .map_err(|e| lib::Error::Error(e.to_string()))?;
+        Ok(match serde_json::from_slice(&output) {
+            Ok(json) => DataType::Json(json),
+            Err(_) => DataType::String(String::from_utf8_lossy(&output).to_string()),
+        })
+    }
+}
+
+/// Deserializer plugins, by topic.
+#[derive(Clone, Default)]
+pub struct Deserializers {
+    by_topic: HashMap<String, WasmDeserializer>,
+}
+
+impl Deserializers {
+    /// Loads the deserializer plugins configured for each topic.
+    pub fn load(
+        by_topic: &IndexMap<String, String>,
+        filters_directory: &Path,
+        filter_limits: &IndexMap<String, FilterLimits>,
+    ) -> Result<Self, lib::Error> {
+        Ok(Self {
+            by_topic: by_topic
+                .iter()
+                .map(|(topic, name)| {
+                    Ok((
+                        topic.clone(),
+                        WasmDeserializer::load(name, filters_directory, filter_limits)?,
+                    ))
+                })
+                .collect::<Result<_, lib::Error>>()?,
+        })
+    }
+
+    /// Returns the deserializer of the topic, if any.
+    pub fn of(&self, topic: &str) -> Option<&dyn PayloadDeserializer> {
+        self.by_topic
+            .get(topic)
+            .map(|d| d as &dyn PayloadDeserializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, path::PathBuf};
+
+    use super::*;
+    use crate::search::wasm_fixtures::{self, FiltersDirectory};
+
+    #[test]
+    fn test_wasm_deserializer() {
+        let directory = FiltersDirectory::new();
+        directory.add(
+            "echo-deserializer-test",
+            &wasm_fixtures::echo(DESERIALIZE_FUNCTION_NAME),
+        );
+        let deserializers = Deserializers::load(
+            &IndexMap::from([("legacy".to_string(), "echo-deserializer-test".to_string())]),
+            directory.path(),
+            &IndexMap::new(),
+        )
+        .unwrap();
+        assert!(deserializers.of("payments").is_none());
+
+        let input = DeserializerInput {
+            topic: "legacy".to_string(),
+            field: "value".to_string(),
+            payload: vec![1, 2],
+            headers: BTreeMap::new(),
+        };
+        let data = deserializers.of("legacy").unwrap().deserialize(&input);
+        assert_eq!(
+            data.unwrap(),
+            DataType::Json(serde_json::to_value(&input).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_search_filter_is_not_a_deserializer() {
+        let directory = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("http_search_filter");
+        assert!(
+            Deserializers::load(
+                &IndexMap::from([("legacy".to_string(), "module".to_string())]),
+                &directory,
+                &IndexMap::new()
+            )
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,358 @@
+//! Search engine deserializing kafka records and matching them against the search query on several cores.
+//! Workers are dedicated threads, so this CPU-bound work doesn't starve the tasks of the tokio runtime.
+//!
+//! Messages are dispatched to workers by topic and partition:
+//! records of a same partition are always evaluated by the same worker, in the order they were consumed.
+//! Workers share the cache of the schema registry client and the session of the search, see [`SearchSession`].
+//!
+//! Deserialization is driven by the search query: predicates on the metadata of the record
+//! (topic, partition, offset, timestamp, headers, size) are evaluated first.
+//! The key and the value are only deserialized when the search query reads them or when the record matches.
+//! Records of a topic with transformers are fully deserialized and rewritten before they are matched,
+//! a record that cannot be rewritten is excluded.
+//! Records of a topic with a deserializer plugin are deserialized by the plugin instead of the schema registry.
+//! With `latest by key`, records that don't match are sent as well since they may replace the latest record of their key.
+
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use lib::{
+    Error, KafkaRecord,
+    kafka::{RecordFields, SchemaRegistryClient},
+};
+use rdkafka::{Message, message::OwnedMessage};
+use tokio::{
+    runtime::Handle,
+    sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+};
+use tracing::trace_span;
+
+use super::{
+    Search, SearchContext, deserializer::Deserializers, host_functions::SearchSession,
+    transform::Transforms,
+};
+
+/// A record evaluated by a worker of the search engine.
+/// `record` is `None` when the record doesn't match the search query,
+/// unless the search query keeps the latest record of each key: every record is then sent, with its key deserialized.
+#[derive(Debug)]
+pub struct Evaluation {
+    pub record: Option<KafkaRecord>,
+    pub matches: bool,
+}
+
+/// Number of records evaluated by the search engine since it started.
+#[derive(Debug)]
+pub struct Throughput {
+    read: AtomicUsize,
+    matched: AtomicUsize,
+    started_at: Instant,
+}
+
+impl Throughput {
+    fn new() -> Self {
+        Self {
+            read: AtomicUsize::new(0),
+            matched: AtomicUsize::new(0),
+            started_at: Instant::now(),
+        }
+    }
+
+    /// Number of records deserialized and matched against the search query.
+    pub fn read(&self) -> usize {
+        self.read.load(Ordering::Relaxed)
+    }
+
+    /// Number of records matching the search query.
+    pub fn matched(&self) -> usize {
+        self.matched.load(Ordering::Relaxed)
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    /// Number of records evaluated per second.
+    pub fn records_per_second(&self) -> u64 {
+        let elapsed = self.elapsed().as_millis().max(1);
+        u64::try_from(self.read() as u128 * 1000 / elapsed).unwrap_or(u64::MAX)
+    }
+}
+
+/// A pool of workers deserializing kafka records and matching them against the search query.
+/// Workers stop once the engine is dropped or the receiver of evaluations is closed.
+#[derive(Debug)]
+pub struct SearchEngine {
+    workers: Vec<UnboundedSender<OwnedMessage>>,
+    throughput: Arc<Throughput>,
+}
+
+impl SearchEngine {
+    /// Spawns `workers` workers, each one on its own thread since deserializing and matching records is CPU-bound.
+    /// Workers await the schema registry on the current tokio runtime.
+    /// Evaluations are sent to the returned receiver.
+    /// It fails when a worker cannot be spawned or when it's not called from a tokio runtime.
+    pub fn start<S>(
+        search_query: S,
+        filters_directory: PathBuf,
+        schema_registry: Option<SchemaRegistryClient>,
+        transforms: Transforms,
+        deserializers: Deserializers,
+        workers: usize,
+    ) -> Result<(Self, UnboundedReceiver<Evaluation>), Error>
+    where
+        S: Search + Send + Sync + 'static,
+    {
+        // Each search has its own session, the previous search may still be running
+        let session = Arc::new(SearchSession::new(schema_registry.clone()));
+        let fields = search_query.fields();
+        let latest_by_key = search_query.latest_by_key();
+        let search_query = Arc::new(search_query);
+        let filters_directory = Arc::new(filters_directory);
+        let transforms = Arc::new(transforms);
+        let deserializers = Arc::new(deserializers);
+        let throughput = Arc::new(Throughput::new());
+        let (tx_evaluations, rx_evaluations) = mpsc::unbounded_channel();
+
+        let runtime = Handle::try_current().ok();
+        let workers = (0..workers.max(1))
+            .map(|id| {
+                let (tx, mut rx) = mpsc::unbounded_channel::<OwnedMessage>();
+                let runtime = runtime.clone().ok_or(Error::Error(
+                    "The search engine needs a tokio runtime".to_string(),
+                ))?;
+                let search_query = search_query.clone();
+                let filters_directory = filters_directory.clone();
+                let transforms = transforms.clone();
+                let deserializers = deserializers.clone();
+                let mut schema_registry = schema_registry.clone();
+                let throughput = throughput.clone();
+                let session = session.clone();
+                let tx_evaluations = tx_evaluations.clone();
+                thread::Builder::new()
+                    .name(format!("search-worker-{id}"))
+                    .spawn(move || {
+                        while let Some(message) = rx.blocking_recv() {
+                            let mut record = KafkaRecord::parse_metadata(&message);
+                            let span = trace_span!("matching", offset = %record.offset, partition = %record.partition, topic = %record.topic);
+                            let transformed = transforms.applies_to(&record.topic);
+                            let deserializer = deserializers.of(&record.topic);
+                            let metadata_matches = match transformed {
+                                // Transformers may rewrite the metadata as well
+                                true => None,
+                                false => {
+                                    let context = SearchContext::new(&record, &filters_directory).with_session(&session);
+                                    let _search_span = span.enter();
+                                    search_query.matches_metadata(&context)
+                                }
+                            };
+                            let mut deserialized = RecordFields::NONE;
+                            let mut excluded = false;
+                            let matches = match metadata_matches {
+                                Some(matches) => matches,
+                                None => {
+                                    deserialized = match transformed {
+                                        true => RecordFields::ALL,
+                                        false => fields,
+                                    };
+                                    // Only fetching schemas awaits, deserializing runs on this thread
+                                    runtime.block_on(record.deserialize_fields(
+                                        &message,
+                                        &mut schema_registry,
+                                        deserializer,
+                                        deserialized,
+                                    ));
+                                    if transformed {
+                                        match transforms.apply(&record, Some(&session)) {
+                                            Ok(transformed) => record = transformed,
+                                            // The record can't be displayed as the user expects it
+                                            Err(_) => excluded = true,
+                                        }
+                                    }
+                                    let context = SearchContext::new(&record, &filters_directory).with_session(&session);
+                                    let _search_span = span.enter();
+                                    !excluded && search_query.matches(&context)
+                                }
+                            };
+                            if matches {
+                                // Matching records are displayed, they must be fully deserialized.
+                                runtime.block_on(record.deserialize_fields(
+                                    &message,
+                                    &mut schema_registry,
+                                    deserializer,
+                                    RecordFields::ALL.difference(deserialized),
+                                ));
+                            }
+                            if !matches && latest_by_key && !excluded {
+                                // The key tells which record this one replaces
+                                runtime.block_on(record.deserialize_fields(
+                                    &message,
+                                    &mut schema_registry,
+                                    deserializer,
+                                    RecordFields::KEY.difference(deserialized),
+                                ));
+                            }
+                            throughput.read.fetch_add(1, Ordering::Relaxed);
+                            if matches {
+                                throughput.matched.fetch_add(1, Ordering::Relaxed);
+                            }
+                            let evaluation = Evaluation {
+                                record: (matches || latest_by_key && !excluded).then_some(record),
+                                matches,
+                            };
+                            if tx_evaluations.send(evaluation).is_err() {
+                                return;
+                            }
+                        }
+                    })
+                    .map(|_| tx)
+                    .map_err(|e| Error::Error(format!("Cannot spawn search worker {id}: {e}")))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok((
+            Self {
+                workers,
+                throughput,
+            },
+            rx_evaluations,
+        ))
+    }
+
+    /// Sends a message to the worker in charge of its topic partition.
+    pub fn send(&self, message: OwnedMessage) -> Result<(), Error> {
+        let worker = self.worker_of(message.topic(), message.partition());
+        self.workers
+            .get(worker)
+            .ok_or(Error::Error("The search engine has no worker".to_string()))?
+            .send(message)
+            .map_err(|_| Error::Error("The search engine is stopped".to_string()))
+    }
+
+    /// Number of workers of the search engine.
+    pub fn workers(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Returns the throughput of the search engine, it can be read while the engine is running.
+    pub fn throughput(&self) -> Arc<Throughput> {
+        self.throughput.clone()
+    }
+
+    fn worker_of(&self, topic: &str, partition: i32) -> usize {
+        let mut hasher = DefaultHasher::new();
+        (topic, partition).hash(&mut hasher);
+        usize::try_from(hasher.finish() % self.workers.len().max(1) as u64).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lib::SearchQuery;
+    use rdkafka::Timestamp;
+
+    use super::*;
+
+    fn message(partition: i32, offset: i64) -> OwnedMessage {
+        OwnedMessage::new(
+            Some(format!("{offset}").into_bytes()),
+            Some(format!("key-{offset}").into_bytes()),
+            "payments".to_string(),
+            Timestamp::NotAvailable,
+            partition,
+            offset,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_search_engine_needs_a_runtime() {
+        let (_, query) = SearchQuery::parse("offset < 100").unwrap();
+        let engine = SearchEngine::start(
+            query,
+            PathBuf::from("tests/filters"),
+            None,
+            Transforms::default(),
+            Deserializers::default(),
+            2,
+        );
+        assert!(engine.is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_search_engine_keeps_partition_order() {
+        let (_, query) = SearchQuery::parse("offset < 100").unwrap();
+        let (engine, mut rx) = SearchEngine::start(
+            query,
+            PathBuf::from("tests/filters"),
+            None,
+            Transforms::default(),
+            Deserializers::default(),
+            4,
+        )
+        .unwrap();
+        assert_eq!(engine.workers(), 4);
+        let throughput = engine.throughput();
+        for offset in 0..200 {
+            for partition in 0..3 {
+                engine.send(message(partition, offset)).unwrap();
+            }
+        }
+        drop(engine);
+
+        let mut offsets: Vec<Vec<i64>> = vec![vec![]; 3];
+        let mut read = 0;
+        while let Some(evaluation) = rx.recv().await {
+            read += 1;
+            if let Some(record) = evaluation.record {
+                // Matching records are fully deserialized, even if the search query only reads the offset
+                assert_eq!(record.key_as_string, format!("key-{}", record.offset));
+                offsets[record.partition as usize].push(record.offset);
+            }
+        }
+        assert_eq!(read, 600);
+        assert_eq!(throughput.read(), 600);
+        assert_eq!(throughput.matched(), 300);
+        for offsets in offsets {
+            assert_eq!(offsets, (0..100).collect::<Vec<_>>());
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_search_engine_shares_the_store_of_a_search() {
+        use crate::search::{ValidSearchQuery, wasm_fixtures};
+
+        let directory = wasm_fixtures::FiltersDirectory::new();
+        directory.add("first-seen", &wasm_fixtures::first_seen());
+        for _ in 0..2 {
+            let query = ValidSearchQuery::from("first-seen()", directory.path()).unwrap();
+            let (engine, mut rx) = SearchEngine::start(
+                query,
+                directory.path().to_path_buf(),
+                None,
+                Transforms::default(),
+                Deserializers::default(),
+                3,
+            )
+            .unwrap();
+            for partition in 0..3 {
+                engine.send(message(partition, 0)).unwrap();
+            }
+            drop(engine);
+            let mut matched = 0;
+            while let Some(evaluation) = rx.recv().await {
+                matched += usize::from(evaluation.matches);
+            }
+            // The store is shared by the partitions, a new search starts with an empty store
+            assert_eq!(matched, 1);
+        }
+    }
+}
@@ -1,10 +1,13 @@
-use lib::search::{
-    expression::{AndExpression, OrExpression},
-    filter::Filter,
-    offset::FromOffset,
+use lib::{
+    kafka::RecordFields,
+    search::{
+        expression::{AndExpression, OrExpression},
+        filter::Filter,
+        offset::FromOffset,
+    },
 };
 
-use super::{Search, SearchContext};
+use super::{PartitionContext, Search, SearchContext, planner::TimestampRange};
 
 impl Search for AndExpression {
     fn offset(&self) -> Option<FromOffset> {
@@ -37,12 +40,71 @@ impl Search for AndExpression {
         }
     }
 
+    fn matches_partition(&self, context: &PartitionContext) -> Option<bool> {
+        match self {
+            Self::AndTerm(t) => t.matches_partition(context),
+            Self::AndExpression(e) => {
+                let mut result = Some(true);
+                for ee in e {
+                    match ee.matches_partition(context) {
+                        Some(false) => return Some(false),
+                        None => result = None,
+                        Some(true) => (),
+                    }
+                }
+                result
+            }
+        }
+    }
+
+    fn timestamp_range(&self) -> TimestampRange {
+        match self {
+            Self::AndTerm(t) => t.timestamp_range(),
+            Self::AndExpression(e) => e.iter().fold(TimestampRange::default(), |range, ee| {
+                range.intersection(ee.timestamp_range())
+            }),
+        }
+    }
+
     fn filters(&self) -> Vec<Filter> {
         match self {
             AndExpression::AndTerm(term) => term.filters(),
             AndExpression::AndExpression(vec) => vec.iter().flat_map(Search::filters).collect(),
         }
     }
+
+    fn cost(&self) -> u64 {
+        match self {
+            AndExpression::AndTerm(term) => term.cost(),
+            AndExpression::AndExpression(vec) => vec.iter().map(Search::cost).sum(),
+        }
+    }
+
+    fn fields(&self) -> RecordFields {
+        match self {
+            AndExpression::AndTerm(term) => term.fields(),
+            AndExpression::AndExpression(vec) => vec
+                .iter()
+                .fold(RecordFields::NONE, |fields, t| fields.union(t.fields())),
+        }
+    }
+
+    fn matches_metadata(&self, context: &SearchContext) -> Option<bool> {
+        match self {
+            Self::AndTerm(t) => t.matches_metadata(context),
+            Self::AndExpression(e) => {
+                let mut result = Some(true);
+                for ee in e {
+                    match ee.matches_metadata(context) {
+                        Some(false) => return Some(false),
+                        None => result = None,
+                        Some(true) => (),
+                    }
+                }
+                result
+            }
+        }
+    }
 }
 
 impl Search for OrExpression {
@@ -70,10 +132,76 @@ impl Search for OrExpression {
         }
     }
 
+    fn matches_partition(&self, context: &PartitionContext) -> Option<bool> {
+        match self {
+            Self::OrTerm(t) => t.matches_partition(context),
+            Self::OrExpression(e) => {
+                if e.is_empty() {
+                    return Some(true);
+                }
+                let mut result = Some(false);
+                for ee in e {
+                    match ee.matches_partition(context) {
+                        Some(true) => return Some(true),
+                        None => result = None,
+                        Some(false) => (),
+                    }
+                }
+                result
+            }
+        }
+    }
+
+    fn timestamp_range(&self) -> TimestampRange {
+        match self {
+            Self::OrTerm(t) => t.timestamp_range(),
+            Self::OrExpression(e) if e.is_empty() => TimestampRange::default(),
+            Self::OrExpression(e) => e.iter().fold(TimestampRange::empty(), |range, ee| {
+                range.union(ee.timestamp_range())
+            }),
+        }
+    }
+
     fn filters(&self) -> Vec<Filter> {
         match self {
             OrExpression::OrTerm(and_expression) => and_expression.filters(),
             OrExpression::OrExpression(vec) => vec.iter().flat_map(Search::filters).collect(),
         }
     }
+
+    fn cost(&self) -> u64 {
+        match self {
+            OrExpression::OrTerm(and_expression) => and_expression.cost(),
+            OrExpression::OrExpression(vec) => vec.iter().map(Search::cost).sum(),
+        }
+    }
+
+    fn fields(&self) -> RecordFields {
+        match self {
+            OrExpression::OrTerm(and_expression) => and_expression.fields(),
+            OrExpression::OrExpression(vec) => vec
+                .iter()
+                .fold(RecordFields::NONE, |fields, e| fields.union(e.fields())),
+        }
+    }
+
+    fn matches_metadata(&self, context: &SearchContext) -> Option<bool> {
+        match self {
+            Self::OrTerm(t) => t.matches_metadata(context),
+            Self::OrExpression(e) => {
+                if e.is_empty() {
+                    return Some(true);
+                }
+                let mut result = Some(false);
+                for ee in e {
+                    match ee.matches_metadata(context) {
+                        Some(true) => return Some(true),
+                        None => result = None,
+                        Some(false) => (),
+                    }
+                }
+                result
+            }
+        }
+    }
 }
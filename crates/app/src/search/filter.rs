@@ -1,46 +1,457 @@
 use std::{
     collections::HashMap,
-    sync::{LazyLock, Mutex},
+    fmt::Display,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, LazyLock, OnceLock, RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
 };
 
-use extism::{Plugin, convert::Json};
+use extism::{
+    FromBytesOwned, Manifest, Plugin, PluginBuilder, Pool, PoolBuilder, ToBytes, Wasm,
+    convert::Json,
+};
 use itertools::Itertools;
 use lib::search::filter::Parameter;
-use lib::{
-    FilterResult,
-    search::filter::{Filter, FilterInput},
-};
-use tracing::error;
+use lib::{FilterResult, KafkaRecord, search::filter::Filter};
+use serde::Serialize;
+use serde_json::Value;
+use thousands::Separable;
+use tracing::{error, warn};
 
-use super::{Search, SearchContext};
+use super::{
+    Search, SearchContext,
+    host_functions::{SearchSession, host_functions},
+};
+use crate::configuration::FilterLimits;
 
 pub const MATCHES_FUNCTION_NAME: &str = "matches";
+/// Same as [`MATCHES_FUNCTION_NAME`], the input is encoded in MessagePack instead of JSON.
+/// Search filters may export it in addition to `matches`, it is called instead.
+pub const MATCHES_MSGPACK_FUNCTION_NAME: &str = "matches_msgpack";
 pub const PARSE_PARAMETERS_FUNCTION_NAME: &str = "parse_parameters";
+pub const TRANSFORM_FUNCTION_NAME: &str = "transform";
+pub const DESERIALIZE_FUNCTION_NAME: &str = "deserialize";
+pub const DESCRIBE_FUNCTION_NAME: &str = "describe";
+
+/// extism reports the limits a call exceeds with untyped errors, only their messages tell them apart.
+/// They are pinned to the extism version of `Cargo.lock` by `test_extism_limit_errors`.
+const TIMEOUT_ERROR: &str = "timeout";
+const OUT_OF_MEMORY_ERROR: &str = "oom";
+const OUT_OF_FUEL_ERROR: &str = "plugin ran out of fuel";
+
+/// How long a call waits for an instance of the search filter when all of them are busy.
+const INSTANCE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Kinds of wasm modules, they are told apart by the functions they export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModuleKind {
+    SearchFilter,
+    Transformer,
+    Deserializer,
+}
+
+impl ModuleKind {
+    pub fn of(plugin: &Plugin) -> Self {
+        if plugin.function_exists(MATCHES_FUNCTION_NAME) {
+            return Self::SearchFilter;
+        }
+        if plugin.function_exists(TRANSFORM_FUNCTION_NAME) {
+            return Self::Transformer;
+        }
+        if plugin.function_exists(DESERIALIZE_FUNCTION_NAME) {
+            return Self::Deserializer;
+        }
+        Self::SearchFilter
+    }
+}
+
+impl Display for ModuleKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModuleKind::SearchFilter => write!(f, "search filter"),
+            ModuleKind::Transformer => write!(f, "transformer"),
+            ModuleKind::Deserializer => write!(f, "deserializer"),
+        }
+    }
+}
+
+/// Search filters loaded in memory, by name.
+pub type Filters = RwLock<HashMap<String, Arc<FilterPool>>>;
 
 /// FILTERS are lazy loaded and cached in memory.
-pub(crate) static CACHED_FILTERS: LazyLock<Mutex<HashMap<String, Plugin>>> =
-    LazyLock::new(|| HashMap::new().into());
+pub(crate) static CACHED_FILTERS: LazyLock<Filters> = LazyLock::new(|| HashMap::new().into());
+
+/// Returns the search filter `name`, its WASM module is loaded from `filters_directory` the first time.
+/// It is loaded again when it comes from another directory or its resource limits changed.
+pub fn load_filter(
+    name: &str,
+    filters_directory: &Path,
+    limits: &FilterLimits,
+    kind: ModuleKind,
+) -> Result<Arc<FilterPool>, lib::Error> {
+    let path = filters_directory.join(format!("{name}.wasm"));
+    let cached = CACHED_FILTERS
+        .read()
+        .ok()
+        .and_then(|filters| filters.get(name).cloned())
+        .filter(|filter| filter.path == path && filter.limits == *limits);
+    let filter = match cached {
+        Some(filter) => filter,
+        None => {
+            let filter = FilterPool::new(name, &path, limits, kind).map_err(|err| {
+                error!("No such file '{}': {}", path.display(), err);
+                lib::Error::Error(format!(
+                    "Cannot find search filter '{name}' in {}: {}",
+                    filters_directory.display(),
+                    err
+                ))
+            })?;
+            let filter = Arc::new(filter);
+            if let Ok(mut filters) = CACHED_FILTERS.write() {
+                filters.insert(name.to_string(), filter.clone());
+            }
+            filter
+        }
+    };
+    match filter.disabled() {
+        Some(reason) => Err(lib::Error::Error(reason.to_string())),
+        None => Ok(filter),
+    }
+}
+
+/// Instances of a search filter.
+/// Each instance evaluates one record at a time, so the workers of the search engine
+/// get their own instance, created on demand up to the number of available cores.
+/// A search filter exceeding its resource limits is disabled for the rest of the session.
+pub struct FilterPool {
+    name: String,
+    kind: ModuleKind,
+    path: PathBuf,
+    pool: Pool,
+    limits: FilterLimits,
+    /// Whether the search filter exports [`MATCHES_MSGPACK_FUNCTION_NAME`]
+    msgpack: bool,
+    disabled: OnceLock<String>,
+    calls: AtomicU64,
+    errors: AtomicU64,
+    latency_in_nanos: AtomicU64,
+}
+
+impl FilterPool {
+    /// Loads the WASM module of the search filter, instances are created with the given resource limits.
+    pub fn new(
+        name: &str,
+        path: &Path,
+        limits: &FilterLimits,
+        kind: ModuleKind,
+    ) -> Result<Self, extism::Error> {
+        let manifest = Manifest::new([Wasm::file(path)])
+            .with_memory_max(limits.max_memory_in_pages())
+            .with_timeout(limits.timeout());
+        let fuel = limits.fuel;
+        let functions = host_functions(name, path.parent().unwrap_or(Path::new(".")));
+        let pool = PoolBuilder::new().build(move || {
+            let builder = PluginBuilder::new(&manifest)
+                .with_wasi(true)
+                .with_functions(functions.clone());
+            match fuel {
+                Some(fuel) => builder.with_fuel_limit(fuel),
+                None => builder,
+            }
+            .build()
+        });
+        // Instantiate the module once so an invalid module is reported right away.
+        let msgpack = matches!(
+            pool.get(INSTANCE_TIMEOUT)?,
+            Some(plugin) if plugin.function_exists(MATCHES_MSGPACK_FUNCTION_NAME)
+        );
+        Ok(Self {
+            name: name.to_string(),
+            kind,
+            path: path.to_path_buf(),
+            pool,
+            limits: limits.clone(),
+            msgpack,
+            disabled: OnceLock::new(),
+            calls: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+            latency_in_nanos: AtomicU64::new(0),
+        })
+    }
+
+    /// Calls a function of the search filter with an available instance, outside of a search.
+    /// The search filter is disabled if the call exceeds its resource limits.
+    pub fn call<'a, I, O>(&self, function: &str, input: I) -> Result<O, extism::Error>
+    where
+        I: ToBytes<'a>,
+        O: FromBytesOwned,
+    {
+        self.call_in(None, function, input)
+    }
+
+    /// Same as [`FilterPool::call`], host functions get the session of the search the call belongs to.
+    pub fn call_in<'a, I, O>(
+        &self,
+        session: Option<&Arc<SearchSession>>,
+        function: &str,
+        input: I,
+    ) -> Result<O, extism::Error>
+    where
+        I: ToBytes<'a>,
+        O: FromBytesOwned,
+    {
+        if let Some(reason) = self.disabled() {
+            return Err(extism::Error::msg(reason.to_string()));
+        }
+        let result = match (self.pool.get(INSTANCE_TIMEOUT)?, session) {
+            (Some(mut plugin), Some(session)) => {
+                plugin.call_with_host_context::<I, O, _>(function, input, session.clone())
+            }
+            (Some(mut plugin), None) => plugin.call::<I, O>(function, input),
+            (None, _) => Err(extism::Error::msg(format!(
+                "No instance of '{}' was available after {}s",
+                self.name,
+                INSTANCE_TIMEOUT.as_secs()
+            ))),
+        };
+        if let Err(e) = &result {
+            self.disable_if_limits_exceeded(e);
+        }
+        result
+    }
+
+    /// Returns `true` when the WASM module exports the given function.
+    pub fn function_exists(&self, function: &str) -> bool {
+        matches!(self.pool.get(INSTANCE_TIMEOUT), Ok(Some(plugin)) if plugin.function_exists(function))
+    }
+
+    /// Checks the user-provided parameters with the `parse_parameters` function of the WASM module.
+    pub fn parse_parameters(&self, parameters: &[Parameter]) -> Result<(), lib::Error> {
+        let parameters =
+            serde_json::to_string(&parameters.iter().map(Parameter::json).collect_vec())?;
+        self.call::<&str, String>(PARSE_PARAMETERS_FUNCTION_NAME, &parameters)
+            .map(|_| ())
+            .map_err(|e| {
+                error!(
+                    "Error when calling '{PARSE_PARAMETERS_FUNCTION_NAME}' from wasm module '{}': {e:?}",
+                    self.name
+                );
+                lib::Error::Error(format!("{}: {e}", self.name))
+            })
+    }
+
+    /// Returns why the search filter has been disabled, if it has.
+    pub fn disabled(&self) -> Option<&str> {
+        self.disabled.get().map(String::as_str)
+    }
+
+    fn disable_if_limits_exceeded(&self, error: &extism::Error) {
+        let reason = match error.to_string().as_str() {
+            TIMEOUT_ERROR => format!("a call took more than {}ms", self.limits.timeout_in_ms),
+            OUT_OF_MEMORY_ERROR => format!("it used more than {}MiB", self.limits.max_memory_in_mb),
+            OUT_OF_FUEL_ERROR => format!(
+                "a call used more than {} units of fuel",
+                self.limits.fuel.unwrap_or_default()
+            ),
+            _ => return,
+        };
+        let kind = self.kind.to_string();
+        let reason = format!(
+            "{}{} '{}' has been disabled, {reason}",
+            kind[..1].to_uppercase(),
+            &kind[1..],
+            self.name
+        );
+        if self.disabled.set(reason.clone()).is_ok() {
+            warn!("{reason}");
+        }
+    }
+
+    /// Returns the number of calls to `matches`, their mean latency and the number of errors.
+    pub fn metrics(&self) -> FilterMetrics {
+        let calls = self.calls.load(Ordering::Relaxed);
+        FilterMetrics {
+            name: self.name.clone(),
+            kind: self.kind,
+            calls,
+            errors: self.errors.load(Ordering::Relaxed),
+            mean_latency: Duration::from_nanos(
+                self.latency_in_nanos
+                    .load(Ordering::Relaxed)
+                    .checked_div(calls)
+                    .unwrap_or_default(),
+            ),
+            instances: self.pool.count(),
+            disabled: self.disabled().map(str::to_string),
+        }
+    }
+
+    pub fn reset_metrics(&self) {
+        self.calls.store(0, Ordering::Relaxed);
+        self.errors.store(0, Ordering::Relaxed);
+        self.latency_in_nanos.store(0, Ordering::Relaxed);
+    }
+
+    /// Calls the `matches` function of the search filter with the kafka record and the given parameters.
+    /// The input is encoded in MessagePack, cheaper to encode and decode than JSON, when the search filter supports it.
+    pub fn evaluate(
+        &self,
+        record: &KafkaRecord,
+        parameters: &[Parameter],
+        session: Option<&Arc<SearchSession>>,
+    ) -> Result<bool, lib::Error> {
+        let input = self.input(record, parameters)?;
+        self.matches(&input, session)
+            .map_err(|e| lib::Error::Error(e.to_string()))
+    }
+
+    /// Encodes the input of the `matches` function.
+    fn input(&self, record: &KafkaRecord, parameters: &[Parameter]) -> Result<Vec<u8>, lib::Error> {
+        let input = FilterInputRef {
+            record,
+            params: parameters.iter().map(Parameter::json).collect_vec(),
+        };
+        match self.msgpack {
+            // Fields are named, the input is decoded like the JSON one
+            true => rmp_serde::to_vec_named(&input)
+                .map_err(|e| lib::Error::Error(format!("Cannot encode the record: {e}"))),
+            false => Ok(serde_json::to_vec(&input)?),
+        }
+    }
+
+    fn matches(
+        &self,
+        input: &[u8],
+        session: Option<&Arc<SearchSession>>,
+    ) -> Result<bool, extism::Error> {
+        let function = match self.msgpack {
+            true => MATCHES_MSGPACK_FUNCTION_NAME,
+            false => MATCHES_FUNCTION_NAME,
+        };
+        self.measure(|| {
+            self.call_in::<&[u8], Json<FilterResult>>(session, function, input)
+                .map(|e| e.0.r#match)
+        })
+    }
+
+    /// Calls the `transform` function of a transformer, it returns the rewritten kafka record.
+    pub(crate) fn transform(
+        &self,
+        input: &[u8],
+        session: Option<&Arc<SearchSession>>,
+    ) -> Result<KafkaRecord, extism::Error> {
+        self.measure(|| {
+            self.call_in::<&[u8], Json<KafkaRecord>>(session, TRANSFORM_FUNCTION_NAME, input)
+                .map(|e| e.0)
+        })
+    }
+
+    /// Calls the `deserialize` function of a deserializer, it returns the deserialized payload.
+    pub(crate) fn deserialize(&self, input: &[u8]) -> Result<Vec<u8>, extism::Error> {
+        self.measure(|| self.call::<&[u8], Vec<u8>>(DESERIALIZE_FUNCTION_NAME, input))
+    }
+
+    /// Records the number of calls, the latency and the errors of a call.
+    fn measure<T>(
+        &self,
+        call: impl FnOnce() -> Result<T, extism::Error>,
+    ) -> Result<T, extism::Error> {
+        let start = Instant::now();
+        let result = call();
+        let elapsed = u64::try_from(start.elapsed().as_nanos()).unwrap_or(u64::MAX);
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        self.latency_in_nanos.fetch_add(elapsed, Ordering::Relaxed);
+        if result.is_err() {
+            self.errors.fetch_add(1, Ordering::Relaxed);
+        }
+        result
+    }
+}
+
+/// Metrics of a search filter, a transformer or a deserializer since the search started.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterMetrics {
+    pub name: String,
+    pub kind: ModuleKind,
+    /// Number of records evaluated by the search filter
+    pub calls: u64,
+    /// Number of calls that failed
+    pub errors: u64,
+    pub mean_latency: Duration,
+    /// Number of instances of the WASM module
+    pub instances: usize,
+    /// Why the search filter has been disabled
+    pub disabled: Option<String>,
+}
+
+impl Display for FilterMetrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({}): {} calls, {:.2}ms, {} errors",
+            self.name,
+            self.kind,
+            self.calls.separate_with_underscores(),
+            self.mean_latency.as_secs_f64() * 1000.0,
+            self.errors.separate_with_underscores()
+        )?;
+        if self.disabled.is_some() {
+            write!(f, ", disabled")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the metrics of the WASM modules loaded in memory, sorted by name.
+pub fn filters_metrics() -> Vec<FilterMetrics> {
+    CACHED_FILTERS
+        .read()
+        .map(|filters| {
+            filters
+                .values()
+                .map(|f| f.metrics())
+                .sorted_by(|a, b| a.name.cmp(&b.name))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Resets the metrics of the search filters, before a new search starts.
+pub fn reset_filters_metrics() {
+    if let Ok(filters) = CACHED_FILTERS.read() {
+        filters.values().for_each(|f| f.reset_metrics());
+    }
+}
+
+/// Same JSON document as [`lib::search::filter::FilterInput`], without cloning the record.
+#[derive(Serialize)]
+pub(crate) struct FilterInputRef<'a> {
+    pub record: &'a KafkaRecord,
+    pub params: Vec<Value>,
+}
 
 impl Search for Filter {
     fn matches(&self, context: &SearchContext) -> bool {
-        let mut filters = context.filters.lock().unwrap();
-        let plugin = &mut filters.get_mut(&self.name).unwrap();
-        let input = FilterInput {
-            record: context.record.clone(),
-            params: self.parameters.iter().map(Parameter::json).collect_vec(),
+        let Some(filter) = context
+            .filters
+            .read()
+            .ok()
+            .and_then(|filters| filters.get(&self.name).cloned())
+        else {
+            error!("Search filter '{}' is not loaded", self.name);
+            return false;
         };
-
-        match plugin
-            .call::<String, Json<FilterResult>>(
-                MATCHES_FUNCTION_NAME,
-                serde_json::to_string(&input).unwrap(),
-            )
-            .map(|e| e.0)
-        {
-            Ok(res) => res.r#match,
+        if filter.disabled().is_some() {
+            return false;
+        }
+        match filter.evaluate(context.record, &self.parameters, context.session) {
+            Ok(res) => res,
             Err(e) => {
                 error!(
-                    "Error when calling '{MATCHES_FUNCTION_NAME}' from wasm module '{}': {e:?}",
+                    "Error when calling '{MATCHES_FUNCTION_NAME}' from wasm module '{}': {e}",
                     self.name
                 );
                 false
@@ -52,3 +463,266 @@ impl Search for Filter {
         vec![]
     }
 }
+
+#[cfg(test)]
+use super::wasm_fixtures::{self, FiltersDirectory};
+
+#[test]
+fn test_filter_pool_metrics() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("http_search_filter")
+        .join("module.wasm");
+    let filter = FilterPool::new(
+        "module",
+        &path,
+        &FilterLimits::default(),
+        ModuleKind::SearchFilter,
+    )
+    .unwrap();
+    let input = serde_json::to_vec(&FilterInputRef {
+        record: &KafkaRecord::default(),
+        params: vec![],
+    })
+    .unwrap();
+    // The search filter sends an HTTP request, which is not allowed
+    assert!(filter.matches(&input, None).is_err());
+    assert!(filter.matches(&input, None).is_err());
+
+    let metrics = filter.metrics();
+    assert_eq!((metrics.calls, metrics.errors), (2, 2));
+    assert_eq!(metrics.instances, 1);
+    filter.reset_metrics();
+    assert_eq!(filter.metrics().calls, 0);
+}
+
+#[test]
+fn test_msgpack_input() {
+    let directory = FiltersDirectory::new();
+    let module = wasm_fixtures::returning(MATCHES_MSGPACK_FUNCTION_NAME, r#"{"match": true}"#);
+    // `matches` fails, `matches_msgpack` is called instead
+    let module = format!(
+        r#"{} (func (export "{MATCHES_FUNCTION_NAME}") (result i32) (i32.const 1)))"#,
+        &module[..module.len() - 1]
+    );
+    let path = directory.add("msgpack", &module);
+    let filter = FilterPool::new(
+        "msgpack",
+        &path,
+        &FilterLimits::default(),
+        ModuleKind::SearchFilter,
+    )
+    .unwrap();
+    let record = KafkaRecord {
+        topic: "payments".to_string(),
+        key: lib::DataType::String("k1".to_string()),
+        value: lib::DataType::Json(serde_json::json!({"amount": 10})),
+        ..Default::default()
+    };
+    let parameters = [Parameter::String("1234".to_string())];
+    assert!(filter.evaluate(&record, &parameters, None).unwrap());
+
+    // Plugins decode the same input, whatever the encoding
+    let json = serde_json::to_vec(&FilterInputRef {
+        record: &record,
+        params: vec![serde_json::json!("1234")],
+    })
+    .unwrap();
+    let msgpack = filter.input(&record, &parameters).unwrap();
+    assert!(msgpack.len() < json.len());
+    assert_eq!(
+        rmp_serde::from_slice::<lib::search::filter::FilterInput>(&msgpack).unwrap(),
+        serde_json::from_slice::<lib::search::filter::FilterInput>(&json).unwrap()
+    );
+}
+
+#[test]
+fn test_extism_limit_errors() {
+    // The error messages of extism are not part of its API: check them again when upgrading extism
+    const EXTISM_VERSION: &str = "1.21.0";
+    let lock = std::fs::read_to_string(
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../Cargo.lock"),
+    )
+    .unwrap();
+    assert!(
+        lock.contains(&format!(
+            "name = \"extism\"\nversion = \"{EXTISM_VERSION}\""
+        )),
+        "extism has been upgraded, check the errors it reports when a limit is exceeded"
+    );
+}
+
+#[test]
+fn test_filter_exceeding_its_limits_is_disabled() {
+    let directory = FiltersDirectory::new();
+    let path = directory.add("infinite-loop", &wasm_fixtures::infinite_loop());
+    let limits = FilterLimits {
+        timeout_in_ms: 100,
+        ..Default::default()
+    };
+    let filter =
+        FilterPool::new("infinite-loop", &path, &limits, ModuleKind::SearchFilter).unwrap();
+    assert!(filter.matches(b"{}", None).is_err());
+    assert_eq!(
+        filter.disabled(),
+        Some("Search filter 'infinite-loop' has been disabled, a call took more than 100ms")
+    );
+    // Once disabled, the search filter is not called anymore
+    assert!(filter.matches(b"{}", None).is_err());
+    assert_eq!(filter.pool.count(), 1);
+    assert!(filter.metrics().disabled.is_some());
+}
+
+#[test]
+fn test_filter_exceeding_its_memory_is_disabled() {
+    let directory = FiltersDirectory::new();
+    let path = directory.add("greedy", &wasm_fixtures::growing_memory(100));
+    let limits = FilterLimits {
+        max_memory_in_mb: 1,
+        ..Default::default()
+    };
+    let filter = FilterPool::new("greedy", &path, &limits, ModuleKind::SearchFilter).unwrap();
+    assert!(filter.matches(b"{}", None).is_err());
+    assert_eq!(
+        filter.disabled(),
+        Some("Search filter 'greedy' has been disabled, it used more than 1MiB")
+    );
+}
+
+#[test]
+fn test_filter_exceeding_its_fuel_is_disabled() {
+    let directory = FiltersDirectory::new();
+    let path = directory.add("infinite-loop", &wasm_fixtures::infinite_loop());
+    let limits = FilterLimits {
+        fuel: Some(10_000),
+        ..Default::default()
+    };
+    let filter =
+        FilterPool::new("infinite-loop", &path, &limits, ModuleKind::SearchFilter).unwrap();
+    assert!(filter.matches(b"{}", None).is_err());
+    assert_eq!(
+        filter.disabled(),
+        Some(
+            "Search filter 'infinite-loop' has been disabled, a call used more than 10000 units of fuel"
+        )
+    );
+}
+
+#[test]
+fn test_filter_is_called_after_metadata_predicates() {
+    let directory = FiltersDirectory::new();
+    directory.add(
+        "always-matches",
+        &wasm_fixtures::returning(MATCHES_FUNCTION_NAME, r#"{"match": true}"#),
+    );
+    let query =
+        super::ValidSearchQuery::from("always-matches() and partition == 1", directory.path())
+            .unwrap();
+    let filter = CACHED_FILTERS.read().unwrap()["always-matches"].clone();
+    for partition in 0..4 {
+        let record = KafkaRecord {
+            partition,
+            ..Default::default()
+        };
+        let context = SearchContext::new(&record, directory.path());
+        assert_eq!(query.matches(&context), partition == 1);
+    }
+    // Records rejected by `partition == 1` are not passed to the search filter
+    assert_eq!(filter.metrics().calls, 1);
+}
+
+#[test]
+fn test_filter_is_reloaded_when_its_limits_change() {
+    let directory = FiltersDirectory::new();
+    directory.add("reloaded", &wasm_fixtures::infinite_loop());
+    let limits = FilterLimits {
+        timeout_in_ms: 100,
+        ..Default::default()
+    };
+    let filter = load_filter(
+        "reloaded",
+        directory.path(),
+        &limits,
+        ModuleKind::SearchFilter,
+    )
+    .unwrap();
+    assert!(filter.matches(b"{}", None).is_err());
+    assert!(
+        load_filter(
+            "reloaded",
+            directory.path(),
+            &limits,
+            ModuleKind::SearchFilter
+        )
+        .is_err()
+    );
+
+    let limits = FilterLimits {
+        timeout_in_ms: 200,
+        ..Default::default()
+    };
+    let filter = load_filter(
+        "reloaded",
+        directory.path(),
+        &limits,
+        ModuleKind::SearchFilter,
+    )
+    .unwrap();
+    assert_eq!(filter.disabled(), None);
+}
+
+#[test]
+fn test_metrics_are_labelled_by_module_kind() {
+    let directory = FiltersDirectory::new();
+    let path = directory.add("decrypt", &wasm_fixtures::infinite_loop());
+    let limits = FilterLimits {
+        timeout_in_ms: 100,
+        ..Default::default()
+    };
+    let transformer = FilterPool::new("decrypt", &path, &limits, ModuleKind::Transformer).unwrap();
+    assert!(transformer.matches(b"{}", None).is_err());
+    let metrics = transformer.metrics();
+    assert_eq!(metrics.kind, ModuleKind::Transformer);
+    assert!(
+        metrics
+            .to_string()
+            .starts_with("decrypt (transformer): 1 calls")
+    );
+    assert_eq!(
+        transformer.disabled(),
+        Some("Transformer 'decrypt' has been disabled, a call took more than 100ms")
+    );
+}
+
+#[test]
+fn test_disabled_filter_is_false() {
+    let directory = FiltersDirectory::new();
+    directory.add("never-returns", &wasm_fixtures::infinite_loop());
+    let limits = FilterLimits {
+        timeout_in_ms: 100,
+        ..Default::default()
+    };
+    load_filter(
+        "never-returns",
+        directory.path(),
+        &limits,
+        ModuleKind::SearchFilter,
+    )
+    .unwrap();
+    let record = KafkaRecord::default();
+    let context = SearchContext::new(&record, directory.path());
+    // A search filter that fails is `false`, whatever the order of the predicates
+    for (query, matches) in [
+        ("never-returns()", false),
+        ("never-returns() and offset == 0", false),
+        ("offset == 0 and never-returns()", false),
+        ("!never-returns()", true),
+        ("!never-returns() or offset == 0", true),
+        ("offset == 0 or !never-returns()", true),
+        ("never-returns() or offset == 0", true),
+        ("never-returns() or offset > 10", false),
+    ] {
+        let (_, query) = lib::SearchQuery::parse(query).unwrap();
+        assert_eq!(query.matches(&context), matches, "{query}");
+    }
+}
@@ -0,0 +1,403 @@
+//! Host functions search filters can import from the `extism:host/user` namespace.
+//!
+//! They are sandboxed: search filters have no network access and can only read files of their own directory
+//! in the [`DATA_DIRECTORY`] next to the search filters, see [`data_directory`].
+//!
+//! | Function            | Input                  | Output                  |
+//! | ------------------- | ---------------------- | ----------------------- |
+//! | `yozefu_schema`     | schema id (`u32`)      | JSON, `Schema` or `null`|
+//! | `yozefu_log`        | level, message         |                         |
+//! | `yozefu_read_file`  | file name              | content of the file     |
+//! | `yozefu_store_get`  | key                    | JSON, string or `null`  |
+//! | `yozefu_store_set`  | key, value             |                         |
+//! | `yozefu_store_swap` | key, value             | JSON, string or `null`  |
+//!
+//! Each search has its own [`SearchSession`], the search engine passes it to the calls of its workers
+//! as the host context of extism. The session holds a key-value store per search filter, shared by the
+//! records of every topic and partition of the search. The store is limited to [`MAX_STORE_ENTRIES`] entries
+//! and [`MAX_STORE_BYTES`] bytes. Calls outside of a search, such as `parse_parameters` or deserializers,
+//! have no session: the store is empty and cannot be written.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+use extism::{CurrentPlugin, Function, PTR, ToBytes, UserData, Val, convert::Json, host_fn};
+use lib::kafka::{Schema, SchemaRegistryClient};
+use tracing::{debug, error, info, trace, warn};
+
+/// Directory, next to the search filters, containing the files search filters can read.
+pub const DATA_DIRECTORY: &str = "data";
+
+/// Returns the directory of the files the search filter `name` can read, `data/<name>`.
+pub fn data_directory(filters_directory: &Path, name: &str) -> PathBuf {
+    filters_directory.join(DATA_DIRECTORY).join(name)
+}
+
+/// Maximum number of entries of the key-value store of a search filter.
+pub const MAX_STORE_ENTRIES: usize = 100_000;
+
+/// Maximum number of bytes, keys and values, of the key-value store of a search filter.
+pub const MAX_STORE_BYTES: usize = 64 * 1024 * 1024;
+
+/// State shared by the search filters during a search.
+#[derive(Default)]
+pub struct SearchSession {
+    schema_registry: Option<SchemaRegistryClient>,
+    /// Key-value stores, by search filter
+    stores: RwLock<HashMap<String, Store>>,
+    files: RwLock<HashMap<PathBuf, String>>,
+}
+
+/// Key-value store of a search filter.
+#[derive(Default)]
+struct Store {
+    entries: HashMap<String, String>,
+    bytes: usize,
+}
+
+impl SearchSession {
+    /// Creates the state of a new search.
+    /// Schemas are looked up in the cache of the given schema registry client.
+    pub fn new(schema_registry: Option<SchemaRegistryClient>) -> Self {
+        Self {
+            schema_registry,
+            ..Default::default()
+        }
+    }
+
+    fn cached_schema(&self, id: u32) -> Option<Schema> {
+        self.schema_registry.as_ref()?.cached_schema(id)
+    }
+
+    fn store_get(&self, filter: &str, key: &str) -> Option<String> {
+        self.stores
+            .read()
+            .ok()?
+            .get(filter)?
+            .entries
+            .get(key)
+            .cloned()
+    }
+
+    /// Sets a value in the store of the search filter and returns the previous one.
+    /// It fails once the store is full.
+    fn store_set(
+        &self,
+        filter: &str,
+        key: String,
+        value: String,
+    ) -> Result<Option<String>, String> {
+        let mut stores = self.stores.write().map_err(|e| e.to_string())?;
+        let store = stores.entry(filter.to_string()).or_default();
+        let replaced = store.entries.get(&key).map(|v| key.len() + v.len());
+        let bytes = store.bytes - replaced.unwrap_or_default() + key.len() + value.len();
+        let entries = store.entries.len() + usize::from(replaced.is_none());
+        if entries > MAX_STORE_ENTRIES || bytes > MAX_STORE_BYTES {
+            return Err(format!(
+                "The key-value store of '{filter}' is full, it is limited to {MAX_STORE_ENTRIES} entries and {}MiB",
+                MAX_STORE_BYTES / 1024 / 1024
+            ));
+        }
+        store.bytes = bytes;
+        Ok(store.entries.insert(key, value))
+    }
+}
+
+/// Search filter calling the host function.
+pub(crate) struct FilterHost {
+    name: String,
+    data_directory: PathBuf,
+}
+
+/// Returns the host functions of a search filter located in `filters_directory`.
+pub fn host_functions(name: &str, filters_directory: &Path) -> Vec<Function> {
+    let host = UserData::new(FilterHost {
+        name: name.to_string(),
+        data_directory: data_directory(filters_directory, name),
+    });
+    vec![
+        Function::new("yozefu_schema", [PTR], [PTR], host.clone(), yozefu_schema),
+        Function::new("yozefu_log", [PTR, PTR], [], host.clone(), yozefu_log),
+        Function::new(
+            "yozefu_read_file",
+            [PTR],
+            [PTR],
+            host.clone(),
+            yozefu_read_file,
+        ),
+        Function::new(
+            "yozefu_store_get",
+            [PTR],
+            [PTR],
+            host.clone(),
+            yozefu_store_get,
+        ),
+        Function::new(
+            "yozefu_store_set",
+            [PTR, PTR],
+            [],
+            host.clone(),
+            yozefu_store_set,
+        ),
+        Function::new(
+            "yozefu_store_swap",
+            [PTR, PTR],
+            [PTR],
+            host,
+            yozefu_store_swap,
+        ),
+    ]
+}
+
+/// Returns the name and the data directory of the search filter.
+fn filter_of(host: &UserData<FilterHost>) -> Result<(String, PathBuf), extism::Error> {
+    let host = host.get()?;
+    let host = host.lock().map_err(|e| extism::Error::msg(e.to_string()))?;
+    Ok((host.name.clone(), host.data_directory.clone()))
+}
+
+/// Returns the session of the search the call belongs to, if any.
+fn session_of(plugin: &mut CurrentPlugin) -> Option<Arc<SearchSession>> {
+    plugin.host_context::<Arc<SearchSession>>().ok().cloned()
+}
+
+/// Writes the output of a host function in the memory of the plugin.
+fn output<'a>(
+    plugin: &mut CurrentPlugin,
+    outputs: &mut [Val],
+    value: impl ToBytes<'a>,
+) -> Result<(), extism::Error> {
+    let handle = plugin.memory_new(value)?;
+    outputs[0] = plugin.memory_to_val(handle);
+    Ok(())
+}
+
+// The following host functions read the session from the host context, `host_fn!` doesn't give access to it.
+
+fn yozefu_schema(
+    plugin: &mut CurrentPlugin,
+    inputs: &[Val],
+    outputs: &mut [Val],
+    host: UserData<FilterHost>,
+) -> Result<(), extism::Error> {
+    let id: u32 = plugin.memory_get_val(&inputs[0])?;
+    let (name, _) = filter_of(&host)?;
+    debug!("Search filter '{name}' looks up schema {id}");
+    let schema = session_of(plugin).and_then(|session| session.cached_schema(id));
+    output(plugin, outputs, Json(schema))
+}
+
+host_fn!(yozefu_log(host: FilterHost; level: String, message: String) {
+    let (name, _) = filter_of(&host)?;
+    log(&name, &level, &message);
+    Ok(())
+});
+
+fn yozefu_read_file(
+    plugin: &mut CurrentPlugin,
+    inputs: &[Val],
+    outputs: &mut [Val],
+    host: UserData<FilterHost>,
+) -> Result<(), extism::Error> {
+    let file: String = plugin.memory_get_val(&inputs[0])?;
+    let (_, data_directory) = filter_of(&host)?;
+    let session = session_of(plugin);
+    let content =
+        read_file(session.as_deref(), &data_directory, &file).map_err(extism::Error::msg)?;
+    output(plugin, outputs, content)
+}
+
+fn yozefu_store_get(
+    plugin: &mut CurrentPlugin,
+    inputs: &[Val],
+    outputs: &mut [Val],
+    host: UserData<FilterHost>,
+) -> Result<(), extism::Error> {
+    let key: String = plugin.memory_get_val(&inputs[0])?;
+    let (name, _) = filter_of(&host)?;
+    let value = session_of(plugin).and_then(|session| session.store_get(&name, &key));
+    output(plugin, outputs, Json(value))
+}
+
+fn yozefu_store_set(
+    plugin: &mut CurrentPlugin,
+    inputs: &[Val],
+    _outputs: &mut [Val],
+    host: UserData<FilterHost>,
+) -> Result<(), extism::Error> {
+    let key: String = plugin.memory_get_val(&inputs[0])?;
+    let value: String = plugin.memory_get_val(&inputs[1])?;
+    let (name, _) = filter_of(&host)?;
+    store_set(session_of(plugin), &name, key, value).map(|_| ())
+}
+
+fn yozefu_store_swap(
+    plugin: &mut CurrentPlugin,
+    inputs: &[Val],
+    outputs: &mut [Val],
+    host: UserData<FilterHost>,
+) -> Result<(), extism::Error> {
+    let key: String = plugin.memory_get_val(&inputs[0])?;
+    let value: String = plugin.memory_get_val(&inputs[1])?;
+    let (name, _) = filter_of(&host)?;
+    let previous = store_set(session_of(plugin), &name, key, value)?;
+    output(plugin, outputs, Json(previous))
+}
+
+fn store_set(
+    session: Option<Arc<SearchSession>>,
+    filter: &str,
+    key: String,
+    value: String,
+) -> Result<Option<String>, extism::Error> {
+    session
+        .ok_or_else(|| {
+            extism::Error::msg("The key-value store can only be written during a search")
+        })?
+        .store_set(filter, key, value)
+        .map_err(extism::Error::msg)
+}
+
+fn log(filter: &str, level: &str, message: &str) {
+    match level.to_lowercase().as_str() {
+        "error" => error!(filter, "{message}"),
+        "warn" => warn!(filter, "{message}"),
+        "debug" => debug!(filter, "{message}"),
+        "trace" => trace!(filter, "{message}"),
+        _ => info!(filter, "{message}"),
+    }
+}
+
+/// Reads a file of the data directory, the content is cached for the rest of the search, if any.
+/// Only file names are accepted, paths such as `../config.json` are rejected,
+/// as well as symbolic links to files outside of the data directory.
+fn read_file(
+    session: Option<&SearchSession>,
+    data_directory: &Path,
+    file: &str,
+) -> Result<String, String> {
+    let outside = || {
+        format!(
+            "'{file}' is not a file name, search filters can only read files of '{}'",
+            data_directory.display()
+        )
+    };
+    if Path::new(file).file_name().and_then(|f| f.to_str()) != Some(file) {
+        return Err(outside());
+    }
+    let canonicalize = |path: &Path| {
+        path.canonicalize()
+            .map_err(|e| format!("Cannot read '{}': {e}", path.display()))
+    };
+    let path = canonicalize(&data_directory.join(file))?;
+    if !path.starts_with(canonicalize(data_directory)?) {
+        return Err(outside());
+    }
+    let cached = session
+        .and_then(|s| s.files.read().ok())
+        .and_then(|files| files.get(&path).cloned());
+    if let Some(content) = cached {
+        return Ok(content);
+    }
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Cannot read '{}': {e}", path.display()))?;
+    if let Some(mut files) = session.and_then(|s| s.files.write().ok()) {
+        files.insert(path, content.clone());
+    }
+    Ok(content)
+}
+
+#[test]
+fn test_host_functions() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp directory");
+    let data_directory = temp_dir.path();
+    fs::write(data_directory.join("countries.csv"), "FR,France").unwrap();
+
+    let session = SearchSession::new(None);
+    assert_eq!(
+        read_file(Some(&session), data_directory, "countries.csv"),
+        Ok("FR,France".to_string())
+    );
+    assert_eq!(
+        read_file(None, data_directory, "countries.csv"),
+        Ok("FR,France".to_string())
+    );
+    assert!(read_file(Some(&session), data_directory, "../countries.csv").is_err());
+    assert!(read_file(Some(&session), data_directory, "/etc/passwd").is_err());
+    #[cfg(unix)]
+    {
+        // A symbolic link doesn't escape the data directory
+        let outside = tempfile::tempdir().expect("Failed to create temp directory");
+        fs::write(outside.path().join("secret"), "secret").unwrap();
+        std::os::unix::fs::symlink(outside.path().join("secret"), data_directory.join("link"))
+            .unwrap();
+        assert!(read_file(Some(&session), data_directory, "link").is_err());
+    }
+
+    assert_eq!(
+        session.store_set("dedup", "key-1".to_string(), "1".to_string()),
+        Ok(None)
+    );
+    assert_eq!(session.store_get("dedup", "key-1"), Some("1".to_string()));
+    assert_eq!(session.store_get("other-filter", "key-1"), None);
+    assert_eq!(
+        session.store_set("dedup", "key-1".to_string(), "2".to_string()),
+        Ok(Some("1".to_string()))
+    );
+    assert_eq!(session.cached_schema(1), None);
+
+    // A new search starts with an empty store
+    let session = SearchSession::new(None);
+    assert_eq!(session.store_get("dedup", "key-1"), None);
+    assert!(session.files.read().unwrap().is_empty());
+
+    // The store can only be written during a search
+    assert!(store_set(None, "dedup", "key-1".to_string(), "1".to_string()).is_err());
+}
+
+#[test]
+fn test_store_is_limited() {
+    let session = SearchSession::new(None);
+    let value = "v".repeat(MAX_STORE_BYTES / 2);
+    assert!(
+        session
+            .store_set("dedup", "a".to_string(), value.clone())
+            .is_ok()
+    );
+    // Replacing a value frees its bytes
+    assert!(
+        session
+            .store_set("dedup", "a".to_string(), value.clone())
+            .is_ok()
+    );
+    assert!(
+        session
+            .store_set("dedup", "b".to_string(), value.clone())
+            .is_err()
+    );
+    assert!(
+        session
+            .store_set("other-filter", "b".to_string(), value)
+            .is_ok()
+    );
+
+    for i in 1..MAX_STORE_ENTRIES {
+        session
+            .store_set("many", i.to_string(), String::new())
+            .unwrap();
+    }
+    assert!(
+        session
+            .store_set("many", "last".to_string(), String::new())
+            .is_ok()
+    );
+    assert!(
+        session
+            .store_set("many", "one-too-many".to_string(), String::new())
+            .is_err()
+    );
+}
@@ -0,0 +1,147 @@
+//! Search filters imported in the workspace.
+//! A search filter is a wasm module `<name>.wasm`. When the module exports a `describe` function,
+//! its description is stored next to it, in `<name>.json`, by the `import-filter` command.
+//! The files it reads are in `data/<name>`.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local};
+use itertools::Itertools;
+use lib::search::filter::FilterDescription;
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use super::host_functions::data_directory;
+
+/// A search filter of the filters directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedFilter {
+    pub name: String,
+    /// Location of the wasm module
+    pub path: PathBuf,
+    /// Size of the wasm module, in bytes
+    pub size: u64,
+    /// When the wasm module was last modified, usually when it was imported
+    pub modified_at: Option<DateTime<Local>>,
+    pub description: Option<FilterDescription>,
+}
+
+impl ImportedFilter {
+    /// Returns the location of the description of the search filter `name`.
+    pub fn description_file(filters_directory: &Path, name: &str) -> PathBuf {
+        filters_directory.join(format!("{name}.json"))
+    }
+
+    /// Search queries using the search filter, `name(...)` when the search filter doesn't describe itself.
+    pub fn examples(&self) -> Vec<String> {
+        match &self.description {
+            Some(description) if !description.examples.is_empty() => description.examples.clone(),
+            _ => vec![format!("{}(...)", self.name)],
+        }
+    }
+
+    /// Returns the SHA-256 checksum of the wasm module, in hexadecimal.
+    pub fn checksum(&self) -> Result<String, lib::Error> {
+        let digest = Sha256::digest(fs::read(&self.path)?);
+        Ok(digest.iter().map(|b| format!("{b:02x}")).collect())
+    }
+
+    /// Deletes the wasm module, its description and its data directory.
+    pub fn remove(&self) -> Result<(), lib::Error> {
+        let filters_directory = self.path.parent().unwrap_or(Path::new("."));
+        fs::remove_file(&self.path)?;
+        let description = Self::description_file(filters_directory, &self.name);
+        if description.exists() {
+            fs::remove_file(description)?;
+        }
+        let data = data_directory(filters_directory, &self.name);
+        if data.exists() {
+            fs::remove_dir_all(data)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the search filter `name` of the filters directory.
+pub fn imported_filter(filters_directory: &Path, name: &str) -> Result<ImportedFilter, lib::Error> {
+    imported_filters(filters_directory)
+        .into_iter()
+        .find(|f| f.name == name)
+        .ok_or(lib::Error::Error(format!(
+            "There is no search filter '{name}' in '{}'",
+            filters_directory.display()
+        )))
+}
+
+/// Lists the search filters of the filters directory, sorted by name.
+pub fn imported_filters(filters_directory: &Path) -> Vec<ImportedFilter> {
+    let Ok(entries) = fs::read_dir(filters_directory) else {
+        return vec![];
+    };
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|e| e == "wasm"))
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_str()?.to_string();
+            let description =
+                read_description(&ImportedFilter::description_file(filters_directory, &name));
+            let metadata = fs::metadata(&path).ok()?;
+            Some(ImportedFilter {
+                name,
+                size: metadata.len(),
+                modified_at: metadata.modified().ok().map(DateTime::<Local>::from),
+                path,
+                description,
+            })
+        })
+        .sorted_by(|a, b| a.name.cmp(&b.name))
+        .collect()
+}
+
+fn read_description(file: &Path) -> Option<FilterDescription> {
+    let content = fs::read_to_string(file).ok()?;
+    serde_json::from_str(&content)
+        .inspect_err(|e| warn!("Cannot read the description '{}': {e}", file.display()))
+        .ok()
+}
+
+#[test]
+fn test_imported_filters() {
+    let filters_directory = super::wasm_fixtures::FiltersDirectory::new();
+    let directory = filters_directory.path();
+    filters_directory.add("key-ends-with", "");
+    fs::write(
+        directory.join("key-ends-with.json"),
+        r#"{"name": "key-ends-with", "examples": ["key-ends-with('1234')"]}"#,
+    )
+    .unwrap();
+    filters_directory.add("decrypt", "");
+    fs::create_dir_all(data_directory(directory, "key-ends-with")).unwrap();
+    fs::create_dir_all(data_directory(directory, "decrypt")).unwrap();
+
+    let filters = imported_filters(directory);
+    assert_eq!(
+        filters.iter().map(|f| f.name.as_str()).collect_vec(),
+        vec!["decrypt", "key-ends-with"]
+    );
+    assert_eq!(filters[0].description, None);
+    assert_eq!(filters[0].examples(), vec!["decrypt(...)"]);
+    assert_eq!(filters[1].examples(), vec!["key-ends-with('1234')"]);
+    assert_eq!(
+        filters[0].checksum().unwrap(),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+
+    imported_filter(directory, "key-ends-with")
+        .unwrap()
+        .remove()
+        .unwrap();
+    assert!(!directory.join("key-ends-with.json").exists());
+    assert!(!data_directory(directory, "key-ends-with").exists());
+    assert!(data_directory(directory, "decrypt").exists());
+    assert!(imported_filter(directory, "key-ends-with").is_err());
+}
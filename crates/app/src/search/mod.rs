@@ -1,28 +1,38 @@
 //! Module implementing the search logic
 
-use extism::{Manifest, Plugin, Wasm};
-use filter::{CACHED_FILTERS, PARSE_PARAMETERS_FUNCTION_NAME};
-use itertools::Itertools;
+use crate::configuration::{FilterLimits, SavedQuery};
+use filter::{CACHED_FILTERS, Filters, ModuleKind, load_filter};
+use host_functions::SearchSession;
+use indexmap::IndexMap;
 use lib::{
     KafkaRecord, SearchQuery,
-    search::{
-        filter::{Filter, Parameter},
-        offset::FromOffset,
-    },
+    kafka::RecordFields,
+    search::{expression::Expression, filter::Filter, offset::FromOffset},
 };
 use std::{
-    collections::HashMap,
     path::{Path, PathBuf},
-    sync::{LazyLock, Mutex},
+    sync::{Arc, LazyLock},
 };
-use tracing::error;
+use transform::Transforms;
+
+use planner::TimestampRange;
 
 pub mod atom;
 pub mod compare;
+pub mod cost;
+pub mod deserializer;
+pub mod engine;
 pub mod expression;
 pub mod filter;
+pub mod host_functions;
+pub mod imported_filter;
+pub use lib::kafka::partitioner;
+pub mod planner;
 pub mod search_query;
 pub mod term;
+pub mod transform;
+#[cfg(any(test, feature = "test-fixtures"))]
+pub mod wasm_fixtures;
 
 pub trait Search {
     /// Returns the offset from which the search should start.
@@ -32,8 +42,68 @@ pub trait Search {
     /// returns `true` if the record matches the search query.
     fn matches(&self, context: &SearchContext) -> bool;
 
+    /// Evaluates the search query knowing only the topic and the partition of the records.
+    /// It returns `Some(false)` when no record of this topic partition can match,
+    /// `Some(true)` when all records match and `None` when it depends on the record itself.
+    fn matches_partition(&self, _context: &PartitionContext) -> Option<bool> {
+        None
+    }
+
+    /// Returns, among the given partitions of a topic, those that may contain records matching the search query.
+    /// When `default_partitioner` is `true`, records are expected to be assigned to partitions by Kafka's default partitioner.
+    /// When `raw_keys` is `true`, keys are read as they were produced, see [`PartitionContext::raw_keys`].
+    fn partitions(
+        &self,
+        topic: &str,
+        partitions: &[i32],
+        default_partitioner: bool,
+        raw_keys: bool,
+    ) -> Vec<i32> {
+        partitions
+            .iter()
+            .filter(|partition| {
+                let context = PartitionContext {
+                    topic,
+                    partition: **partition,
+                    partitions: partitions.len() as i32,
+                    default_partitioner,
+                    raw_keys,
+                };
+                self.matches_partition(&context) != Some(false)
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Returns the range of timestamps records must belong to in order to match the search query.
+    fn timestamp_range(&self) -> TimestampRange {
+        TimestampRange::default()
+    }
+
     /// Returns the search filters that are used in the search query.
     fn filters(&self) -> Vec<Filter>;
+
+    /// Returns the fields of the record that must be deserialized to evaluate the search query.
+    fn fields(&self) -> RecordFields {
+        RecordFields::ALL
+    }
+
+    /// Estimated cost of evaluating the search query on a record, see [`cost`].
+    fn cost(&self) -> u64 {
+        cost::FILTER_COST
+    }
+
+    /// Returns `true` when only the newest record of each key is kept, see [`lib::search::latest`].
+    /// Every record then matters, even those that don't match: they may replace the newest record of their key.
+    fn latest_by_key(&self) -> bool {
+        false
+    }
+
+    /// Evaluates the search query on a record whose key and value are not deserialized yet.
+    /// It returns `None` when the result depends on the key or the value.
+    fn matches_metadata(&self, context: &SearchContext) -> Option<bool> {
+        self.fields().is_empty().then(|| self.matches(context))
+    }
 }
 
 /// Struct that holds the context of the search.
@@ -42,9 +112,27 @@ pub struct SearchContext<'a> {
     /// The record that is being searched.
     pub record: &'a KafkaRecord,
     /// The search filters that are already loaded in memory.
-    pub filters: &'a LazyLock<Mutex<HashMap<String, Plugin>>>,
+    pub filters: &'a LazyLock<Filters>,
     /// The directory containing the search filters
     pub filters_directory: PathBuf,
+    /// The state shared by the search filters during the search, `None` outside of a search.
+    pub session: Option<&'a Arc<SearchSession>>,
+}
+
+/// Struct that holds the context of a topic partition, before any record is consumed.
+#[derive(Clone, Copy)]
+pub struct PartitionContext<'a> {
+    /// The topic name.
+    pub topic: &'a str,
+    /// The partition number.
+    pub partition: i32,
+    /// The number of partitions of the topic.
+    pub partitions: i32,
+    /// Whether records are assigned to partitions by Kafka's default partitioner.
+    pub default_partitioner: bool,
+    /// Whether keys are read as they were produced: no schema, no deserializer plugin and no transformer.
+    /// Otherwise, the key of the search query is not the one the producer hashed.
+    pub raw_keys: bool,
 }
 
 impl SearchContext<'_> {
@@ -53,84 +141,148 @@ impl SearchContext<'_> {
             record,
             filters: &CACHED_FILTERS,
             filters_directory: filters_directory.to_path_buf(),
+            session: None,
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Default)]
-pub struct ValidSearchQuery(SearchQuery);
+impl<'a> SearchContext<'a> {
+    /// Host functions called by the search filters get the given session.
+    pub fn with_session(mut self, session: &'a Arc<SearchSession>) -> Self {
+        self.session = Some(session);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidSearchQuery {
+    query: SearchQuery,
+    /// The expression of the search query, predicates ordered by cost.
+    plan: Expression,
+}
+
+impl Default for ValidSearchQuery {
+    fn default() -> Self {
+        let query = SearchQuery::default();
+        Self {
+            plan: query.expression.clone(),
+            query,
+        }
+    }
+}
 
 impl ValidSearchQuery {
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.query.is_empty()
     }
 
     pub fn limit(&self) -> Option<usize> {
-        self.0.limit
+        self.query.limit
     }
 
     pub fn query(&self) -> &SearchQuery {
-        &self.0
+        &self.query
+    }
+
+    /// Describes the order the predicates of the search query are evaluated in.
+    pub fn explain(&self) -> String {
+        cost::explain(&self.query, &self.plan)
     }
 }
 
 impl ValidSearchQuery {
     pub fn from(input: &str, filters_directory: &Path) -> Result<Self, lib::Error> {
-        let query = SearchQuery::parse(input).map_err(lib::Error::Search)?.1;
-        let filters = query.filters();
-        for filter in filters {
-            let name = filter.name;
-            let path = filters_directory.join(format!("{}.wasm", &name));
-            let url = Wasm::file(&path);
-            let manifest = Manifest::new([url]);
-            let mut filters = CACHED_FILTERS.lock().unwrap();
-            if !filters.contains_key(&name) {
-                match Plugin::new(manifest, [], true) {
-                    Ok(plugin) => filters.insert(name.to_string(), plugin),
-                    Err(err) => {
-                        error!("No such file '{}': {}", path.display(), err);
-                        return Err(lib::Error::Error(format!(
-                            "Cannot find search filter '{name}' in {}: {}",
-                            path.parent().unwrap().display(),
-                            err
-                        )));
-                    }
-                };
-            }
-            let params = filter.parameters;
-            let wasm_module = &mut filters.get_mut(&name).unwrap();
-            if let Err(e) = wasm_module.call::<&str, &str>(
-                PARSE_PARAMETERS_FUNCTION_NAME,
-                &serde_json::to_string(&params.iter().map(Parameter::json).collect_vec()).unwrap(),
-            ) {
-                error!(
-                    "Error when calling '{PARSE_PARAMETERS_FUNCTION_NAME}' from wasm module '{name}': {e:?}"
-                );
-                return Err(lib::Error::Error(format!("{}: {e}", &name)));
-            }
+        Self::with_saved_queries(input, filters_directory, &IndexMap::new())
+    }
+
+    /// Parses the search query, references to saved queries such as `@failed-payments` are replaced with their definition.
+    pub fn with_saved_queries(
+        input: &str,
+        filters_directory: &Path,
+        saved_queries: &IndexMap<String, SavedQuery>,
+    ) -> Result<Self, lib::Error> {
+        Self::with_filter_limits(input, filters_directory, saved_queries, &IndexMap::new())
+    }
+
+    /// Same as [`ValidSearchQuery::with_saved_queries`], search filters are loaded with the given resource limits.
+    /// Search filters without limits get the default ones.
+    pub fn with_filter_limits(
+        input: &str,
+        filters_directory: &Path,
+        saved_queries: &IndexMap<String, SavedQuery>,
+        filter_limits: &IndexMap<String, FilterLimits>,
+    ) -> Result<Self, lib::Error> {
+        let query = SearchQuery::parse(input)
+            .map_err(lib::Error::Search)?
+            .1
+            .expand(&|name: &str| saved_queries.get(name).map(|s| s.query.clone()))
+            .map_err(lib::Error::Search)?;
+        let limits_of = |name: &str| filter_limits.get(name).cloned().unwrap_or_default();
+        for filter in query.filters() {
+            load_filter(
+                &filter.name,
+                filters_directory,
+                &limits_of(&filter.name),
+                ModuleKind::SearchFilter,
+            )?
+            .parse_parameters(&filter.parameters)?;
         }
+        Transforms::load(
+            &query.transforms,
+            &IndexMap::new(),
+            filters_directory,
+            filter_limits,
+        )?;
 
-        Ok(ValidSearchQuery(query))
+        Ok(ValidSearchQuery {
+            plan: cost::order_by_cost(query.expression.clone()),
+            query,
+        })
     }
 }
 
 impl Search for ValidSearchQuery {
     /// Returns the offset from which the search should start.
     fn offset(&self) -> Option<FromOffset> {
-        self.0.offset()
+        self.query.offset()
     }
 
     fn matches(&self, context: &SearchContext) -> bool {
-        self.0.matches(context)
+        self.plan.matches(context)
+    }
+
+    fn matches_partition(&self, context: &PartitionContext) -> Option<bool> {
+        self.query.matches_partition(context)
+    }
+
+    fn timestamp_range(&self) -> TimestampRange {
+        self.query.timestamp_range()
     }
 
     fn filters(&self) -> Vec<Filter> {
-        self.0.filters()
+        self.query.filters()
+    }
+
+    fn fields(&self) -> RecordFields {
+        self.query.fields()
+    }
+
+    fn cost(&self) -> u64 {
+        self.plan.cost()
+    }
+
+    fn latest_by_key(&self) -> bool {
+        self.query.latest_by_key
+    }
+
+    fn matches_metadata(&self, context: &SearchContext) -> Option<bool> {
+        self.plan.matches_metadata(context)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use itertools::Itertools;
     use lib::DataType;
 
     use super::*;
@@ -151,6 +303,38 @@ mod tests {
         assert!(query.matches(&context));
     }
 
+    #[test]
+    fn test_search_query_with_saved_query() {
+        let filters_directory = PathBuf::from("tests/filters");
+        let mut saved_queries = IndexMap::new();
+        saved_queries.insert(
+            "my-key".to_string(),
+            SavedQuery {
+                query: r#"from begin key == "my-key" limit 10"#.to_string(),
+                description: None,
+                topics: vec![],
+            },
+        );
+        let query = ValidSearchQuery::with_saved_queries(
+            "@my-key and partition == 2",
+            &filters_directory,
+            &saved_queries,
+        )
+        .unwrap();
+        assert_eq!(query.limit(), Some(10));
+        assert_eq!(query.offset(), Some(FromOffset::Beginning));
+
+        let record = KafkaRecord {
+            key: DataType::String("my-key".into()),
+            partition: 2,
+            ..Default::default()
+        };
+        let context = SearchContext::new(&record, &filters_directory);
+        assert!(query.matches(&context));
+
+        assert!(ValidSearchQuery::from("@my-key", &filters_directory).is_err());
+    }
+
     #[test]
     fn unknown_search_filter() {
         let filters_directory = PathBuf::from("tests/filters");
@@ -195,7 +379,6 @@ mod tests {
 
     #[test]
     fn test_matches_with_fine_grained_filter_on_json_field() {
-        use crate::search::filter::CACHED_FILTERS;
         use lib::kafka::KafkaRecord;
         use serde_json::json;
         use std::path::PathBuf;
@@ -220,13 +403,239 @@ mod tests {
             size: 12,
             key_as_string: "key".to_string(),
             value_as_string: "value".to_string(),
+            tombstone: false,
         };
-        let context = SearchContext {
-            record: &record,
-            filters: &CACHED_FILTERS,
-            filters_directory,
-        };
+        let context = SearchContext::new(&record, &filters_directory);
 
         assert!(query.matches(&context))
     }
+
+    #[test]
+    fn test_matches_with_built_in_functions() {
+        use serde_json::json;
+
+        let filters_directory = PathBuf::from(".");
+        let record = KafkaRecord {
+            key: DataType::String("3f2b1c9e-8d4a-4e6f-9b7c-1a2d3e4f5a6b".into()),
+            value: DataType::Json(
+                json!({"email": "John@Example.com", "items": [1, 2], "amount": "12"}),
+            ),
+            headers: [("traceparent".to_string(), "00-abc".to_string())].into(),
+            ..Default::default()
+        };
+        let context = SearchContext::new(&record, &filters_directory);
+        let matches = |input: &str| {
+            ValidSearchQuery::from(input, &filters_directory)
+                .unwrap()
+                .matches(&context)
+        };
+
+        assert!(matches(r#"lower(value.email) == "john@example.com""#));
+        assert!(matches("length(value.items) < 10 and len(key) == 36"));
+        assert!(matches(r#"json_type(value.amount) == "string""#));
+        assert!(matches("header_count() >= 1"));
+        assert!(!matches("header_count() > 5"));
+        assert!(!matches("length(value.missing) == 0"));
+        assert!(
+            ValidSearchQuery::from("length(value.items) == \"2\"", &filters_directory).is_err()
+        );
+    }
+
+    #[test]
+    fn test_partitions_to_consume() {
+        let filters_directory = PathBuf::from(".");
+        let partitions = (0..6).collect_vec();
+        let partitions_of = |input: &str| {
+            ValidSearchQuery::from(input, &filters_directory)
+                .unwrap()
+                .partitions("my-topic", &partitions, true, true)
+        };
+
+        assert_eq!(partitions_of("from begin"), partitions);
+        assert_eq!(partitions_of("partition == 3"), vec![3]);
+        assert_eq!(partitions_of("partition >= 4"), vec![4, 5]);
+        assert_eq!(partitions_of("partition == 42"), Vec::<i32>::new());
+        assert_eq!(partitions_of("!(partition < 4)"), vec![4, 5]);
+        assert_eq!(
+            partitions_of("partition == 1 or partition == 4"),
+            vec![1, 4]
+        );
+        assert_eq!(
+            partitions_of(r#"partition == 1 and key contains "my-key""#),
+            vec![1]
+        );
+        assert_eq!(
+            partitions_of(r#"partition == 1 or key contains "my-key""#),
+            partitions
+        );
+        assert_eq!(
+            partitions_of(r#"!(partition == 1 and key contains "my-key")"#),
+            partitions
+        );
+        assert_eq!(
+            partitions_of(r#"key == "my-key""#),
+            vec![partitioner::default_partition(b"my-key", 6)]
+        );
+        assert_eq!(
+            partitions_of(r#"key == "my-key" or key == "another-key""#),
+            [b"my-key".as_slice(), b"another-key"]
+                .iter()
+                .map(|key| partitioner::default_partition(key, 6))
+                .sorted()
+                .dedup()
+                .collect_vec()
+        );
+        assert_eq!(partitions_of(r#"key != "my-key""#), partitions);
+        assert_eq!(
+            ValidSearchQuery::from(r#"key == "my-key""#, &filters_directory)
+                .unwrap()
+                .partitions("my-topic", &partitions, false, true),
+            partitions
+        );
+        assert_eq!(
+            ValidSearchQuery::from(r#"key == "my-key""#, &filters_directory)
+                .unwrap()
+                .partitions("my-topic", &partitions, true, false),
+            partitions
+        );
+        assert_eq!(partitions_of(r#"key == """#), partitions);
+        assert_eq!(partitions_of(r#"key == "42""#), partitions);
+        assert_eq!(partitions_of(r#"key == '{"id": 1}'"#), partitions);
+        assert_eq!(
+            partitions_of(r#"topic == "another-topic" and partition == 1"#),
+            Vec::<i32>::new()
+        );
+    }
+
+    #[test]
+    fn test_fields_read_by_the_search_query() {
+        let filters_directory = PathBuf::from(".");
+        let fields_of = |input: &str| {
+            ValidSearchQuery::from(input, &filters_directory)
+                .unwrap()
+                .fields()
+        };
+
+        assert_eq!(fields_of("from begin"), RecordFields::NONE);
+        assert_eq!(
+            fields_of(r#"partition == 1 and offset > 10 and header_count() > 1"#),
+            RecordFields::NONE
+        );
+        assert_eq!(fields_of(r#"key == "my-key""#), RecordFields::KEY);
+        assert_eq!(fields_of("length(key) == 36"), RecordFields::KEY);
+        assert_eq!(
+            fields_of(r#"partition == 1 and (value.status == "FAILED" or key == "my-key")"#),
+            RecordFields::ALL
+        );
+    }
+
+    #[test]
+    fn test_matches_metadata() {
+        let filters_directory = PathBuf::from(".");
+        let record = KafkaRecord {
+            partition: 2,
+            offset: 42,
+            ..Default::default()
+        };
+        let context = SearchContext::new(&record, &filters_directory);
+        let matches = |input: &str| {
+            ValidSearchQuery::from(input, &filters_directory)
+                .unwrap()
+                .matches_metadata(&context)
+        };
+
+        assert_eq!(matches("from begin"), Some(true));
+        assert_eq!(matches("partition == 2 and offset > 10"), Some(true));
+        assert_eq!(
+            matches(r#"partition == 1 and value.status == "FAILED""#),
+            Some(false)
+        );
+        assert_eq!(
+            matches(r#"partition == 2 and value.status == "FAILED""#),
+            None
+        );
+        assert_eq!(
+            matches(r#"partition == 2 or value.status == "FAILED""#),
+            Some(true)
+        );
+        assert_eq!(
+            matches(r#"!(partition == 2 or key == "my-key")"#),
+            Some(false)
+        );
+        assert_eq!(matches(r#"!(partition == 1 or key == "my-key")"#), None);
+
+        // Search filters are not called when metadata predicates decide
+        let matches_with_filter = |input: &str| {
+            lib::SearchQuery::parse(input)
+                .unwrap()
+                .1
+                .matches_metadata(&context)
+        };
+        assert_eq!(
+            matches_with_filter("partition == 1 and count_calls()"),
+            Some(false)
+        );
+        assert_eq!(
+            matches_with_filter("count_calls() and partition == 1"),
+            Some(false)
+        );
+        assert_eq!(
+            matches_with_filter("count_calls() or partition == 2"),
+            Some(true)
+        );
+        assert_eq!(
+            matches_with_filter("count_calls() and partition == 2"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_timestamp_range() {
+        let filters_directory = PathBuf::from(".");
+        let range_of = |input: &str| {
+            ValidSearchQuery::from(input, &filters_directory)
+                .unwrap()
+                .timestamp_range()
+        };
+        let from = 1_732_359_600_000;
+        let to = 1_732_370_400_000;
+
+        assert_eq!(range_of("from begin"), TimestampRange::default());
+        assert_eq!(
+            range_of(r#"timestamp >= "2024-11-23T12:00:00.000+01:00""#),
+            TimestampRange::new(Some(from), None)
+        );
+        assert_eq!(
+            range_of(
+                r#"timestamp between "2024-11-23T12:00:00.000+01:00" and "2024-11-23T15:00:00.000+01:00""#
+            ),
+            TimestampRange::new(Some(from), Some(to))
+        );
+        assert_eq!(
+            range_of(
+                r#"key == "my-key" and timestamp > "2024-11-23T12:00:00.000+01:00" and timestamp < "2024-11-23T15:00:00.000+01:00""#
+            ),
+            TimestampRange::new(Some(from), Some(to))
+        );
+        assert_eq!(
+            range_of(
+                r#"timestamp < "2024-11-23T12:00:00.000+01:00" or timestamp > "2024-11-23T15:00:00.000+01:00""#
+            ),
+            TimestampRange::default()
+        );
+        assert_eq!(
+            range_of(r#"timestamp == "2024-11-23T12:00:00.000+01:00" or key == "my-key""#),
+            TimestampRange::default()
+        );
+        assert_eq!(
+            range_of(r#"!(timestamp > "2024-11-23T12:00:00.000+01:00")"#),
+            TimestampRange::default()
+        );
+        assert!(
+            range_of(
+                r#"timestamp > "2024-11-23T15:00:00.000+01:00" and timestamp < "2024-11-23T12:00:00.000+01:00""#
+            )
+            .is_empty()
+        );
+    }
 }
@@ -0,0 +1,278 @@
+//! Query planner deriving, from the timestamp predicates of the search query,
+//! where the consumer should start and stop reading each partition.
+
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use lib::{Error, SearchQuery, search::offset::FromOffset};
+use rdkafka::{Message, Offset, Timestamp, TopicPartitionList, consumer::Consumer};
+use tracing::info;
+
+use super::Search;
+
+/// Range of timestamps, in milliseconds, a record must belong to in order to match the search query.
+/// A `None` bound means the range is unbounded on that side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TimestampRange {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+impl TimestampRange {
+    pub fn new(from: Option<i64>, to: Option<i64>) -> Self {
+        Self { from, to }
+    }
+
+    /// A range no record belongs to.
+    pub fn empty() -> Self {
+        Self::new(Some(i64::MAX), Some(i64::MIN))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        matches!((self.from, self.to), (Some(from), Some(to)) if from > to)
+    }
+
+    /// Range of records matching both predicates.
+    pub fn intersection(self, other: Self) -> Self {
+        Self {
+            from: self.from.max(other.from),
+            to: match (self.to, other.to) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            },
+        }
+    }
+
+    /// Smallest range containing the records matching at least one of the predicates.
+    pub fn union(self, other: Self) -> Self {
+        if self.is_empty() {
+            return other;
+        }
+        if other.is_empty() {
+            return self;
+        }
+        Self {
+            from: self.from.zip(other.from).map(|(a, b)| a.min(b)),
+            to: self.to.zip(other.to).map(|(a, b)| a.max(b)),
+        }
+    }
+}
+
+/// Plans where the consumer starts and stops reading the partitions of a topic.
+///
+/// The lower bound of the timestamp range is used to seek, with `offsets_for_times`, to the earliest offset
+/// that can contain a matching record. The upper bound is used to find the offset from which
+/// records are too recent to match, so the partition can be stopped early.
+/// Timestamps only increase with offsets when the broker sets them (`LogAppendTime`):
+/// a producer may set an older timestamp (`CreateTime`) after that offset, see [`StopOffsets::stop`].
+#[derive(Debug, Clone)]
+pub struct QueryPlanner {
+    offset: Option<FromOffset>,
+    range: TimestampRange,
+}
+
+impl QueryPlanner {
+    pub fn new(query: &SearchQuery) -> Self {
+        Self {
+            offset: query.offset(),
+            range: query.timestamp_range(),
+        }
+    }
+
+    /// Returns the assignments of the given partitions of the topic.
+    /// The offsets at which partitions must be stopped are added to `stop_offsets`.
+    pub fn plan<C: Consumer>(
+        &self,
+        consumer: &C,
+        topic: &str,
+        partitions: &[i32],
+        stop_offsets: &mut StopOffsets,
+    ) -> Result<TopicPartitionList, Error> {
+        if self.range.is_empty() {
+            info!(
+                "Topic '{topic}' is skipped since the timestamp predicates of the search query can't be satisfied"
+            );
+            return Ok(TopicPartitionList::new());
+        }
+
+        let start = match (&self.offset, self.range.from) {
+            (None, None) => Self::assign_partitions(topic, partitions, Offset::End),
+            (None, Some(from)) => Self::seek(consumer, topic, partitions, from)?,
+            (Some(FromOffset::Beginning), None) => {
+                Self::assign_partitions(topic, partitions, Offset::Beginning)
+            }
+            (Some(FromOffset::Beginning), Some(from)) => {
+                Self::seek(consumer, topic, partitions, from)?
+            }
+            (Some(FromOffset::End), _) => Self::assign_partitions(topic, partitions, Offset::End),
+            (Some(FromOffset::Offset(o)), None) => {
+                Self::assign_partitions(topic, partitions, Offset::Offset(*o))
+            }
+            (Some(FromOffset::Offset(o)), Some(from)) => {
+                let mut seeks = Self::seek(consumer, topic, partitions, from)?;
+                for elem in seeks.clone().elements() {
+                    if let Offset::Offset(s) = elem.offset() {
+                        seeks.set_partition_offset(
+                            topic,
+                            elem.partition(),
+                            Offset::Offset(s.max(*o)),
+                        )?;
+                    }
+                }
+                seeks
+            }
+            (Some(FromOffset::OffsetTail(o)), _) => {
+                Self::assign_partitions(topic, partitions, Offset::OffsetTail(*o))
+            }
+            (Some(FromOffset::Timestamp(timestamp)), from) => Self::seek(
+                consumer,
+                topic,
+                partitions,
+                from.map_or(*timestamp, |from| from.max(*timestamp)),
+            )?,
+        };
+
+        let Some(to) = self.range.to else {
+            return Ok(start);
+        };
+        let ends = Self::seek(consumer, topic, partitions, to.saturating_add(1))?;
+        let mut assignments = TopicPartitionList::new();
+        for elem in start.elements() {
+            let end = ends
+                .find_partition(topic, elem.partition())
+                .and_then(|e| e.offset().to_raw());
+            if let Some(end) = end.filter(|end| *end >= 0) {
+                stop_offsets.insert(topic, elem.partition(), end);
+            }
+            assignments.add_partition_offset(topic, elem.partition(), elem.offset())?;
+        }
+        Ok(assignments)
+    }
+
+    /// Returns, for each partition, the earliest offset whose timestamp is greater than or equal to the given timestamp.
+    fn seek<C: Consumer>(
+        consumer: &C,
+        topic: &str,
+        partitions: &[i32],
+        timestamp: i64,
+    ) -> Result<TopicPartitionList, Error> {
+        let assignments = Self::assign_partitions(topic, partitions, Offset::Offset(timestamp));
+        if assignments.count() == 0 {
+            return Ok(assignments);
+        }
+        Ok(consumer.offsets_for_times(assignments, Duration::from_secs(60))?)
+    }
+
+    /// Assigns topics to a consumer
+    fn assign_partitions(topic: &str, partitions: &[i32], offset: Offset) -> TopicPartitionList {
+        let mut assignments = TopicPartitionList::new();
+        for partition in partitions {
+            assignments
+                .add_partition_offset(topic, *partition, offset)
+                .expect("Failed to add partition to assignment in 'assign_partitions' function");
+        }
+        assignments
+    }
+}
+
+/// Offsets from which the records of a partition are too recent to match the search query.
+#[derive(Debug, Clone, Default)]
+pub struct StopOffsets {
+    offsets: HashMap<(String, i32), i64>,
+    stopped: HashSet<(String, i32)>,
+    partitions: usize,
+}
+
+impl StopOffsets {
+    pub(crate) fn insert(&mut self, topic: &str, partition: i32, offset: i64) {
+        self.offsets.insert((topic.to_string(), partition), offset);
+    }
+
+    /// Sets the number of partitions assigned to the consumer.
+    pub(crate) fn set_partitions(&mut self, partitions: usize) {
+        self.partitions = partitions;
+    }
+
+    /// Returns `true` if the partition of the message must not be read anymore.
+    /// The first time, the partition is paused so the consumer doesn't fetch its records anymore.
+    ///
+    /// Only partitions whose timestamps are set by the broker are stopped, records with a timestamp set by the producer
+    /// may be older than the previous ones and still match the search query.
+    pub fn stop<C: Consumer, M: Message>(&mut self, consumer: &C, message: &M) -> bool {
+        if !self.too_recent(message) {
+            return false;
+        }
+        let key = (message.topic().to_string(), message.partition());
+        if !self.stopped.contains(&key) {
+            info!(
+                "Stop consuming partition {} of topic '{}', next records are too recent to match the search query",
+                key.1, key.0
+            );
+            let mut partition = TopicPartitionList::new();
+            partition.add_partition(&key.0, key.1);
+            let _ = consumer.pause(&partition);
+            self.stopped.insert(key);
+        }
+        true
+    }
+
+    /// Returns `true` when the message and the next ones of its partition are too recent to match the search query.
+    fn too_recent<M: Message>(&self, message: &M) -> bool {
+        let key = (message.topic().to_string(), message.partition());
+        matches!(self.offsets.get(&key), Some(end) if message.offset() >= *end)
+            && matches!(message.timestamp(), Timestamp::LogAppendTime(_))
+    }
+
+    /// Returns `true` when all the assigned partitions have been stopped.
+    pub fn all_stopped(&self) -> bool {
+        self.partitions > 0 && self.stopped.len() >= self.partitions
+    }
+}
+
+#[test]
+fn test_timestamp_range() {
+    let range = TimestampRange::new(Some(10), Some(20));
+    assert_eq!(
+        range.intersection(TimestampRange::new(Some(15), None)),
+        TimestampRange::new(Some(15), Some(20))
+    );
+    assert_eq!(
+        range.union(TimestampRange::new(Some(15), None)),
+        TimestampRange::new(Some(10), None)
+    );
+    assert!(
+        range
+            .intersection(TimestampRange::new(Some(30), None))
+            .is_empty()
+    );
+    assert_eq!(range.union(TimestampRange::empty()), range);
+    assert_eq!(
+        TimestampRange::default().intersection(range),
+        TimestampRange::new(Some(10), Some(20))
+    );
+}
+
+#[test]
+fn test_stop_offsets_with_timestamps_set_by_the_broker() {
+    use rdkafka::message::OwnedMessage;
+    let message = |offset, timestamp| {
+        OwnedMessage::new(
+            None,
+            None,
+            "payments".to_string(),
+            timestamp,
+            0,
+            offset,
+            None,
+        )
+    };
+    let mut stop_offsets = StopOffsets::default();
+    stop_offsets.insert("payments", 0, 10);
+    assert!(!stop_offsets.too_recent(&message(9, Timestamp::LogAppendTime(0))));
+    assert!(stop_offsets.too_recent(&message(10, Timestamp::LogAppendTime(0))));
+    // Producers may set an older timestamp on the next records
+    assert!(!stop_offsets.too_recent(&message(10, Timestamp::CreateTime(0))));
+    assert!(!stop_offsets.too_recent(&message(42, Timestamp::CreateTime(0))));
+}
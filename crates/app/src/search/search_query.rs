@@ -1,9 +1,10 @@
 use lib::{
     SearchQuery,
+    kafka::RecordFields,
     search::{filter::Filter, offset::FromOffset},
 };
 
-use super::{Search, SearchContext};
+use super::{PartitionContext, Search, SearchContext, planner::TimestampRange};
 
 impl Search for SearchQuery {
     fn offset(&self) -> Option<FromOffset> {
@@ -14,7 +15,36 @@ impl Search for SearchQuery {
         self.expression.matches(context)
     }
 
+    fn matches_partition(&self, context: &PartitionContext) -> Option<bool> {
+        // Transformers may rewrite the key
+        let context = PartitionContext {
+            raw_keys: context.raw_keys && self.transforms.is_empty(),
+            ..*context
+        };
+        self.expression.matches_partition(&context)
+    }
+
+    fn timestamp_range(&self) -> TimestampRange {
+        self.expression.timestamp_range()
+    }
+
     fn filters(&self) -> Vec<Filter> {
         self.expression.filters()
     }
+
+    fn fields(&self) -> RecordFields {
+        self.expression.fields()
+    }
+
+    fn cost(&self) -> u64 {
+        self.expression.cost()
+    }
+
+    fn latest_by_key(&self) -> bool {
+        self.latest_by_key
+    }
+
+    fn matches_metadata(&self, context: &SearchContext) -> Option<bool> {
+        self.expression.matches_metadata(context)
+    }
 }
@@ -1,6 +1,9 @@
-use lib::search::{filter::Filter, offset::FromOffset, term::Term};
+use lib::{
+    kafka::RecordFields,
+    search::{filter::Filter, offset::FromOffset, term::Term},
+};
 
-use super::{Search, SearchContext};
+use super::{PartitionContext, Search, SearchContext, planner::TimestampRange};
 
 impl Search for Term {
     fn offset(&self) -> Option<FromOffset> {
@@ -17,10 +20,44 @@ impl Search for Term {
         }
     }
 
+    fn matches_partition(&self, context: &PartitionContext) -> Option<bool> {
+        match self {
+            Term::Not(a) => a.matches_partition(context).map(|m| !m),
+            Term::Atom(a) => a.matches_partition(context),
+        }
+    }
+
+    fn timestamp_range(&self) -> TimestampRange {
+        match self {
+            // The complement of an over-approximated range is not a safe approximation.
+            Term::Not(_) => TimestampRange::default(),
+            Term::Atom(a) => a.timestamp_range(),
+        }
+    }
+
     fn filters(&self) -> Vec<Filter> {
         match self {
             Term::Not(atom) => atom.filters(),
             Term::Atom(atom) => atom.filters(),
         }
     }
+
+    fn cost(&self) -> u64 {
+        match self {
+            Term::Not(atom) | Term::Atom(atom) => atom.cost(),
+        }
+    }
+
+    fn fields(&self) -> RecordFields {
+        match self {
+            Term::Not(atom) | Term::Atom(atom) => atom.fields(),
+        }
+    }
+
+    fn matches_metadata(&self, context: &SearchContext) -> Option<bool> {
+        match self {
+            Term::Not(a) => a.matches_metadata(context).map(|m| !m),
+            Term::Atom(a) => a.matches_metadata(context),
+        }
+    }
 }
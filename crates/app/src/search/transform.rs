@@ -0,0 +1,252 @@
+//! Transformers rewrite kafka records before they are matched against the search query and displayed.
+//! A transformer is a wasm module, imported like a search filter, that exports a `transform` function:
+//! it receives the same JSON input as a search filter and returns the rewritten kafka record.
+//!
+//! Transformers configured for a topic run first, then the ones of the search query, in the order they are written.
+//! A record a transformer fails on, or a disabled transformer, is excluded from the results of the search:
+//! the first failure of each transformer is logged as an error, the next ones are counted in its metrics.
+
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use indexmap::IndexMap;
+use itertools::Itertools;
+use lib::{
+    KafkaRecord,
+    search::filter::{Filter, Parameter},
+};
+use tracing::{debug, error};
+
+use super::{
+    filter::{FilterInputRef, FilterPool, ModuleKind, TRANSFORM_FUNCTION_NAME, load_filter},
+    host_functions::SearchSession,
+};
+use crate::configuration::FilterLimits;
+
+/// A transformer and its parameters.
+#[derive(Clone)]
+pub struct Transformer {
+    filter: Filter,
+    pool: Arc<FilterPool>,
+    /// Whether a failure has already been logged
+    failed: Arc<AtomicBool>,
+}
+
+impl Transformer {
+    fn load(
+        filter: &Filter,
+        filters_directory: &Path,
+        filter_limits: &IndexMap<String, FilterLimits>,
+    ) -> Result<Self, lib::Error> {
+        let limits = filter_limits.get(&filter.name).cloned().unwrap_or_default();
+        let pool = load_filter(
+            &filter.name,
+            filters_directory,
+            &limits,
+            ModuleKind::Transformer,
+        )?;
+        if !pool.function_exists(TRANSFORM_FUNCTION_NAME) {
+            return Err(lib::Error::Error(format!(
+                "'{}' is not a transformer, the wasm module doesn't export a '{TRANSFORM_FUNCTION_NAME}' function",
+                filter.name
+            )));
+        }
+        pool.parse_parameters(&filter.parameters)?;
+        Ok(Self {
+            filter: filter.clone(),
+            pool,
+            failed: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    fn transform(
+        &self,
+        record: &KafkaRecord,
+        session: Option<&Arc<SearchSession>>,
+    ) -> Result<KafkaRecord, lib::Error> {
+        if let Some(reason) = self.pool.disabled() {
+            return Err(lib::Error::Error(reason.to_string()));
+        }
+        let input = serde_json::to_vec(&FilterInputRef {
+            record,
+            params: self
+                .filter
+                .parameters
+                .iter()
+                .map(Parameter::json)
+                .collect_vec(),
+        })?;
+        let mut transformed = self
+            .pool
+            .transform(&input, session)
+            .map_err(|e| lib::Error::Error(format!("{}: {e}", self.filter)))?;
+        transformed.normalize();
+        Ok(transformed)
+    }
+}
+
+/// Transformers applied to the kafka records, by topic.
+#[derive(Clone, Default)]
+pub struct Transforms {
+    all_topics: Vec<Transformer>,
+    by_topic: HashMap<String, Vec<Transformer>>,
+}
+
+impl Transforms {
+    /// Loads the transformers of the search query and the ones configured for each topic.
+    pub fn load(
+        query: &[Filter],
+        by_topic: &IndexMap<String, Vec<Filter>>,
+        filters_directory: &Path,
+        filter_limits: &IndexMap<String, FilterLimits>,
+    ) -> Result<Self, lib::Error> {
+        let load = |filters: &[Filter]| {
+            filters
+                .iter()
+                .map(|f| Transformer::load(f, filters_directory, filter_limits))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        Ok(Self {
+            all_topics: load(query)?,
+            by_topic: by_topic
+                .iter()
+                .map(|(topic, filters)| Ok((topic.clone(), load(filters)?)))
+                .collect::<Result<_, lib::Error>>()?,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.all_topics.is_empty() && self.by_topic.values().all(Vec::is_empty)
+    }
+
+    /// Names of the transformers.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.all_topics
+            .iter()
+            .chain(self.by_topic.values().flatten())
+            .map(|t| t.filter.name.as_str())
+    }
+
+    /// Returns `true` when the kafka records of the topic are rewritten.
+    pub fn applies_to(&self, topic: &str) -> bool {
+        !self.all_topics.is_empty() || self.by_topic.get(topic).is_some_and(|t| !t.is_empty())
+    }
+
+    /// Rewrites the kafka record with the transformers of its topic, then the ones of the search query.
+    /// It fails when a transformer fails or is disabled, the record must then be excluded from the results.
+    /// Host functions of the transformers get the session of the search, if any.
+    pub fn apply(
+        &self,
+        record: &KafkaRecord,
+        session: Option<&Arc<SearchSession>>,
+    ) -> Result<KafkaRecord, lib::Error> {
+        let transformers = self
+            .by_topic
+            .get(&record.topic)
+            .into_iter()
+            .flatten()
+            .chain(&self.all_topics);
+        let mut transformed = None;
+        for transformer in transformers {
+            let current = transformed.as_ref().unwrap_or(record);
+            let next = transformer.transform(current, session).inspect_err(|e| {
+                let message = format!(
+                    "Cannot transform the record {}-{} of '{}', it is excluded from the results: {e}",
+                    record.partition, record.offset, record.topic
+                );
+                match transformer.failed.swap(true, Ordering::Relaxed) {
+                    false => error!("{message}"),
+                    true => debug!("{message}"),
+                }
+            })?;
+            transformed = Some(next);
+        }
+        Ok(transformed.unwrap_or_else(|| record.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::search::wasm_fixtures::{self, FiltersDirectory};
+
+    #[test]
+    fn test_apply_transforms() {
+        let directory = FiltersDirectory::new();
+        directory.add(
+            "decrypt-test",
+            &wasm_fixtures::returning(
+                TRANSFORM_FUNCTION_NAME,
+                r#"{"topic":"payments","timestamp":null,"partition":0,"offset":3,"headers":{},"key":"k1","value":{"amount":10}}"#,
+            ),
+        );
+        let transform = "decrypt-test('k1')".parse::<Filter>().unwrap();
+        let transforms = Transforms::load(
+            &[],
+            &IndexMap::from([("payments".to_string(), vec![transform])]),
+            directory.path(),
+            &IndexMap::new(),
+        )
+        .unwrap();
+        assert!(!transforms.is_empty());
+        assert!(transforms.applies_to("payments"));
+        assert!(!transforms.applies_to("orders"));
+
+        let record = transforms
+            .apply(
+                &KafkaRecord {
+                    topic: "payments".to_string(),
+                    offset: 3,
+                    ..Default::default()
+                },
+                None,
+            )
+            .unwrap();
+        assert_eq!(record.key, lib::DataType::String("k1".to_string()));
+        assert_eq!(record.key_as_string, "k1");
+        assert_eq!(record.value_as_string, r#"{"amount":10}"#);
+    }
+
+    #[test]
+    fn test_failed_transformation_is_an_error() {
+        let directory = FiltersDirectory::new();
+        directory.add(
+            "broken-transformer-test",
+            &wasm_fixtures::returning(TRANSFORM_FUNCTION_NAME, "not a kafka record"),
+        );
+        let transform = "broken-transformer-test()".parse::<Filter>().unwrap();
+        let transforms = Transforms::load(
+            &[transform],
+            &IndexMap::new(),
+            directory.path(),
+            &IndexMap::new(),
+        )
+        .unwrap();
+        assert_eq!(
+            transforms.names().collect_vec(),
+            vec!["broken-transformer-test"]
+        );
+        for _ in 0..2 {
+            assert!(transforms.apply(&KafkaRecord::default(), None).is_err());
+        }
+    }
+
+    #[test]
+    fn test_search_filter_is_not_a_transformer() {
+        let directory = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("http_search_filter");
+        let filter = "module()".parse::<Filter>().unwrap();
+        assert!(
+            Transforms::load(&[filter], &IndexMap::new(), &directory, &IndexMap::new()).is_err()
+        );
+    }
+}
@@ -0,0 +1,139 @@
+//! WebAssembly modules used by the tests, written in the WebAssembly text format.
+//! Extism compiles a `.wasm` file written in the text format as well.
+//! Other crates of the workspace use them through the `test-fixtures` feature.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use tempfile::TempDir;
+
+/// A temporary filters directory, it is deleted once dropped.
+pub struct FiltersDirectory {
+    directory: TempDir,
+}
+
+impl FiltersDirectory {
+    pub fn new() -> Self {
+        Self {
+            directory: tempfile::tempdir().expect("Failed to create temp directory"),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        self.directory.path()
+    }
+
+    /// Writes the module `<name>.wasm` and returns its path.
+    pub fn add(&self, name: &str, module: &str) -> PathBuf {
+        let path = self.path().join(format!("{name}.wasm"));
+        fs::write(&path, module).unwrap();
+        path
+    }
+}
+
+impl Default for FiltersDirectory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A module whose `function` returns `output`. It accepts any parameter.
+pub fn returning(function: &str, output: &str) -> String {
+    format!(
+        r#"(module
+  (import "extism:host/env" "alloc" (func $alloc (param i64) (result i64)))
+  (import "extism:host/env" "store_u8" (func $store_u8 (param i64 i32)))
+  (import "extism:host/env" "output_set" (func $output_set (param i64 i64)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "{data}")
+  (func (export "parse_parameters") (result i32) (i32.const 0))
+  (func (export "{function}") (result i32)
+    (local $offset i64) (local $i i32)
+    (local.set $offset (call $alloc (i64.const {length})))
+    (block $done
+      (loop $copy
+        (br_if $done (i32.ge_u (local.get $i) (i32.const {length})))
+        (call $store_u8
+          (i64.add (local.get $offset) (i64.extend_i32_u (local.get $i)))
+          (i32.load8_u (local.get $i)))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $copy)))
+    (call $output_set (local.get $offset) (i64.const {length}))
+    (i32.const 0)))"#,
+        data = output.replace('\\', "\\\\").replace('"', "\\\""),
+        length = output.len()
+    )
+}
+
+/// A module whose `function` returns its input.
+pub fn echo(function: &str) -> String {
+    format!(
+        r#"(module
+  (import "extism:host/env" "input_length" (func $input_length (result i64)))
+  (import "extism:host/env" "input_offset" (func $input_offset (result i64)))
+  (import "extism:host/env" "output_set" (func $output_set (param i64 i64)))
+  (memory (export "memory") 1)
+  (func (export "parse_parameters") (result i32) (i32.const 0))
+  (func (export "{function}") (result i32)
+    (call $output_set (call $input_offset) (call $input_length))
+    (i32.const 0)))"#
+    )
+}
+
+/// A search filter whose `matches` function grows its memory by `pages` pages of 64KiB.
+pub fn growing_memory(pages: u32) -> String {
+    format!(
+        r#"(module
+  (memory 1)
+  (func (export "parse_parameters") (result i32) (i32.const 0))
+  (func (export "matches") (result i32) (drop (memory.grow (i32.const {pages}))) (i32.const 0)))"#
+    )
+}
+
+/// A search filter whose `matches` function never returns.
+pub fn infinite_loop() -> String {
+    r#"(module
+  (func (export "parse_parameters") (result i32) (i32.const 0))
+  (func (export "matches") (result i32) (loop $forever (br $forever)) (i32.const 0)))"#
+        .to_string()
+}
+
+/// A search filter matching the first record it evaluates during a search, whatever its topic partition.
+/// It swaps the key `seen` of the key-value store and matches when there was no previous value.
+pub fn first_seen() -> String {
+    r#"(module
+  (import "extism:host/env" "alloc" (func $alloc (param i64) (result i64)))
+  (import "extism:host/env" "store_u8" (func $store_u8 (param i64 i32)))
+  (import "extism:host/env" "length" (func $length (param i64) (result i64)))
+  (import "extism:host/env" "output_set" (func $output_set (param i64 i64)))
+  (import "extism:host/user" "yozefu_store_swap" (func $swap (param i64 i64) (result i64)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "seen")
+  (data (i32.const 8) "1")
+  (data (i32.const 16) "{\"match\":true}")
+  (data (i32.const 32) "{\"match\":false}")
+  (func $write (param $from i32) (param $length i32) (result i64)
+    (local $offset i64) (local $i i32)
+    (local.set $offset (call $alloc (i64.extend_i32_u (local.get $length))))
+    (block $done
+      (loop $copy
+        (br_if $done (i32.ge_u (local.get $i) (local.get $length)))
+        (call $store_u8
+          (i64.add (local.get $offset) (i64.extend_i32_u (local.get $i)))
+          (i32.load8_u (i32.add (local.get $from) (local.get $i))))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $copy)))
+    (local.get $offset))
+  (func (export "parse_parameters") (result i32) (i32.const 0))
+  (func (export "matches") (result i32)
+    ;; The previous value is `null` the first time
+    (if (i64.eq
+          (call $length (call $swap (call $write (i32.const 0) (i32.const 4)) (call $write (i32.const 8) (i32.const 1))))
+          (i64.const 4))
+      (then (call $output_set (call $write (i32.const 16) (i32.const 14)) (i64.const 14)))
+      (else (call $output_set (call $write (i32.const 32) (i32.const 15)) (i64.const 15))))
+    (i32.const 0)))"#
+        .to_string()
+}
@@ -15,6 +15,8 @@ fn check_backwards_compatibility() {
         theme: "default".to_string(),
         default_kafka_config: IndexMap::new(),
         history: Vec::default(),
+        saved_queries: IndexMap::new(),
+        filter_limits: IndexMap::new(),
         show_shortcuts: false,
         export_directory: PathBuf::from("./yozefu-exports"),
         consumer: ConsumerConfig::default(),
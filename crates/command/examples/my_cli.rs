@@ -45,7 +45,12 @@ impl MyCli {
             consumer: Some(ConsumerConfig {
                 buffer_capacity: 1000,
                 timeout_in_ms: 100,
+                workers: None,
             }),
+            custom_partitioner: false,
+            saved_queries: IndexMap::new(),
+            transforms: IndexMap::new(),
+            deserializers: IndexMap::new(),
         }
         .create(&cluster.to_string())
     }
@@ -1,4 +1,4 @@
-//! Command to create a new wasm filter.
+//! Command to create a new wasm filter or transformer.
 use std::{
     fs,
     path::{Path, PathBuf},
@@ -25,6 +25,9 @@ pub(crate) struct CreateFilterCommand {
     #[clap(long)]
     /// Location of the search filter repository
     directory: Option<PathBuf>,
+    #[clap(long, default_value_t = BlueprintKind::Filter)]
+    /// Kind of WebAssembly module to create
+    kind: BlueprintKind,
     /// Name of the search filter
     name: String,
 }
@@ -36,8 +39,17 @@ pub enum SupportedLanguages {
     Golang,
 }
 
+/// Kinds of WebAssembly modules that have a blueprint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, EnumIter, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum BlueprintKind {
+    Filter,
+    Transformer,
+}
+
 impl Command for CreateFilterCommand {
     async fn execute(&self) -> Result<(), Error> {
+        let blueprint = self.blueprint()?;
         let repo_dir = match &self.directory {
             Some(d) => d.clone(),
             None => std::env::temp_dir().join(format!(
@@ -52,7 +64,7 @@ impl Command for CreateFilterCommand {
 
         match Self::clone_repository(&repo_dir) {
             Ok(()) => {
-                self.prepare_git_repository(&repo_dir)?;
+                Self::prepare_git_repository(&repo_dir, &blueprint)?;
 
                 info!(
                     "The filter repository has been initialized: '{}'",
@@ -89,13 +101,28 @@ impl Command for CreateFilterCommand {
 }
 
 impl CreateFilterCommand {
+    /// Returns the directory of the blueprint in [`wasm-blueprints`](https://github.com/MAIF/yozefu/tree/main/crates/wasm-blueprints).
+    /// Transformers only have a Rust blueprint.
+    fn blueprint(&self) -> Result<String, Error> {
+        match (self.kind, &self.language) {
+            (BlueprintKind::Filter, language) => Ok(language.to_string()),
+            (BlueprintKind::Transformer, SupportedLanguages::Rust) => {
+                Ok(format!("{}-transformer", SupportedLanguages::Rust))
+            }
+            (BlueprintKind::Transformer, language) => Err(Error::Error(format!(
+                "There is no transformer blueprint in {language}, only in {}",
+                SupportedLanguages::Rust
+            ))),
+        }
+    }
+
     /// Clones the [`wasm-blueprints`](https://github.com/MAIF/yozefu/tree/main/crates/wasm-blueprints) repository
-    /// and reorganizes directories to keep only the programming language selected by the user.
-    fn prepare_git_repository(&self, repo_dir: &Path) -> Result<(), Error> {
+    /// and reorganizes directories to keep only the blueprint selected by the user.
+    fn prepare_git_repository(repo_dir: &Path, blueprint: &str) -> Result<(), Error> {
         let source = repo_dir
             .join("crates")
             .join("wasm-blueprints")
-            .join(self.language.to_string());
+            .join(blueprint);
         let temp = repo_dir.parent().unwrap().join(
             repo_dir
                 .file_name()
@@ -155,6 +182,7 @@ async fn test_success() {
     let command = CreateFilterCommand {
         language: SupportedLanguages::Rust,
         directory: Some(temp_dir.path().to_path_buf()),
+        kind: BlueprintKind::Filter,
         name: "sha256".to_string(),
     };
 
@@ -167,3 +195,30 @@ async fn test_success() {
         "Running this command should create a directory containing the code for the search filter"
     )
 }
+
+#[test]
+fn test_blueprint() {
+    let command = |language, kind| CreateFilterCommand {
+        language,
+        directory: None,
+        kind,
+        name: "unwrap-value".to_string(),
+    };
+    assert_eq!(
+        command(SupportedLanguages::Golang, BlueprintKind::Filter)
+            .blueprint()
+            .unwrap(),
+        "golang"
+    );
+    assert_eq!(
+        command(SupportedLanguages::Rust, BlueprintKind::Transformer)
+            .blueprint()
+            .unwrap(),
+        "rust-transformer"
+    );
+    assert!(
+        command(SupportedLanguages::Golang, BlueprintKind::Transformer)
+            .blueprint()
+            .is_err()
+    );
+}
@@ -0,0 +1,320 @@
+//! Commands to manage the search filters of the workspace.
+//!
+//! ```bash
+//! yozf filter list
+//! yozf filter remove key-ends-with
+//! yozf filter test key-ends-with --params "'1234'" --records export.json
+//! ```
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use app::{
+    configuration::GlobalConfig,
+    search::{
+        filter::{ModuleKind, load_filter},
+        host_functions::SearchSession,
+        imported_filter::imported_filter,
+    },
+};
+use clap::{Args, Subcommand};
+use itertools::Itertools;
+use lib::{Error, KafkaRecord, search::filter::Filter};
+use strum::{Display, EnumString};
+use tracing::info;
+
+use crate::{GlobalArgs, command::Command};
+
+use super::ListFiltersCommand;
+
+/// Command to manage the search filters.
+#[derive(Debug, Args, Clone)]
+pub(crate) struct FilterCommand {
+    #[command(subcommand)]
+    pub subcommand: FilterSubCommand,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+pub(crate) enum FilterSubCommand {
+    /// List the imported search filters with their size, modification date and checksum
+    List(ListFiltersCommand),
+    /// Remove a search filter, its data directory and its resource limits
+    Remove(RemoveFilterCommand),
+    /// Run a search filter against kafka records of a JSON file, no cluster is needed
+    Test(TestFilterCommand),
+}
+
+impl Command for FilterCommand {
+    async fn execute(&self) -> Result<(), Error> {
+        match &self.subcommand {
+            FilterSubCommand::List(command) => command.execute().await,
+            FilterSubCommand::Remove(command) => command.execute().await,
+            FilterSubCommand::Test(command) => command.execute().await,
+        }
+    }
+}
+
+#[derive(Debug, Args, Clone)]
+pub(crate) struct RemoveFilterCommand {
+    /// Name of the search filter
+    name: String,
+    #[command(flatten)]
+    global: GlobalArgs,
+}
+
+impl Command for RemoveFilterCommand {
+    async fn execute(&self) -> Result<(), Error> {
+        let workspace = self.global.workspace();
+        let filter = imported_filter(&workspace.filters_dir(), &self.name)?;
+        let usages = usages(workspace.config(), &self.name);
+        if !usages.is_empty() {
+            return Err(Error::Error(format!(
+                "'{}' cannot be removed, it is used as a {}. Edit '{}' first",
+                self.name,
+                usages.join(", "),
+                workspace.config_file().display()
+            )));
+        }
+        filter.remove()?;
+        info!("'{}' has been removed", filter.path.display());
+
+        let mut config = workspace.config().clone();
+        if config.filter_limits.shift_remove(&self.name).is_some() {
+            let file = workspace.config_file();
+            fs::write(&file, serde_json::to_string_pretty(&config)?)?;
+            info!(
+                "Resource limits of '{}' have been removed from '{}'",
+                self.name,
+                file.display()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Describes where the configuration uses the search filter as a transformer or a deserializer.
+fn usages(config: &GlobalConfig, name: &str) -> Vec<String> {
+    config
+        .clusters
+        .iter()
+        .flat_map(|(cluster, config)| {
+            let transforms = config
+                .transforms
+                .iter()
+                .filter(|(_, transforms)| {
+                    transforms
+                        .iter()
+                        .any(|t| t.parse::<Filter>().is_ok_and(|f| f.name == name))
+                })
+                .map(move |(topic, _)| {
+                    format!("transformer of topic '{topic}' in cluster '{cluster}'")
+                });
+            let deserializers = config
+                .deserializers
+                .iter()
+                .filter(|(_, deserializer)| *deserializer == name)
+                .map(move |(topic, _)| {
+                    format!("deserializer of topic '{topic}' in cluster '{cluster}'")
+                });
+            transforms.chain(deserializers).collect_vec()
+        })
+        .collect()
+}
+
+/// Expected outcome of the search filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, Display)]
+#[strum(serialize_all = "kebab-case")]
+pub(crate) enum Expectation {
+    Match,
+    NoMatch,
+}
+
+#[derive(Debug, Args, Clone)]
+pub(crate) struct TestFilterCommand {
+    /// Name of the search filter
+    name: String,
+    /// Parameters of the search filter, written like in a search query: `'1234', 2`
+    #[clap(long, default_value = "")]
+    params: String,
+    /// JSON file containing an array of kafka records, a file exported by yozefu for instance
+    #[clap(long)]
+    records: PathBuf,
+    /// Whether the kafka records are expected to match or not: `match` or `no-match`
+    #[clap(long, default_value_t = Expectation::Match)]
+    expect: Expectation,
+    #[command(flatten)]
+    global: GlobalArgs,
+}
+
+impl TestFilterCommand {
+    fn filter(&self) -> Result<Filter, Error> {
+        format!("{}({})", self.name, self.params)
+            .parse::<Filter>()
+            .map_err(|e| Error::Error(format!("Invalid parameters '{}': {e}", self.params)))
+    }
+
+    /// Reads the kafka records of the file, fields that are not part of a kafka record are ignored.
+    fn records(&self) -> Result<Vec<KafkaRecord>, Error> {
+        let content = fs::read_to_string(&self.records)
+            .map_err(|e| Error::Error(format!("Cannot read '{}': {e}", self.records.display())))?;
+        let mut records: Vec<KafkaRecord> = serde_json::from_str(&content)?;
+        records.iter_mut().for_each(KafkaRecord::normalize);
+        Ok(records)
+    }
+}
+
+impl Command for TestFilterCommand {
+    async fn execute(&self) -> Result<(), Error> {
+        self.run(&mut io::stdout())
+    }
+}
+
+impl TestFilterCommand {
+    /// Evaluates the kafka records and writes `PASS` or `FAIL` for each one.
+    fn run(&self, out: &mut impl Write) -> Result<(), Error> {
+        let workspace = self.global.workspace();
+        let filter = self.filter()?;
+        let limits = workspace.config().filter_limits_of(&self.name);
+        let pool = load_filter(
+            &self.name,
+            &workspace.filters_dir(),
+            &limits,
+            ModuleKind::SearchFilter,
+        )?;
+        pool.parse_parameters(&filter.parameters)?;
+
+        let records = self.records()?;
+        // The records are evaluated as a single search, they share the key-value store
+        let session = Arc::new(SearchSession::new(None));
+        let mut failures = 0;
+        for record in &records {
+            let outcome = match pool.evaluate(record, &filter.parameters, Some(&session)) {
+                Ok(true) => Expectation::Match.to_string(),
+                Ok(false) => Expectation::NoMatch.to_string(),
+                Err(e) => format!("error: {e}"),
+            };
+            let passed = outcome == self.expect.to_string();
+            if !passed {
+                failures += 1;
+            }
+            writeln!(
+                out,
+                "{} {} {}-{}: {outcome}",
+                if passed { "PASS" } else { "FAIL" },
+                record.topic,
+                record.partition,
+                record.offset
+            )?;
+        }
+
+        match failures {
+            0 => {
+                info!("{} records passed", records.len());
+                Ok(())
+            }
+            _ => Err(Error::Error(format!(
+                "{failures} out of {} records didn't pass, '{filter}' was expected to return '{}'",
+                records.len(),
+                self.expect
+            ))),
+        }
+    }
+}
+
+#[test]
+fn test_filter_and_records() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp directory");
+    let records = temp_dir.path().join("export.json");
+    fs::write(
+        &records,
+        r#"[{"topic":"payments","timestamp":null,"partition":0,"offset":3,"headers":{},"key":"k1","value":{"amount":10},"absolute_delta_in_ms":0,"relative_delta_in_ms":0,"search_query":""}]"#,
+    )
+    .unwrap();
+    let command = TestFilterCommand {
+        name: "key-ends-with".to_string(),
+        params: "'1234', 2".to_string(),
+        records,
+        expect: Expectation::NoMatch,
+        global: GlobalArgs::default(),
+    };
+    assert_eq!(
+        command.filter().unwrap().to_string(),
+        "key-ends-with('1234', 2)"
+    );
+    let records = command.records().unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].key_as_string, "k1");
+    assert_eq!("no-match".parse::<Expectation>(), Ok(Expectation::NoMatch));
+}
+
+#[test]
+fn test_run_search_filter() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp directory");
+    let global = GlobalArgs {
+        config_dir: Some(temp_dir.path().to_path_buf()),
+        ..Default::default()
+    };
+    fs::write(
+        global
+            .workspace()
+            .filters_dir()
+            .join("always-matches-test.wasm"),
+        app::search::wasm_fixtures::returning("matches", r#"{"match":true}"#),
+    )
+    .unwrap();
+    let records = temp_dir.path().join("export.json");
+    fs::write(
+        &records,
+        r#"[{"topic":"payments","timestamp":null,"partition":0,"offset":3,"headers":{},"key":"k1","value":{"amount":10}}]"#,
+    )
+    .unwrap();
+    let mut command = TestFilterCommand {
+        name: "always-matches-test".to_string(),
+        params: String::new(),
+        records,
+        expect: Expectation::Match,
+        global,
+    };
+    let mut out = vec![];
+    assert!(command.run(&mut out).is_ok());
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "PASS payments 0-3: match\n"
+    );
+
+    command.expect = Expectation::NoMatch;
+    let mut out = vec![];
+    assert_eq!(
+        command.run(&mut out).unwrap_err().to_string(),
+        "1 out of 1 records didn't pass, 'always-matches-test()' was expected to return 'no-match'"
+    );
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "FAIL payments 0-3: match\n"
+    );
+}
+
+#[test]
+fn test_usages() {
+    let mut config = GlobalConfig::new(&PathBuf::from("config.json"));
+    let mut cluster = app::configuration::ClusterConfig::default();
+    cluster
+        .transforms
+        .insert("payments".to_string(), vec!["decrypt('k1')".to_string()]);
+    cluster
+        .deserializers
+        .insert("orders".to_string(), "decrypt".to_string());
+    config.clusters.insert("dev".to_string(), cluster);
+    assert_eq!(
+        usages(&config, "decrypt"),
+        vec![
+            "transformer of topic 'payments' in cluster 'dev'",
+            "deserializer of topic 'orders' in cluster 'dev'"
+        ]
+    );
+    assert!(usages(&config, "key-ends-with").is_empty());
+}
@@ -1,10 +1,20 @@
 //! Command to import a search filter.
 use std::{fs, path::PathBuf};
 
-use app::search::filter::{MATCHES_FUNCTION_NAME, PARSE_PARAMETERS_FUNCTION_NAME};
+use app::{
+    configuration::FilterLimits,
+    search::{
+        filter::{
+            DESCRIBE_FUNCTION_NAME, DESERIALIZE_FUNCTION_NAME, MATCHES_FUNCTION_NAME, ModuleKind,
+            PARSE_PARAMETERS_FUNCTION_NAME, TRANSFORM_FUNCTION_NAME,
+        },
+        host_functions::host_functions,
+        imported_filter::ImportedFilter,
+    },
+};
 use clap::Args;
-use extism::{Manifest, Plugin, Wasm};
-use lib::Error;
+use extism::{Manifest, Plugin, Wasm, convert::Json};
+use lib::{Error, search::filter::FilterDescription};
 use tracing::info;
 
 use crate::{GlobalArgs, command::Command};
@@ -21,6 +31,15 @@ pub(crate) struct ImportFilterCommand {
     /// Overwrite the search filter file if it already exists
     #[clap(long)]
     force: bool,
+    /// Maximum number of WebAssembly instructions executed by a call to the search filter
+    #[clap(long)]
+    fuel: Option<u64>,
+    /// Maximum memory of an instance of the search filter, in MiB
+    #[clap(long)]
+    max_memory_in_mb: Option<u32>,
+    /// Maximum duration of a call to the search filter, in milliseconds
+    #[clap(long)]
+    timeout_in_ms: Option<u64>,
     #[command(flatten)]
     global: GlobalArgs,
 }
@@ -29,6 +48,35 @@ pub(crate) struct ImportFilterCommand {
 pub const REQUIRED_WASM_FUNCTIONS: [&str; 2] =
     [PARSE_PARAMETERS_FUNCTION_NAME, MATCHES_FUNCTION_NAME];
 
+/// Wasm functions a transformer must expose.
+pub const REQUIRED_TRANSFORMER_WASM_FUNCTIONS: [&str; 2] =
+    [PARSE_PARAMETERS_FUNCTION_NAME, TRANSFORM_FUNCTION_NAME];
+
+/// Wasm functions a deserializer must expose.
+pub const REQUIRED_DESERIALIZER_WASM_FUNCTIONS: [&str; 1] = [DESERIALIZE_FUNCTION_NAME];
+
+/// Wasm functions a module of the given kind must expose.
+fn required_functions(kind: ModuleKind) -> &'static [&'static str] {
+    match kind {
+        ModuleKind::SearchFilter => &REQUIRED_WASM_FUNCTIONS,
+        ModuleKind::Transformer => &REQUIRED_TRANSFORMER_WASM_FUNCTIONS,
+        ModuleKind::Deserializer => &REQUIRED_DESERIALIZER_WASM_FUNCTIONS,
+    }
+}
+
+/// Example of how to use the imported module.
+fn usage(kind: ModuleKind, name: &str) -> String {
+    match kind {
+        ModuleKind::SearchFilter => format!("`from begin offset > 50 && {name}(...)`"),
+        ModuleKind::Transformer => format!("`from begin offset > 50 | transform {name}(...)`"),
+        ModuleKind::Deserializer => {
+            format!(
+                "`\"deserializers\": {{ \"my-topic\": \"{name}\" }}` in the configuration of your cluster"
+            )
+        }
+    }
+}
+
 impl Command for ImportFilterCommand {
     async fn execute(&self) -> Result<(), Error> {
         let destination = self.destination()?;
@@ -40,10 +88,15 @@ impl Command for ImportFilterCommand {
             )));
         }
 
-        self.check_wasm_module(&self.file)?;
+        let (kind, description) = self.check_wasm_module(&self.file)?;
         fs::copy(&self.file, &destination)?;
+        self.save_limits()?;
+        self.save_description(description.as_ref())?;
         info!("'{}' has been imported successfully", destination.display());
-        info!("To use it: `from begin offset > 50 && {name}(...)`");
+        match description.as_ref().map(|d| d.examples.as_slice()) {
+            Some([example, ..]) => info!("To use it: `{example}`"),
+            _ => info!("To use it: {}", usage(kind, &name)),
+        }
 
         Ok(())
     }
@@ -67,25 +120,106 @@ impl ImportFilterCommand {
         }
     }
 
+    /// Returns the resource limits passed as arguments, if any.
+    fn limits(&self) -> Option<FilterLimits> {
+        if self.fuel.is_none() && self.max_memory_in_mb.is_none() && self.timeout_in_ms.is_none() {
+            return None;
+        }
+        let default = FilterLimits::default();
+        Some(FilterLimits {
+            fuel: self.fuel,
+            max_memory_in_mb: self.max_memory_in_mb.unwrap_or(default.max_memory_in_mb),
+            timeout_in_ms: self.timeout_in_ms.unwrap_or(default.timeout_in_ms),
+        })
+    }
+
+    /// Writes the resource limits of the search filter to the configuration file.
+    fn save_limits(&self) -> Result<(), Error> {
+        let Some(limits) = self.limits() else {
+            return Ok(());
+        };
+        let workspace = self.global.workspace();
+        let mut config = workspace.config().clone();
+        let file = workspace.config_file();
+        config.filter_limits.insert(self.name(), limits);
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&file, serde_json::to_string_pretty(&config)?)?;
+        info!(
+            "Resource limits of '{}' have been saved to '{}'",
+            self.name(),
+            file.display()
+        );
+        Ok(())
+    }
+
     /// Checks that the search filter complies with the tool requirements.
-    /// The search filter must expose functions defined in `REQUIRED_WASM_FUNCTIONS`.
-    fn check_wasm_module(&self, wasm_file: &PathBuf) -> Result<(), Error> {
+    /// The search filter must expose functions defined in `REQUIRED_WASM_FUNCTIONS`,
+    /// a transformer the ones defined in `REQUIRED_TRANSFORMER_WASM_FUNCTIONS`
+    /// and a deserializer the ones defined in `REQUIRED_DESERIALIZER_WASM_FUNCTIONS`.
+    /// The description returned by the optional `describe` function is validated as well.
+    fn check_wasm_module(
+        &self,
+        wasm_file: &PathBuf,
+    ) -> Result<(ModuleKind, Option<FilterDescription>), Error> {
         let url = Wasm::file(wasm_file);
         let manifest = Manifest::new([url]);
+        // Search filters may import the host functions exposed by yozefu
+        let functions = host_functions(&self.name(), &self.global.workspace().filters_dir());
         let mut filter =
-            Plugin::new(manifest, [], true).map_err(|e| Error::Error(e.to_string()))?;
-        check_presence_of_functions(&mut filter)?;
+            Plugin::new(manifest, functions, true).map_err(|e| Error::Error(e.to_string()))?;
+        let kind = ModuleKind::of(&filter);
+        check_presence_of_functions(&mut filter, kind)?;
+        let description = describe(&mut filter)?;
+        Ok((kind, description))
+    }
+
+    /// Stores the description of the search filter next to its wasm module.
+    /// The description of a previous import is removed when the search filter doesn't describe itself anymore.
+    fn save_description(&self, description: Option<&FilterDescription>) -> Result<(), Error> {
+        let file =
+            ImportedFilter::description_file(&self.global.workspace().filters_dir(), &self.name());
+        match description {
+            Some(description) => fs::write(&file, serde_json::to_string_pretty(description)?)?,
+            None if file.exists() => fs::remove_file(&file)?,
+            None => (),
+        }
         Ok(())
     }
 }
 
-fn check_presence_of_functions(plugin: &mut Plugin) -> Result<(), Error> {
-    for function_name in &REQUIRED_WASM_FUNCTIONS {
+/// Calls the optional `describe` function of the wasm module.
+fn describe(plugin: &mut Plugin) -> Result<Option<FilterDescription>, Error> {
+    if !plugin.function_exists(DESCRIBE_FUNCTION_NAME) {
+        info!("'{DESCRIBE_FUNCTION_NAME}' is not exported, the search filter won't be documented");
+        return Ok(None);
+    }
+    let description = plugin
+        .call::<&str, Json<FilterDescription>>(DESCRIBE_FUNCTION_NAME, "")
+        .map(|d| d.0)
+        .map_err(|e| Error::Error(format!("'{DESCRIBE_FUNCTION_NAME}' failed: {e}")))?;
+    description.validate().map_err(|e| {
+        Error::Error(format!(
+            "The description returned by '{DESCRIBE_FUNCTION_NAME}' is invalid: {e}"
+        ))
+    })?;
+    info!(
+        "'{}' {}: {}",
+        description.name,
+        description.version.as_deref().unwrap_or_default(),
+        description.description.as_deref().unwrap_or_default()
+    );
+    Ok(Some(description))
+}
+
+fn check_presence_of_functions(plugin: &mut Plugin, kind: ModuleKind) -> Result<(), Error> {
+    for function_name in required_functions(kind) {
         match plugin.function_exists(function_name) {
-            true => info!("'{function_name}' found in the search filter"),
+            true => info!("'{function_name}' found in the {kind}"),
             false => {
                 return Err(Error::Error(format!(
-                    "'{function_name}' is missing in the search filter. Make sure the wasm module exports a '{function_name}' filter"
+                    "'{function_name}' is missing in the {kind}. Make sure the wasm module exports a '{function_name}' function"
                 )));
             }
         }
@@ -101,6 +235,9 @@ async fn test_name() {
         filter_name: Some("my-filter".to_string()),
         global: GlobalArgs::default(),
         force: false,
+        fuel: None,
+        max_memory_in_mb: None,
+        timeout_in_ms: None,
     };
     assert_eq!(command.name(), "my-filter");
 }
@@ -112,11 +249,115 @@ async fn test_name_from_file_path() {
         file: temp_dir.path().join("random.wasm"),
         filter_name: None,
         force: false,
+        fuel: None,
+        max_memory_in_mb: None,
+        timeout_in_ms: None,
         global: GlobalArgs::default(),
     };
     assert_eq!(command.name(), "random");
 }
 
+#[tokio::test]
+async fn test_limits() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp directory");
+    let mut command = ImportFilterCommand {
+        file: temp_dir.path().join("random.wasm"),
+        filter_name: None,
+        force: false,
+        fuel: None,
+        max_memory_in_mb: None,
+        timeout_in_ms: None,
+        global: GlobalArgs::default(),
+    };
+    assert_eq!(command.limits(), None);
+    command.timeout_in_ms = Some(50);
+    assert_eq!(
+        command.limits(),
+        Some(FilterLimits {
+            timeout_in_ms: 50,
+            ..Default::default()
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_search_filter_using_host_functions() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp directory");
+    let file = temp_dir.path().join("logger.wasm");
+    fs::write(
+        &file,
+        r#"(module
+  (import "extism:host/user" "yozefu_log" (func $log (param i64 i64)))
+  (func (export "parse_parameters") (result i32) (i32.const 0))
+  (func (export "matches") (result i32) (i32.const 0)))"#,
+    )
+    .unwrap();
+    let command = ImportFilterCommand {
+        file: file.clone(),
+        filter_name: None,
+        force: false,
+        fuel: None,
+        max_memory_in_mb: None,
+        timeout_in_ms: None,
+        global: GlobalArgs::default(),
+    };
+    assert_eq!(
+        command.check_wasm_module(&file).unwrap(),
+        (ModuleKind::SearchFilter, None)
+    );
+}
+
+/// Returns a wasm module whose `describe` function outputs `description`.
+#[cfg(test)]
+fn describing(description: &str) -> Plugin {
+    let module = app::search::wasm_fixtures::returning("describe", description);
+    Plugin::new(Manifest::new([Wasm::data(module)]), [], true).unwrap()
+}
+
+#[test]
+fn test_describe() {
+    let description = describe(&mut describing(
+        r#"{"name":"key-ends-with","version":"1.0.0","examples":["key-ends-with('1234')"]}"#,
+    ))
+    .unwrap()
+    .unwrap();
+    assert_eq!(description.name, "key-ends-with");
+    assert_eq!(description.examples, vec!["key-ends-with('1234')"]);
+
+    assert!(describe(&mut describing(r#"{"name":"","parameters":"string"}"#)).is_err());
+    assert!(describe(&mut describing("not json")).is_err());
+
+    let module = r#"(module (func (export "matches") (result i32) (i32.const 0)))"#;
+    let mut plugin = Plugin::new(Manifest::new([Wasm::data(module)]), [], true).unwrap();
+    assert_eq!(describe(&mut plugin).unwrap(), None);
+}
+
+#[test]
+fn test_module_kind() {
+    let kind_of = |functions: &[&str]| {
+        let exports = functions
+            .iter()
+            .map(|f| format!(r#"(func (export "{f}") (result i32) (i32.const 0))"#))
+            .collect::<String>();
+        let module = format!("(module {exports})");
+        let plugin = Plugin::new(Manifest::new([Wasm::data(module)]), [], true).unwrap();
+        ModuleKind::of(&plugin)
+    };
+    assert_eq!(
+        kind_of(&[PARSE_PARAMETERS_FUNCTION_NAME, MATCHES_FUNCTION_NAME]),
+        ModuleKind::SearchFilter
+    );
+    assert_eq!(
+        kind_of(&[PARSE_PARAMETERS_FUNCTION_NAME, TRANSFORM_FUNCTION_NAME]),
+        ModuleKind::Transformer
+    );
+    assert_eq!(
+        kind_of(&[DESERIALIZE_FUNCTION_NAME]),
+        ModuleKind::Deserializer
+    );
+    assert_eq!(kind_of(&[]), ModuleKind::SearchFilter);
+}
+
 //fn check_parse_parameters(plugin: &mut Plugin) -> Result<(), Error> {
 //    match plugin
 //        .call::<String, i32>(PARSE_PARAMETERS_FUNCTION_NAME, "[]".to_string())
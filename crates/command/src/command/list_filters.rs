@@ -0,0 +1,99 @@
+//! Command listing the imported search filters and what they do.
+//!
+//! ```bash
+//! yozf filter list
+//! ```
+use std::fmt::Write;
+
+use app::search::imported_filter::{ImportedFilter, imported_filters};
+use clap::Args;
+use lib::Error;
+use thousands::Separable;
+use tracing::info;
+
+use crate::{GlobalArgs, command::Command};
+
+/// List the imported search filters.
+#[derive(Debug, Clone, Args)]
+pub(crate) struct ListFiltersCommand {
+    #[command(flatten)]
+    global: GlobalArgs,
+}
+
+impl Command for ListFiltersCommand {
+    async fn execute(&self) -> Result<(), Error> {
+        let directory = self.global.workspace().filters_dir();
+        let filters = imported_filters(&directory);
+        if filters.is_empty() {
+            info!(
+                "No search filter has been imported in '{}' yet",
+                directory.display()
+            );
+        }
+        for filter in filters {
+            println!("{}", describe(&filter, &filter.checksum()?)?);
+        }
+        Ok(())
+    }
+}
+
+fn describe(filter: &ImportedFilter, checksum: &str) -> Result<String, Error> {
+    let mut text = filter.name.clone();
+    let description = filter.description.as_ref();
+    if let Some(version) = description.and_then(|d| d.version.as_ref()) {
+        write!(text, " {version}")?;
+    }
+    if let Some(author) = description.and_then(|d| d.author.as_ref()) {
+        write!(text, " by {author}")?;
+    }
+    write!(
+        text,
+        "\n    {} bytes, modified on {}, sha256 {checksum}",
+        filter.size.separate_with_underscores(),
+        filter
+            .modified_at
+            .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or("an unknown date".to_string()),
+    )?;
+    if let Some(description) = description {
+        if let Some(d) = &description.description {
+            write!(text, "\n    {d}")?;
+        }
+        if !description.parameters.is_null() {
+            write!(text, "\n    Parameters: {}", description.parameters)?;
+        }
+    }
+    for example in filter.examples() {
+        write!(text, "\n    Example: {example}")?;
+    }
+    Ok(text)
+}
+
+#[test]
+fn test_describe() {
+    let mut filter = ImportedFilter {
+        name: "key-ends-with".to_string(),
+        path: "key-ends-with.wasm".into(),
+        size: 0,
+        modified_at: None,
+        description: None,
+    };
+    assert_eq!(
+        describe(&filter, "e3b0c442").unwrap(),
+        "key-ends-with\n    0 bytes, modified on an unknown date, sha256 e3b0c442\n    Example: key-ends-with(...)"
+    );
+
+    filter.description = serde_json::from_str(
+        r#"{"name": "key-ends-with", "version": "1.0.0", "description": "Keys ending with the suffix", "parameters": {"type": "array"}}"#,
+    )
+    .unwrap();
+    filter.size = 1024;
+    assert_eq!(
+        describe(&filter, "e3b0c442").unwrap(),
+        r#"key-ends-with 1.0.0
+    1_024 bytes, modified on an unknown date, sha256 e3b0c442
+    Keys ending with the suffix
+    Parameters: {"type":"array"}
+    Example: key-ends-with(...)"#
+    );
+}
@@ -10,7 +10,7 @@ use std::str::FromStr;
 use std::{fs, io};
 
 use app::configuration::{
-    ClusterConfig, Configuration, GlobalConfig, InternalConfig, Workspace, YozefuConfig,
+    ClusterConfig, Configuration, GlobalConfig, InternalConfig, SavedQuery, Workspace, YozefuConfig,
 };
 use app::search::ValidSearchQuery;
 
@@ -19,7 +19,7 @@ use clap::error::ErrorKind;
 use clap::{CommandFactory, Parser};
 use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
 use itertools::Itertools;
-use lib::Error;
+use lib::{Error, SearchQuery};
 use rdkafka::consumer::BaseConsumer;
 use strum::{Display, EnumString};
 use tracing::{debug, info, warn};
@@ -27,6 +27,7 @@ use tui::Theme;
 use tui::error::TuiError;
 use tui::{State, Ui};
 
+use super::read_query_file;
 use crate::headless::Headless;
 use crate::headless::formatter::{
     JsonFormatter, KafkaFormatter, PlainFormatter, SimpleFormatter, TransposeFormatter,
@@ -72,9 +73,12 @@ where
     #[clap(long)]
     /// Disable the TUI, print results in stdout instead.
     pub headless: bool,
-    /// The initial search query. If you start the query with the letter @, the rest should be a filename to read the data from, or - if you want yozefu to read the data from stdin.
+    /// The initial search query. If you start the query with the letter @, the rest should be a filename to read the data from, or - if you want yozefu to read the data from stdin. To start the query with a saved query, wrap it in parentheses: '(@name) and ...'.
     query: Vec<String>,
     #[clap(long)]
+    /// Name of a saved query to run. It is combined with the search query, if any
+    pub saved: Option<String>,
+    #[clap(long)]
     /// Theme to use
     pub theme: Option<String>,
     #[clap(long, requires = "headless")]
@@ -89,6 +93,9 @@ where
     #[clap(short, long)]
     /// Name of the file to export kafka records
     pub output: Option<PathBuf>,
+    #[clap(long)]
+    /// Print the order the predicates of the search query are evaluated in, without consuming any record
+    pub explain: bool,
     #[command(flatten)]
     pub global: GlobalArgs,
     #[clap(skip)]
@@ -124,6 +131,12 @@ where
             }
         }
 
+        if self.explain {
+            return self
+                .explain(&yozefu_config)
+                .map_err(std::convert::Into::into);
+        }
+
         match self.headless {
             true => {
                 let _ = init_logging_stderr(self.debug);
@@ -151,10 +164,22 @@ where
     /// Returns the search query to use.
     fn query(&self, initial_query: &str) -> Result<String, Error> {
         let q = self.query.join(" ").trim().to_string();
-        if q.is_empty() {
-            return Ok(initial_query.to_string());
+        match &self.saved {
+            Some(saved) => {
+                let q = match q.is_empty() {
+                    true => q,
+                    false => Self::read_query(q)?,
+                };
+                let (_, query) = SearchQuery::parse(q.trim()).map_err(Error::Search)?;
+                Ok(query.and_saved_query(saved).to_string())
+            }
+            None if q.is_empty() => Ok(initial_query.to_string()),
+            None => Self::read_query(q),
         }
+    }
 
+    /// Reads the search query from stdin or from a file when needed.
+    fn read_query(q: String) -> Result<String, Error> {
         if q == "-" {
             info!("Reading query from stdin");
             let mut buffer = String::new();
@@ -162,21 +187,35 @@ where
             return Ok(buffer);
         }
 
-        match q.starts_with('@') {
-            true => {
-                let query_file = Path::new(&q[1..]);
-                fs::read_to_string(query_file).map_err(|e| {
-                    Error::Error(format!(
-                        "Cannot read search query from file {:?}: {}",
-                        query_file.display(),
-                        e
-                    ))
-                })
-            }
-            false => Ok(q),
+        read_query_file(q)
+    }
+
+    /// Returns the saved query passed with `--saved`.
+    fn saved_query(&self, config: &InternalConfig) -> Result<Option<SavedQuery>, Error> {
+        let Some(name) = &self.saved else {
+            return Ok(None);
+        };
+        let saved_queries = config.saved_queries();
+        match saved_queries.get(name) {
+            Some(saved_query) => Ok(Some(saved_query.clone())),
+            None => Err(Error::Error(format!(
+                "Unknown saved query '{name}'. Possible saved queries: [{}]",
+                saved_queries.keys().join(", ")
+            ))),
         }
     }
 
+    /// Topics to consume: those passed with `--topics`, otherwise those of the saved query.
+    fn selected_topics(&self, config: &InternalConfig) -> Result<Vec<String>, Error> {
+        if !self.topics.is_empty() {
+            return Ok(self.topics.clone());
+        }
+        Ok(self
+            .saved_query(config)?
+            .map(|saved_query| saved_query.topics)
+            .unwrap_or_default())
+    }
+
     fn workspace(&self, yozefu_config: &YozefuConfig) -> Result<Workspace, Error> {
         let workspace = self.global.workspace();
 
@@ -286,6 +325,7 @@ where
         let cluster = self.cluster();
         let internal_config = self.internal_config(yozefu_config)?;
         let query = self.query(internal_config.initial_query())?;
+        let topics = self.selected_topics(&internal_config)?;
 
         let _ = init_logging_file(self.debug, &internal_config.workspace().log_file());
         let theme_name = self
@@ -297,12 +337,12 @@ where
         let mut ui = Ui::new(
             self.app(&query, internal_config)?,
             &query,
-            self.topics.clone(),
+            topics.clone(),
             state.clone(),
         );
 
         Self::check_connection(yozefu_config)?;
-        ui.run(self.topics.clone(), state).await
+        ui.run(topics, state).await
     }
 
     fn check_connection(yozefu_config: &YozefuConfig) -> Result<(), Error> {
@@ -313,12 +353,31 @@ where
     /// Creates the App
     fn app(&self, query: &str, config: InternalConfig) -> Result<App, Error> {
         debug!("{config:?}");
-        let search_query = ValidSearchQuery::from(query, &config.workspace().filters_dir())?;
+        let search_query = ValidSearchQuery::with_filter_limits(
+            query,
+            &config.workspace().filters_dir(),
+            &config.saved_queries(),
+            &config.filter_limits(),
+        )?;
 
         //let output_file = internal_config.output_file();
         Ok(App::new(self.cluster().to_string(), config, search_query))
     }
 
+    /// Prints how the search query is evaluated
+    fn explain(&self, yozefu_config: &YozefuConfig) -> Result<(), Error> {
+        let config = self.internal_config(yozefu_config)?;
+        let query = self.query(config.initial_query())?;
+        let search_query = ValidSearchQuery::with_filter_limits(
+            &query,
+            &config.workspace().filters_dir(),
+            &config.saved_queries(),
+            &config.filter_limits(),
+        )?;
+        print!("{}", search_query.explain());
+        Ok(())
+    }
+
     /// Starts the app in headless mode
     async fn headless(&self, yozefu_config: &YozefuConfig) -> Result<(), Error> {
         let internal_config = self.internal_config(yozefu_config)?;
@@ -334,7 +393,7 @@ where
             .map_err(|e| Error::Error(e.to_string()))?,
         );
         progress.set_message("INFO");
-        let topics = self.topics(yozefu_config)?;
+        let topics = self.topics(yozefu_config, self.selected_topics(&internal_config)?)?;
         if !self.disable_progress {
             progress.set_draw_target(ProgressDrawTarget::stderr());
         }
@@ -369,9 +428,13 @@ where
     }
 
     /// Lists available topics when the user didn't provide any
-    fn topics(&self, yozefu_config: &YozefuConfig) -> Result<Vec<String>, Error> {
-        if !self.topics.is_empty() {
-            return Ok(self.topics.clone());
+    fn topics(
+        &self,
+        yozefu_config: &YozefuConfig,
+        topics: Vec<String>,
+    ) -> Result<Vec<String>, Error> {
+        if !topics.is_empty() {
+            return Ok(topics);
         }
         let items = App::list_topics_from_client(yozefu_config)?;
         println!(
@@ -1,18 +1,24 @@
 //! The [`clap`](https://crates.io/crates/clap) commands of the tool
-use std::env::var;
+use std::{env::var, fs};
 
-use lib::Error;
+use lib::{Error, SearchQuery};
 
 mod config_command;
 pub mod configure;
 mod create_filter;
+mod filter_command;
 mod import_filter;
+mod list_filters;
 mod main_command;
+mod query_command;
 mod utility_commands;
 
 pub(crate) use create_filter::CreateFilterCommand;
+pub(crate) use filter_command::FilterCommand;
 pub(crate) use import_filter::ImportFilterCommand;
+pub(crate) use list_filters::ListFiltersCommand;
 pub use main_command::MainCommand;
+pub(crate) use query_command::QueryCommand;
 pub use utility_commands::UtilityCommands;
 
 #[cfg(target_family = "windows")]
@@ -33,3 +39,23 @@ fn default_editor(editor: Option<&str>) -> String {
         .unwrap_or(DEFAULT_EDITOR)
         .to_string()
 }
+
+/// Reads the search query from a file when it starts with `@`, the rest being the path of the file.
+/// A query starting with `@` is always read from a file: to start a query with a saved query, wrap it in parentheses.
+fn read_query_file(query: String) -> Result<String, Error> {
+    let Some(file) = query.strip_prefix('@') else {
+        return Ok(query);
+    };
+    fs::read_to_string(file).map_err(|e| {
+        let hint = match SearchQuery::parse(&query) {
+            Ok(_) => format!(
+                ". To reference a saved query, use '--saved' or wrap it in parentheses: '({})'",
+                query.split_whitespace().next().unwrap_or_default()
+            ),
+            Err(_) => String::new(),
+        };
+        Error::Error(format!(
+            "Cannot read search query from file '{file}': {e}{hint}"
+        ))
+    })
+}
@@ -0,0 +1,104 @@
+//! Commands for tooling around search queries.
+//!
+//! ```bash
+//! yozf query parse 'from begin key == "my-key" limit 10' | jq '.ast'
+//! yozf query parse --json @query.json
+//! ```
+
+use std::io::Read;
+
+use clap::{Args, Subcommand};
+use lib::{Error, search::SearchQuery};
+use serde::Serialize;
+
+use super::{Command, read_query_file};
+
+/// Command to work with search queries without consuming any kafka record.
+#[derive(Debug, Args, Clone)]
+pub struct QueryCommand {
+    #[command(subcommand)]
+    pub subcommand: QuerySubCommand,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+pub enum QuerySubCommand {
+    /// Parse a search query and print its abstract syntax tree and its normalized form
+    Parse(QueryParseCommand),
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct QueryParseCommand {
+    /// The search query. If it starts with @, the rest is a file to read the query from. Use - to read it from stdin.
+    query: Vec<String>,
+    /// The input is an abstract syntax tree in JSON instead of a search query
+    #[clap(long)]
+    json: bool,
+}
+
+/// Output of `yozf query parse`
+#[derive(Debug, Serialize)]
+struct ParsedQuery {
+    /// The normalized search query
+    query: String,
+    /// The abstract syntax tree of the search query
+    ast: SearchQuery,
+}
+
+impl QueryParseCommand {
+    fn input(&self) -> Result<String, Error> {
+        let input = self.query.join(" ").trim().to_string();
+        if input == "-" {
+            let mut buffer = String::new();
+            std::io::stdin().read_to_string(&mut buffer)?;
+            return Ok(buffer);
+        }
+        read_query_file(input)
+    }
+
+    fn parse(&self) -> Result<ParsedQuery, Error> {
+        let input = self.input()?;
+        let ast = match self.json {
+            true => serde_json::from_str::<SearchQuery>(&input)?,
+            false => SearchQuery::parse(input.trim()).map_err(Error::Search)?.1,
+        };
+        Ok(ParsedQuery {
+            query: ast.to_string(),
+            ast,
+        })
+    }
+}
+
+impl Command for QueryCommand {
+    async fn execute(&self) -> Result<(), Error> {
+        match &self.subcommand {
+            QuerySubCommand::Parse(command) => command.execute().await,
+        }
+    }
+}
+
+impl Command for QueryParseCommand {
+    async fn execute(&self) -> Result<(), Error> {
+        let parsed = self.parse()?;
+        println!("{}", serde_json::to_string_pretty(&parsed)?);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_parse_query() {
+    let command = QueryParseCommand {
+        query: vec!["from begin".to_string(), "key == 'a' limit 10".to_string()],
+        json: false,
+    };
+    let parsed = command.parse().unwrap();
+    assert_eq!(
+        parsed.query,
+        r#"from beginning key == "a" order by timestamp asc limit 10"#
+    );
+
+    let command = QueryParseCommand {
+        query: vec![serde_json::to_string(&parsed.ast).unwrap()],
+        json: true,
+    };
+    assert_eq!(command.parse().unwrap().ast, parsed.ast);
+}
@@ -6,8 +6,8 @@ use lib::Error;
 use crate::log::init_logging_stderr;
 
 use super::{
-    Command, CreateFilterCommand, ImportFilterCommand, config_command::ConfigCommand,
-    configure::ConfigureCommand,
+    Command, CreateFilterCommand, FilterCommand, ImportFilterCommand, ListFiltersCommand,
+    QueryCommand, config_command::ConfigCommand, configure::ConfigureCommand,
 };
 
 #[derive(Subcommand, Debug)]
@@ -18,10 +18,17 @@ pub enum UtilityCommands {
     /// Helper to create a new WebAssembly search filter
     #[clap(alias = "new-filter")]
     CreateFilter(CreateFilterCommand),
+    /// Alias of `filter list`
+    #[clap(hide = true)]
+    ListFilters(ListFiltersCommand),
+    /// Manage the search filters: list, remove or test them
+    Filter(FilterCommand),
     /// Edit the configuration file
     Configure(ConfigureCommand),
     /// Print the config to `stdout`
     Config(ConfigCommand),
+    /// Tools for search queries
+    Query(QueryCommand),
 }
 
 impl Command for UtilityCommands {
@@ -30,8 +37,11 @@ impl Command for UtilityCommands {
         match self {
             Self::ImportFilter(command) => command.execute().await,
             Self::CreateFilter(command) => command.execute().await,
+            Self::ListFilters(command) => command.execute().await,
+            Self::Filter(command) => command.execute().await,
             Self::Configure(command) => command.execute().await,
             Self::Config(command) => command.execute().await,
+            Self::Query(command) => command.execute().await,
         }
     }
 }
@@ -18,7 +18,7 @@
 //!    }
 //! }
 //! ```
-use lib::KafkaRecord;
+use lib::{KafkaRecord, search::select::Projection};
 
 use super::KafkaFormatter;
 
@@ -41,4 +41,10 @@ impl KafkaFormatter for JsonFormatter {
     fn fmt(&self, record: &KafkaRecord) -> String {
         serde_json::to_string_pretty(&record).unwrap_or_default()
     }
+
+    fn fmt_projection(&self, record: &KafkaRecord, projection: &Projection) -> String {
+        let object: serde_json::Map<String, serde_json::Value> =
+            projection.project(record).into_iter().collect();
+        serde_json::to_string_pretty(&object).unwrap_or_default()
+    }
 }
@@ -10,11 +10,43 @@ pub use plain_formatter::PlainFormatter;
 pub use simple_formatter::SimpleFormatter;
 pub use transpose_formatter::TransposeFormatter;
 
-use lib::KafkaRecord;
+use lib::{KafkaRecord, search::select::Projection};
 
 /// A kafka formatter displays a kafka record to stdout.
 pub trait KafkaFormatter: Sync + Send {
     fn fmt(&self, record: &KafkaRecord) -> String;
+
+    /// Formats only the fields of the record listed in the `select` clause.
+    fn fmt_projection(&self, record: &KafkaRecord, _projection: &Projection) -> String {
+        self.fmt(record)
+    }
+}
+
+/// Returns the name and the human-readable value of each selected field of the record.
+pub(crate) fn projected_columns(
+    record: &KafkaRecord,
+    projection: &Projection,
+) -> Vec<(String, String)> {
+    projection
+        .project(record)
+        .into_iter()
+        .map(|(name, value)| {
+            let value = match value {
+                serde_json::Value::String(s) => s,
+                serde_json::Value::Null => String::new(),
+                serde_json::Value::Number(n) if name == "timestamp" => n
+                    .as_i64()
+                    .and_then(chrono::DateTime::from_timestamp_millis)
+                    .map(|t| {
+                        t.with_timezone(&chrono::Local)
+                            .to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+                    })
+                    .unwrap_or_default(),
+                value => value.to_string(),
+            };
+            (name, value)
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -3,8 +3,8 @@
 //! 2023-01-01T01:00:00.000+01:00    hello-world[0][2]                    - Step 3: Consume data: rpk topic consume my-topic
 //! ```
 
-use super::KafkaFormatter;
-use lib::KafkaRecord;
+use super::{KafkaFormatter, projected_columns};
+use lib::{KafkaRecord, search::select::Projection};
 #[derive(Clone)]
 pub struct PlainFormatter {}
 
@@ -46,4 +46,12 @@ impl KafkaFormatter for PlainFormatter {
             record.value_as_string
         )
     }
+
+    fn fmt_projection(&self, record: &KafkaRecord, projection: &Projection) -> String {
+        projected_columns(record, projection)
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect::<Vec<_>>()
+            .join("    ")
+    }
 }
@@ -25,6 +25,7 @@ fn test_plain_formatter() {
         size: 12,
         key_as_string: "key".to_string(),
         value_as_string: "value".to_string(),
+        tombstone: false,
     };
     let formatter = PlainFormatter::new();
     assert_eq!(
@@ -43,3 +44,27 @@ fn test_plain_formatter() {
         "1970-01-01T01:00:00.000+01:00    an.sixteen.chars[0][1]    key - value"
     );
 }
+
+#[test]
+fn test_plain_formatter_with_projection() {
+    use lib::SearchQuery;
+
+    let record = KafkaRecord {
+        topic: "test-topic".to_string(),
+        partition: 3,
+        key: DataType::String("key".to_string()),
+        value: DataType::Json(serde_json::json!({"customer": {"id": 42}})),
+        headers: BTreeMap::from([("traceparent".to_string(), "00-abc".to_string())]),
+        ..Default::default()
+    };
+    let projection =
+        SearchQuery::parse("select key, value.customer.id, headers.traceparent, partition")
+            .unwrap()
+            .1
+            .select
+            .unwrap();
+    assert_eq!(
+        PlainFormatter::new().fmt_projection(&record, &projection),
+        "key    42    00-abc    3"
+    );
+}
@@ -8,9 +8,9 @@
 ///    Value: my-value
 ///  Headers:
 /// ```
-use super::KafkaFormatter;
+use super::{KafkaFormatter, projected_columns};
 use itertools::Itertools;
-use lib::KafkaRecord;
+use lib::{KafkaRecord, search::select::Projection};
 
 #[derive(Clone)]
 pub struct TransposeFormatter {}
@@ -55,4 +55,17 @@ Timestamp: {}
                 .join(", ")
         )
     }
+
+    fn fmt_projection(&self, record: &KafkaRecord, projection: &Projection) -> String {
+        let columns = projected_columns(record, projection);
+        let width = columns
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(0);
+        columns
+            .into_iter()
+            .map(|(name, value)| format!("{name:>width$}: {value}\n"))
+            .collect()
+    }
 }
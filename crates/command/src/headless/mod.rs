@@ -1,12 +1,11 @@
 //! Module gathering code for the headless mode.
 
 use app::App;
-use app::search::Search;
-use app::search::SearchContext;
+use app::search::engine::SearchEngine;
+use app::search::filter::filters_metrics;
 use chrono::DateTime;
 use futures_batch::TryChunksTimeoutStreamExt;
 use rdkafka::Message;
-use rdkafka::message::OwnedMessage;
 use std::time::Duration;
 use std::time::Instant;
 use thousands::Separable;
@@ -16,8 +15,11 @@ use tracing::info;
 
 use futures::{StreamExt, TryStreamExt};
 use indicatif::ProgressBar;
+use itertools::Itertools;
 use lib::Error;
 use lib::KafkaRecord;
+use lib::search::aggregation::{AggregationRow, Aggregator};
+use lib::search::latest::LatestByKey;
 use rdkafka::consumer::Consumer;
 use tokio_util::sync::CancellationToken;
 
@@ -54,8 +56,11 @@ impl Headless {
             return Err(Error::Error("Please specify topics to consume".into()));
         }
         info!("Creating consumer for topics [{}]", self.topics.join(", "));
-        let consumer = self.app.create_consumer(&self.topics)?;
-        let mut records_channel = mpsc::unbounded_channel::<KafkaRecord>();
+        let consumer = self.app.create_consumer_2(&self.topics)?;
+        let mut stop_offsets = consumer.stop_offsets();
+        let consumer = consumer.stream_consumer();
+        // Records that don't match are only sent with `latest by key`, they may replace the latest record of their key.
+        let mut records_channel = mpsc::unbounded_channel::<(KafkaRecord, bool)>();
         let search_query = self.app.search_query.clone();
         let token = CancellationToken::new();
         let progress = self.progress.clone();
@@ -65,38 +70,77 @@ impl Headless {
             .estimate_number_of_records_to_read(&consumer.assignment()?)?;
         progress.set_length(count as u64);
 
-        let (tx_dd, mut rx_dd) = mpsc::unbounded_channel::<OwnedMessage>();
-        let mut schema_registry = self.app.schema_registry().clone();
         let token_cloned = token.clone();
 
+        let mut aggregator = Aggregator::new(&self.app.search_query.query().aggregations);
+        let mut latest = self
+            .app
+            .search_query
+            .query()
+            .latest_by_key
+            .then(LatestByKey::new);
+        if !aggregator.is_empty() || latest.is_some() {
+            // Aggregations and latest records are printed once the consumer stops, so CTRL + C must not kill the process.
+            let token = token.clone();
+            tokio::spawn(async move {
+                if tokio::signal::ctrl_c().await.is_ok() {
+                    token.cancel();
+                }
+            });
+        }
+
         let filters_directory = self.app.config.workspace().filters_dir();
+        let (engine, mut evaluations) = SearchEngine::start(
+            search_query.clone(),
+            filters_directory,
+            self.app.schema_registry(),
+            self.app.transforms()?,
+            self.app.deserializers()?,
+            self.app.consumer_config().workers(),
+        )?;
+        let throughput = engine.throughput();
+        let workers = engine.workers();
         tokio::task::Builder::new()
             .name("headless-search-engine")
             .spawn(async move {
+                let mut matched = 0;
                 loop {
-                    let mut limit = 0;
                     select! {
                         () = token_cloned.cancelled() => {
-                            return;
+                            break;
                          },
-                        Some(message) = rx_dd.recv() => {
-                            let record = KafkaRecord::parse_with_schema_registry(message, &mut schema_registry).await;
-                            let context = SearchContext::new(&record, &filters_directory);
-                            if search_query.matches(&context) {
-                                records_channel.0.send(record).unwrap();
-                                limit += 1;
+                        evaluation = evaluations.recv() => {
+                            let Some(evaluation) = evaluation else {
+                                break;
+                            };
+                            if let Some(record) = evaluation.record {
+                                records_channel.0.send((record, evaluation.matches)).unwrap();
+                            }
+                            if evaluation.matches {
+                                matched += 1;
                             }
                             if let Some(query_limit) = search_query.limit() {
-                                if limit >= query_limit {
+                                if matched >= query_limit {
                                     token_cloned.cancel();
                                 }
                             }
                         }
                     }
                 }
+                info!(
+                    "Search engine: {} records evaluated by {} workers in {}ms ({} rec/s)",
+                    throughput.read().separate_with_underscores(),
+                    workers,
+                    throughput.elapsed().as_millis().separate_with_underscores(),
+                    throughput.records_per_second().separate_with_underscores()
+                );
+                for metrics in filters_metrics() {
+                    info!("Metrics of {metrics}");
+                }
             })
             .unwrap();
 
+        let sample = self.app.search_query.query().sample.clone();
         let consumer_config = self.app.consumer_config();
         tokio::task::Builder::new()
             .name("headless-kafka-consumer")
@@ -114,10 +158,22 @@ impl Headless {
                             .and_then(|r| r.timestamp().to_millis())
                             .unwrap_or_default();
                         for message in messages {
+                            if stop_offsets.stop(&consumer, &message) {
+                                if stop_offsets.all_stopped() {
+                                    token.cancel();
+                                }
+                                continue;
+                            }
+                            // Sampling happens before the record is deserialized and matched against the search query.
+                            if sample.as_ref().is_some_and(|s| !s.keep(message.key(), message.partition(), message.offset())) {
+                                continue;
+                            }
                             consumed += 1;
                             total_consumed += 1;
                             let message = message.detach();
-                            tx_dd.send(message).unwrap();
+                            if engine.send(message).is_err() {
+                                token.cancel();
+                            }
                         }
 
                         let elapsed = current_time.elapsed();
@@ -140,12 +196,60 @@ impl Headless {
             })
             .unwrap();
 
-        while let Some(record) = records_channel.1.recv().await {
-            println!("{}", self.formatter.fmt(&record));
-            if self.export_records {
-                self.app.export_record(&record)?;
+        while let Some((record, matches)) = records_channel.1.recv().await {
+            match latest.as_mut() {
+                Some(latest) => latest.add(record, matches),
+                None => self.output(&record, &mut aggregator)?,
+            }
+        }
+        if let Some(latest) = latest {
+            for record in latest.into_records() {
+                self.output(&record, &mut aggregator)?;
             }
         }
+        if !aggregator.is_empty() {
+            println!("{}", Self::aggregations_table(&aggregator.rows()));
+        }
         Ok(())
     }
+
+    /// Prints the record, or aggregates it when the search query has aggregations.
+    fn output(&self, record: &KafkaRecord, aggregator: &mut Aggregator) -> Result<(), Error> {
+        if !aggregator.is_empty() {
+            aggregator.add(record);
+            return Ok(());
+        }
+        match &self.app.search_query.query().select {
+            Some(projection) => {
+                println!("{}", self.formatter.fmt_projection(record, projection))
+            }
+            None => println!("{}", self.formatter.fmt(record)),
+        }
+        if self.export_records {
+            self.app.export_record(record)?;
+        }
+        Ok(())
+    }
+
+    /// Formats the results of the aggregations as a table.
+    fn aggregations_table(rows: &[AggregationRow]) -> String {
+        let width = rows.iter().map(|r| r.aggregation.len()).max().unwrap_or(0);
+        let group_width = rows
+            .iter()
+            .map(|r| r.group.as_ref().map_or(0, String::len))
+            .max()
+            .unwrap_or(0);
+        rows.iter()
+            .map(|r| {
+                format!(
+                    "{:<width$}  {:<group_width$}  {}",
+                    r.aggregation,
+                    r.group.as_deref().unwrap_or_default(),
+                    r.value
+                )
+                .trim_end()
+                .to_string()
+            })
+            .join("\n")
+    }
 }
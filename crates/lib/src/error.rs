@@ -2,6 +2,8 @@
 use rdkafka::error::KafkaError;
 use std::fmt::Display;
 use std::str::Utf8Error;
+
+use crate::search::parse_error::ParseError;
 use std::{
     fmt::{self, Formatter},
     num::TryFromIntError,
@@ -22,7 +24,9 @@ pub enum Error {
 
 #[derive(Debug)]
 pub enum SearchError {
-    Parse(String),
+    Parse(ParseError),
+    /// A saved query is unknown or references itself
+    SavedQuery(String),
 }
 
 impl std::error::Error for Error {}
@@ -45,7 +49,8 @@ impl Display for Error {
 impl Display for SearchError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
-            SearchError::Parse(e) => write!(f, "Cannot parse the search query at '{e}'"),
+            SearchError::Parse(e) => write!(f, "{e}"),
+            SearchError::SavedQuery(e) => write!(f, "{e}"),
         }
     }
 }
@@ -64,22 +64,19 @@ impl DataType {
         right: &str,
     ) -> bool {
         let v = match json_pointer {
-            Some(path) => {
-                let path = path.replace(['.', '['], "/").replace(']', "");
-                match value.pointer(&path) {
-                    Some(d) => match d {
-                        serde_json::Value::Null => "null".to_string(),
-                        serde_json::Value::Bool(v) => v.to_string(),
-                        serde_json::Value::Number(v) => v.to_string(),
-                        serde_json::Value::String(v) => v.to_string(),
-                        serde_json::Value::Array(_) => return false,
-                        serde_json::Value::Object(_) => return false,
-                    },
-                    None => {
-                        return false;
-                    }
+            Some(path) => match value.pointer(&Self::json_pointer(path)) {
+                Some(d) => match d {
+                    serde_json::Value::Null => "null".to_string(),
+                    serde_json::Value::Bool(v) => v.to_string(),
+                    serde_json::Value::Number(v) => v.to_string(),
+                    serde_json::Value::String(v) => v.to_string(),
+                    serde_json::Value::Array(_) => return false,
+                    serde_json::Value::Object(_) => return false,
+                },
+                None => {
+                    return false;
                 }
-            }
+            },
             None => serde_json::to_string(value).unwrap(),
         };
         match operator {
@@ -90,6 +87,11 @@ impl DataType {
         }
     }
 
+    /// Converts a path of the query language, for instance `.album.tracks[0]`, to a JSON pointer.
+    pub fn json_pointer(path: &str) -> String {
+        path.replace(['.', '['], "/").replace(']', "")
+    }
+
     fn compare_string(value: &str, operator: &StringOperator, right: &str) -> bool {
         match operator {
             StringOperator::Contain => value.contains(right),
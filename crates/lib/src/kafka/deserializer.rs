@@ -0,0 +1,69 @@
+//! Custom deserializers for payloads no schema registry knows about, such as in-house binary encodings.
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_with::{base64::Base64, serde_as};
+
+use super::DataType;
+
+/// Input of a deserializer plugin
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+pub struct DeserializerInput {
+    /// The topic the record was read from.
+    pub topic: String,
+    /// `key` or `value`.
+    pub field: String,
+    /// The raw bytes of the key or the value, encoded in base64.
+    #[serde_as(as = "Base64")]
+    #[cfg_attr(test, schemars(with = "String", extend("contentEncoding" = "base64")))]
+    pub payload: Vec<u8>,
+    /// The headers of the record.
+    pub headers: BTreeMap<String, String>,
+}
+
+/// Deserializes the raw key or value of a kafka record.
+#[cfg(feature = "native")]
+pub trait PayloadDeserializer: Send + Sync {
+    fn deserialize(&self, input: &DeserializerInput) -> Result<DataType, crate::Error>;
+}
+
+#[test]
+fn generate_json_schema_for_deserializer_input() {
+    use schemars::schema_for;
+    let mut schema = schema_for!(DeserializerInput);
+    schema.insert("$id".into(), "https://raw.githubusercontent.com/MAIF/yozefu/refs/heads/main/docs/json-schemas/deserializer-input.json".into());
+    std::fs::write(
+        std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .parent()
+            .unwrap()
+            .join("docs")
+            .join("json-schemas")
+            .join("deserializer-input.json"),
+        serde_json::to_string_pretty(&schema).unwrap(),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_payload_is_encoded_in_base64() {
+    let input = DeserializerInput {
+        topic: "legacy-orders".to_string(),
+        field: "value".to_string(),
+        payload: b"hello".to_vec(),
+        headers: BTreeMap::new(),
+    };
+    let json = serde_json::to_string(&input).unwrap();
+    assert_eq!(
+        json,
+        r#"{"topic":"legacy-orders","field":"value","payload":"aGVsbG8=","headers":{}}"#
+    );
+    assert_eq!(
+        serde_json::from_str::<DeserializerInput>(&json).unwrap(),
+        input
+    );
+}
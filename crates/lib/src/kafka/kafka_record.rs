@@ -3,6 +3,8 @@ use super::SchemaRegistryClient;
 #[cfg(feature = "native")]
 use super::avro::avro_to_json;
 use super::data_type::DataType;
+#[cfg(feature = "native")]
+use super::deserializer::{DeserializerInput, PayloadDeserializer};
 use super::schema::Schema as SchemaRef;
 #[cfg(feature = "native")]
 use super::schema::SchemaId;
@@ -24,6 +26,52 @@ use serde::Serialize;
 use serde_json::Error;
 use std::collections::BTreeMap;
 
+/// The parts of a kafka record that must be deserialized.
+/// Deserializing the key or the value, and fetching their schema, is only worth it when something reads them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RecordFields {
+    pub key: bool,
+    pub value: bool,
+}
+
+impl RecordFields {
+    pub const NONE: Self = Self {
+        key: false,
+        value: false,
+    };
+    pub const KEY: Self = Self {
+        key: true,
+        value: false,
+    };
+    pub const VALUE: Self = Self {
+        key: false,
+        value: true,
+    };
+    pub const ALL: Self = Self {
+        key: true,
+        value: true,
+    };
+
+    pub fn union(self, other: Self) -> Self {
+        Self {
+            key: self.key || other.key,
+            value: self.value || other.value,
+        }
+    }
+
+    /// Returns the fields of `self` that are not in `other`.
+    pub fn difference(self, other: Self) -> Self {
+        Self {
+            key: self.key && !other.key,
+            value: self.value && !other.value,
+        }
+    }
+
+    pub fn is_empty(self) -> bool {
+        !self.key && !self.value
+    }
+}
+
 /// Inspired of the `[rdkafka::Message]` struct.
 /// Currently, we only support utf-8 string keys/values/headers.
 #[derive(Clone, Debug, Deserialize, Serialize, Hash, PartialEq, Eq, Default)]
@@ -51,6 +99,9 @@ pub struct KafkaRecord {
     #[serde(skip_serializing, default)]
     /// The value as a string. needed to be displayed in the TUI
     pub value_as_string: String,
+    /// `true` when the record has no value. In a compacted topic, a tombstone deletes the key.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub tombstone: bool,
 }
 
 #[cfg(feature = "native")]
@@ -67,6 +118,18 @@ impl KafkaRecord {
     pub fn has_schemas(&self) -> bool {
         self.key_schema.is_some() || self.value_schema.is_some()
     }
+
+    /// Normalizes a kafka record deserialized from JSON:
+    /// JSON strings become [`DataType::String`] and the string representations of the key and the value, that are not serialized, are updated.
+    pub fn normalize(&mut self) {
+        for data in [&mut self.key, &mut self.value] {
+            if let DataType::Json(serde_json::Value::String(s)) = data {
+                *data = DataType::String(std::mem::take(s));
+            }
+        }
+        self.key_as_string = self.key.to_string();
+        self.value_as_string = self.value.to_string();
+    }
 }
 
 #[cfg(feature = "native")]
@@ -100,6 +163,7 @@ impl KafkaRecord {
             key_schema,
             value_schema,
             size,
+            tombstone: owned_message.payload().is_none(),
         }
     }
 
@@ -107,37 +171,81 @@ impl KafkaRecord {
         owned_message: OwnedMessage,
         schema_registry: &mut Option<SchemaRegistryClient>,
     ) -> Self {
-        let headers = Self::extract_headers(&owned_message);
-        let size = owned_message.payload().map_or(0, <[u8]>::len)
-            + owned_message.key().map_or(0, <[u8]>::len);
-
-        let (key, key_schema, value, value_schema) = match owned_message.topic() {
-            "__consumer_offsets" => {
-                extract_key_and_value_from_consumer_offsets_topics(&owned_message)
-            }
-            _ => {
-                let (key, key_schema) =
-                    Self::extract_data_and_schema(owned_message.key(), schema_registry).await;
-                let (value, value_schema) =
-                    Self::extract_data_and_schema(owned_message.payload(), schema_registry).await;
-                (key, key_schema, value, value_schema)
-            }
-        };
+        let mut record = Self::parse_metadata(&owned_message);
+        record
+            .deserialize_fields(&owned_message, schema_registry, None, RecordFields::ALL)
+            .await;
+        record
+    }
 
+    /// Parses the metadata of the message: topic, partition, offset, timestamp, headers and size.
+    /// The key and the value stay empty until they are deserialized with [`KafkaRecord::deserialize_fields`].
+    pub fn parse_metadata(owned_message: &OwnedMessage) -> Self {
         Self {
-            value_as_string: value.to_string(),
-            value,
-            key_as_string: key.to_string(),
-            key,
             topic: owned_message.topic().to_string(),
             timestamp: owned_message.timestamp().to_millis(),
             partition: owned_message.partition(),
             offset: owned_message.offset(),
-            headers,
-            key_schema,
-            value_schema,
-            size,
+            headers: Self::extract_headers(owned_message),
+            size: owned_message.payload().map_or(0, <[u8]>::len)
+                + owned_message.key().map_or(0, <[u8]>::len),
+            tombstone: owned_message.payload().is_none(),
+            ..Default::default()
+        }
+    }
+
+    /// Deserializes the key and/or the value of the message, fetching their schema if needed.
+    /// When a custom deserializer is given, it deserializes the payloads instead and the schema registry is not used.
+    pub async fn deserialize_fields(
+        &mut self,
+        owned_message: &OwnedMessage,
+        schema_registry: &mut Option<SchemaRegistryClient>,
+        deserializer: Option<&dyn PayloadDeserializer>,
+        fields: RecordFields,
+    ) {
+        if fields.is_empty() {
+            return;
         }
+        if owned_message.topic() == "__consumer_offsets" {
+            let (key, key_schema, value, value_schema) =
+                extract_key_and_value_from_consumer_offsets_topics(owned_message);
+            self.set_key(key, key_schema);
+            self.set_value(value, value_schema);
+            return;
+        }
+        if let Some(deserializer) = deserializer {
+            if fields.key {
+                let key = self.deserialize_with(deserializer, "key", owned_message.key());
+                self.set_key(key, None);
+            }
+            if fields.value {
+                let value = self.deserialize_with(deserializer, "value", owned_message.payload());
+                self.set_value(value, None);
+            }
+            return;
+        }
+        if fields.key {
+            let (key, key_schema) =
+                Self::extract_data_and_schema(owned_message.key(), schema_registry).await;
+            self.set_key(key, key_schema);
+        }
+        if fields.value {
+            let (value, value_schema) =
+                Self::extract_data_and_schema(owned_message.payload(), schema_registry).await;
+            self.set_value(value, value_schema);
+        }
+    }
+
+    fn set_key(&mut self, key: DataType, schema: Option<SchemaRef>) {
+        self.key_as_string = key.to_string();
+        self.key = key;
+        self.key_schema = schema;
+    }
+
+    fn set_value(&mut self, value: DataType, schema: Option<SchemaRef>) {
+        self.value_as_string = value.to_string();
+        self.value = value;
+        self.value_schema = schema;
     }
 
     fn payload_to_data_type(payload: Option<&[u8]>, schema: Option<&Schema>) -> DataType {
@@ -156,6 +264,36 @@ impl KafkaRecord {
         }
     }
 
+    /// Deserializes the key or the value with a custom deserializer.
+    /// A missing key or value is left empty, the deserializer is not called.
+    /// Errors are displayed in place of the payload, like for the other formats.
+    fn deserialize_with(
+        &self,
+        deserializer: &dyn PayloadDeserializer,
+        field: &str,
+        payload: Option<&[u8]>,
+    ) -> DataType {
+        let Some(payload) = payload else {
+            return DataType::default();
+        };
+        let input = DeserializerInput {
+            topic: self.topic.clone(),
+            field: field.to_string(),
+            payload: payload.to_vec(),
+            headers: self.headers.clone(),
+        };
+        match deserializer.deserialize(&input) {
+            Ok(DataType::Json(serde_json::Value::String(s))) => DataType::String(s),
+            Ok(data) => data,
+            Err(e) => DataType::String(format!(
+                "  Yozefu Error: The custom deserializer of topic '{}' could not deserialize the {field}: {e}\n       Payload: {:?}\n        String: {}",
+                self.topic,
+                payload,
+                String::from_utf8(payload.to_vec()).unwrap_or_default()
+            )),
+        }
+    }
+
     /// Fallback to String if this is not json
     /// Will I regret it ? Maybe
     fn try_deserialize_json(payload: Option<&[u8]>) -> Result<DataType, Error> {
@@ -330,3 +468,22 @@ fn test_payload_to_data_type() {
     let d = KafkaRecord::payload_to_data_type(Some("true".as_bytes()), None);
     assert_eq!(d, DataType::Json(serde_json::json!(true)));
 }
+
+#[test]
+fn test_missing_payload_is_not_deserialized() {
+    struct Failing;
+    impl PayloadDeserializer for Failing {
+        fn deserialize(&self, _input: &DeserializerInput) -> Result<DataType, crate::Error> {
+            Err(crate::Error::Error("the payload is empty".to_string()))
+        }
+    }
+    let record = KafkaRecord::default();
+    assert_eq!(
+        record.deserialize_with(&Failing, "key", None),
+        DataType::default()
+    );
+    assert_ne!(
+        record.deserialize_with(&Failing, "key", Some(&[])),
+        DataType::default()
+    );
+}
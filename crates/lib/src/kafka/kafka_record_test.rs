@@ -1,11 +1,11 @@
 use std::{collections::BTreeMap, fs, path::PathBuf};
 
 use chrono::{Local, TimeZone};
-use rdkafka::message::OwnedMessage;
+use rdkafka::message::{Header, OwnedHeaders, OwnedMessage};
 
 use crate::{
     DataType, KafkaRecord,
-    kafka::{SchemaId, schema::Schema},
+    kafka::{DeserializerInput, PayloadDeserializer, RecordFields, SchemaId, schema::Schema},
 };
 
 #[test]
@@ -28,6 +28,101 @@ fn test_kafka_record_deserialization() {
     );
 }
 
+#[tokio::test]
+async fn test_deserialize_fields_lazily() {
+    let message = OwnedMessage::new(
+        Some(br#"{"status":"FAILED"}"#.to_vec()),
+        Some(b"my-key".to_vec()),
+        "my-awesome-topic".to_string(),
+        rdkafka::Timestamp::CreateTime(0),
+        2,
+        313,
+        None,
+    );
+    let mut record = KafkaRecord::parse_metadata(&message);
+    assert_eq!((record.partition, record.offset, record.size), (2, 313, 25));
+    assert_eq!(record.key, DataType::default());
+    assert_eq!(record.value, DataType::default());
+
+    record
+        .deserialize_fields(&message, &mut None, None, RecordFields::KEY)
+        .await;
+    assert_eq!(record.key_as_string, "my-key");
+    assert_eq!(record.value, DataType::default());
+
+    record
+        .deserialize_fields(&message, &mut None, None, RecordFields::VALUE)
+        .await;
+    assert_eq!(
+        record,
+        KafkaRecord::parse_with_schema_registry(message, &mut None).await
+    );
+}
+
+/// Decodes `field=value` pairs separated by `;`
+struct KeyValueDeserializer;
+
+impl PayloadDeserializer for KeyValueDeserializer {
+    fn deserialize(&self, input: &DeserializerInput) -> Result<DataType, crate::Error> {
+        if input.field == "key" {
+            return Ok(DataType::Json(serde_json::Value::String(format!(
+                "{}-{}",
+                input.headers["region"],
+                str::from_utf8(&input.payload)?
+            ))));
+        }
+        let pairs = str::from_utf8(&input.payload)?
+            .split(';')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(k, v)| (k.to_string(), serde_json::Value::String(v.to_string())))
+            .collect();
+        Ok(DataType::Json(serde_json::Value::Object(pairs)))
+    }
+}
+
+#[tokio::test]
+async fn test_deserialize_fields_with_custom_deserializer() {
+    let message = OwnedMessage::new(
+        Some(b"status=FAILED;amount=10".to_vec()),
+        Some(b"my-key".to_vec()),
+        "legacy".to_string(),
+        rdkafka::Timestamp::CreateTime(0),
+        0,
+        1,
+        Some(OwnedHeaders::new().insert(Header {
+            key: "region",
+            value: Some("eu"),
+        })),
+    );
+    let mut record = KafkaRecord::parse_metadata(&message);
+    record
+        .deserialize_fields(
+            &message,
+            &mut None,
+            Some(&KeyValueDeserializer),
+            RecordFields::ALL,
+        )
+        .await;
+    assert_eq!(record.key, DataType::String("eu-my-key".to_string()));
+    assert_eq!(
+        record.value_as_string,
+        r#"{"status":"FAILED","amount":"10"}"#
+    );
+    assert_eq!(record.value_schema, None);
+}
+
+#[test]
+fn test_normalize_kafka_record_from_json() {
+    let mut record: KafkaRecord = serde_json::from_str(
+        r#"{"topic":"a","partition":0,"offset":0,"headers":{},"key":"k1","value":{"amount":10}}"#,
+    )
+    .unwrap();
+    record.normalize();
+    assert_eq!(record.key, DataType::String("k1".to_string()));
+    assert_eq!(record.key_as_string, "k1");
+    assert_eq!(record.value_as_string, r#"{"amount":10}"#);
+}
+
 #[test]
 fn test_has_schemas() {
     let record = KafkaRecord {
@@ -43,6 +138,7 @@ fn test_has_schemas() {
         key: DataType::String("".into()),
         value_as_string: "".into(),
         value: DataType::String("".into()),
+        tombstone: false,
     };
     assert!(record.has_schemas());
 
@@ -59,6 +155,7 @@ fn test_has_schemas() {
         key: DataType::String("".into()),
         value_as_string: "".into(),
         value: DataType::String("".into()),
+        tombstone: false,
     };
 
     assert!(!record.has_schemas());
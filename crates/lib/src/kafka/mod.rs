@@ -17,11 +17,17 @@ mod internal;
 
 mod data_type;
 
+mod deserializer;
+
 mod kafka_record;
+pub mod partitioner;
 mod schema;
 pub use data_type::Comparable;
 pub use data_type::DataType;
-pub use kafka_record::KafkaRecord;
+pub use deserializer::DeserializerInput;
+#[cfg(feature = "native")]
+pub use deserializer::PayloadDeserializer;
+pub use kafka_record::{KafkaRecord, RecordFields};
 pub use schema::SchemaId;
 
 #[cfg(test)]
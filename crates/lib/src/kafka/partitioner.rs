@@ -0,0 +1,72 @@
+//! Implementation of the partitioner used by default by Kafka producers.
+//! Knowing the partitioner, the partition of a record can be deduced from its key.
+
+/// Returns the partition the default partitioner of Kafka assigns a record with the given key to.
+pub fn default_partition(key: &[u8], partitions: i32) -> i32 {
+    if partitions <= 0 {
+        return 0;
+    }
+    (murmur2(key) & 0x7fff_ffff) % partitions
+}
+
+/// The 32-bit murmur2 hash, as implemented by the Java Kafka client.
+pub fn murmur2(data: &[u8]) -> i32 {
+    const SEED: u32 = 0x9747_b28c;
+    const M: u32 = 0x5bd1_e995;
+    const R: u32 = 24;
+
+    let mut h = SEED ^ data.len() as u32;
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut k = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+        h = h.wrapping_mul(M);
+        h ^= k;
+    }
+
+    let tail = chunks.remainder();
+    if tail.len() >= 3 {
+        h ^= u32::from(tail[2]) << 16;
+    }
+    if tail.len() >= 2 {
+        h ^= u32::from(tail[1]) << 8;
+    }
+    if !tail.is_empty() {
+        h ^= u32::from(tail[0]);
+        h = h.wrapping_mul(M);
+    }
+
+    h ^= h >> 13;
+    h = h.wrapping_mul(M);
+    h ^= h >> 15;
+    h as i32
+}
+
+#[test]
+fn test_murmur2() {
+    // Expected values come from the test suite of the Java Kafka client.
+    for (input, expected) in [
+        ("21", -973_932_308),
+        ("foobar", -790_332_482),
+        ("a-little-bit-long-string", -985_981_536),
+        ("a-little-bit-longer-string", -1_486_304_829),
+        (
+            "lkjh234lh9fiuh90y23oiuhsafujhadof229phr9h19h89h8",
+            -58_897_971,
+        ),
+        ("abc", 479_470_107),
+    ] {
+        assert_eq!(murmur2(input.as_bytes()), expected, "murmur2({input})");
+    }
+}
+
+#[test]
+fn test_default_partition() {
+    assert_eq!(
+        default_partition(b"foobar", 6),
+        (-790_332_482 & 0x7fff_ffff) % 6
+    );
+    assert_eq!(default_partition(b"foobar", 0), 0);
+}
@@ -7,6 +7,7 @@ use serde_json::Value;
 use std::{
     collections::{HashMap, HashSet, VecDeque},
     str::FromStr,
+    sync::{Arc, RwLock},
     time::Duration,
 };
 use url::Url;
@@ -176,33 +177,38 @@ impl SimpleSchemaRegistryClient {
 
 #[derive(Clone, Debug)]
 /// A HTTP client to communicate with a confluent schema registry
-/// All schemas are cached
+/// All schemas are cached. Clones of the client share the same cache.
 pub struct SchemaRegistryClient {
     client: SimpleSchemaRegistryClient,
-    cache: HashMap<u32, Schema>,
+    cache: Arc<RwLock<HashMap<u32, Schema>>>,
 }
 
 impl SchemaRegistryClient {
     pub fn new(base_url: Url, headers: &HashMap<String, String>) -> Self {
         Self {
             client: SimpleSchemaRegistryClient::new(base_url, headers),
-            cache: HashMap::default(),
+            cache: Arc::default(),
         }
     }
 
     pub async fn schema(&mut self, id: u32) -> Result<Option<Schema>, Error> {
-        match self.cache.get(&id) {
-            Some(schema) => Ok(Some(schema.clone())),
+        match self.cached_schema(id) {
+            Some(schema) => Ok(Some(schema)),
             None => {
                 let schema = self.client.schema(id).await?;
-                if let Some(schema) = &schema {
-                    self.cache.insert(id, schema.clone());
+                if let (Some(schema), Ok(mut cache)) = (&schema, self.cache.write()) {
+                    cache.insert(id, schema.clone());
                 }
                 Ok(schema)
             }
         }
     }
 
+    /// Returns the schema if it is already in the cache, the schema registry is not requested.
+    pub fn cached_schema(&self, id: u32) -> Option<Schema> {
+        self.cache.read().ok().and_then(|c| c.get(&id).cloned())
+    }
+
     pub fn schema_url(&self, id: u32) -> String {
         self.client.schema_url(id)
     }
@@ -0,0 +1,340 @@
+//! Aggregation clauses summarize the kafka records matching the search query.
+//!
+//! ```sql
+//! count
+//! count by value.status
+//! distinct key
+//! min(timestamp) max(timestamp)
+//! ```
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+};
+
+use chrono::{DateTime, Local};
+use nom::{
+    IResult, Parser,
+    branch::alt,
+    bytes::complete::tag_no_case,
+    character::complete::{char, satisfy},
+    combinator::{map, not, peek},
+    sequence::{delimited, pair, preceded, terminated},
+};
+
+use crate::KafkaRecord;
+
+use super::{
+    select::{field_name, field_value, parse_field},
+    symbol::Symbol,
+    wsi::wsi,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+pub enum Aggregation {
+    /// Number of matching records.
+    Count,
+    /// Number of matching records for each value of the field.
+    CountBy(Symbol),
+    /// Number of distinct values of the field.
+    Distinct(Symbol),
+    /// Smallest value of the field.
+    Min(Symbol),
+    /// Greatest value of the field.
+    Max(Symbol),
+}
+
+impl std::fmt::Display for Aggregation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Aggregation::Count => write!(f, "count"),
+            Aggregation::CountBy(field) => write!(f, "count by {}", field_name(field)),
+            Aggregation::Distinct(field) => write!(f, "distinct {}", field_name(field)),
+            Aggregation::Min(field) => write!(f, "min({})", field_name(field)),
+            Aggregation::Max(field) => write!(f, "max({})", field_name(field)),
+        }
+    }
+}
+
+/// A row of the aggregation results.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AggregationRow {
+    /// The aggregation, as written in the search query.
+    pub aggregation: String,
+    /// The group of records, only for `count by` aggregations.
+    pub group: Option<String>,
+    pub value: String,
+}
+
+#[derive(Debug, Clone)]
+enum AggregationState {
+    Count(usize),
+    CountBy(BTreeMap<String, usize>),
+    Distinct(BTreeSet<String>),
+    Min(Option<serde_json::Value>),
+    Max(Option<serde_json::Value>),
+}
+
+/// Accumulates the kafka records matching the search query to compute its aggregations.
+#[derive(Debug, Clone, Default)]
+pub struct Aggregator {
+    aggregations: Vec<(Aggregation, AggregationState)>,
+}
+
+impl Aggregator {
+    pub fn new(aggregations: &[Aggregation]) -> Self {
+        Self {
+            aggregations: aggregations
+                .iter()
+                .map(|aggregation| {
+                    let state = match aggregation {
+                        Aggregation::Count => AggregationState::Count(0),
+                        Aggregation::CountBy(_) => AggregationState::CountBy(BTreeMap::new()),
+                        Aggregation::Distinct(_) => AggregationState::Distinct(BTreeSet::new()),
+                        Aggregation::Min(_) => AggregationState::Min(None),
+                        Aggregation::Max(_) => AggregationState::Max(None),
+                    };
+                    (aggregation.clone(), state)
+                })
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.aggregations.is_empty()
+    }
+
+    /// Aggregates a record matching the search query.
+    pub fn add(&mut self, record: &KafkaRecord) {
+        for (aggregation, state) in &mut self.aggregations {
+            match (aggregation, state) {
+                (Aggregation::Count, AggregationState::Count(count)) => *count += 1,
+                (Aggregation::CountBy(field), AggregationState::CountBy(groups)) => {
+                    *groups
+                        .entry(group_of(&field_value(field, record)))
+                        .or_default() += 1;
+                }
+                (Aggregation::Distinct(field), AggregationState::Distinct(values)) => {
+                    values.insert(group_of(&field_value(field, record)));
+                }
+                (Aggregation::Min(field), AggregationState::Min(min)) => {
+                    let value = field_value(field, record);
+                    if !value.is_null()
+                        && min
+                            .as_ref()
+                            .is_none_or(|m| compare_values(&value, m) == Ordering::Less)
+                    {
+                        *min = Some(value);
+                    }
+                }
+                (Aggregation::Max(field), AggregationState::Max(max)) => {
+                    let value = field_value(field, record);
+                    if !value.is_null()
+                        && max
+                            .as_ref()
+                            .is_none_or(|m| compare_values(&value, m) == Ordering::Greater)
+                    {
+                        *max = Some(value);
+                    }
+                }
+                _ => unreachable!("An aggregation is always initialized with its own state"),
+            }
+        }
+    }
+
+    /// Returns the results of the aggregations.
+    pub fn rows(&self) -> Vec<AggregationRow> {
+        let mut rows = vec![];
+        for (aggregation, state) in &self.aggregations {
+            let label = aggregation.to_string();
+            match state {
+                AggregationState::Count(count) => rows.push(AggregationRow {
+                    aggregation: label,
+                    group: None,
+                    value: count.to_string(),
+                }),
+                AggregationState::CountBy(groups) => {
+                    rows.extend(groups.iter().map(|(group, count)| AggregationRow {
+                        aggregation: label.clone(),
+                        group: Some(group.clone()),
+                        value: count.to_string(),
+                    }));
+                }
+                AggregationState::Distinct(values) => rows.push(AggregationRow {
+                    aggregation: label,
+                    group: None,
+                    value: values.len().to_string(),
+                }),
+                AggregationState::Min(value) | AggregationState::Max(value) => {
+                    let field = match aggregation {
+                        Aggregation::Min(field) | Aggregation::Max(field) => field,
+                        _ => {
+                            unreachable!("An aggregation is always initialized with its own state")
+                        }
+                    };
+                    rows.push(AggregationRow {
+                        aggregation: label,
+                        group: None,
+                        value: value
+                            .as_ref()
+                            .map(|v| display_value(field, v))
+                            .unwrap_or_default(),
+                    });
+                }
+            }
+        }
+        rows
+    }
+}
+
+fn group_of(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn compare_values(a: &serde_json::Value, b: &serde_json::Value) -> Ordering {
+    match (a, b) {
+        (serde_json::Value::Number(a), serde_json::Value::Number(b)) => a
+            .as_f64()
+            .partial_cmp(&b.as_f64())
+            .unwrap_or(Ordering::Equal),
+        (a, b) => group_of(a).cmp(&group_of(b)),
+    }
+}
+
+fn display_value(field: &Symbol, value: &serde_json::Value) -> String {
+    match (field, value.as_i64()) {
+        (Symbol::Timestamp, Some(timestamp)) => DateTime::from_timestamp_millis(timestamp)
+            .map(|t| {
+                t.with_timezone(&Local)
+                    .to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+            })
+            .unwrap_or_default(),
+        _ => group_of(value),
+    }
+}
+
+/// Makes sure a keyword is not the prefix of a longer word, such as the name of a search filter.
+fn keyword<'a>(
+    name: &'static str,
+) -> impl Parser<&'a str, Output = &'a str, Error = nom::error::Error<&'a str>> {
+    terminated(
+        tag_no_case(name),
+        not(peek(satisfy(|c: char| {
+            c.is_alphanumeric() || c == '-' || c == '_' || c == '('
+        }))),
+    )
+}
+
+fn parse_count(input: &str) -> IResult<&str, Aggregation> {
+    alt((
+        map(
+            preceded(
+                alt((
+                    pair(wsi(keyword("count")), wsi(keyword("by"))),
+                    pair(wsi(keyword("group")), wsi(keyword("by"))),
+                )),
+                parse_field,
+            ),
+            Aggregation::CountBy,
+        ),
+        map(wsi(keyword("count")), |_| Aggregation::Count),
+    ))
+    .parse(input)
+}
+
+fn parse_distinct(input: &str) -> IResult<&str, Aggregation> {
+    map(
+        preceded(wsi(keyword("distinct")), parse_field),
+        Aggregation::Distinct,
+    )
+    .parse(input)
+}
+
+fn parse_min_max(input: &str) -> IResult<&str, Aggregation> {
+    let argument = || delimited(wsi(char('(')), parse_field, wsi(char(')')));
+    alt((
+        map(
+            preceded(wsi(tag_no_case("min")), argument()),
+            Aggregation::Min,
+        ),
+        map(
+            preceded(wsi(tag_no_case("max")), argument()),
+            Aggregation::Max,
+        ),
+    ))
+    .parse(input)
+}
+
+pub(crate) fn parse_aggregation(input: &str) -> IResult<&str, Aggregation> {
+    alt((parse_count, parse_distinct, parse_min_max)).parse(input)
+}
+
+#[test]
+fn test_parse_aggregation() {
+    assert_eq!(parse_aggregation("count"), Ok(("", Aggregation::Count)));
+    assert_eq!(
+        parse_aggregation("count by value.status"),
+        Ok((
+            "",
+            Aggregation::CountBy(Symbol::Value(Some(".status".to_string())))
+        ))
+    );
+    assert_eq!(
+        parse_aggregation("group by partition"),
+        Ok(("", Aggregation::CountBy(Symbol::Partition)))
+    );
+    assert_eq!(
+        parse_aggregation("distinct key"),
+        Ok(("", Aggregation::Distinct(Symbol::Key)))
+    );
+    assert_eq!(
+        parse_aggregation("min(timestamp)"),
+        Ok(("", Aggregation::Min(Symbol::Timestamp)))
+    );
+    assert_eq!(
+        parse_aggregation("max( offset )"),
+        Ok(("", Aggregation::Max(Symbol::Offset)))
+    );
+    assert!(parse_aggregation("counter(\"42\")").is_err());
+}
+
+#[test]
+fn test_aggregator() {
+    use crate::DataType;
+    use serde_json::json;
+
+    let aggregations = [
+        Aggregation::Count,
+        Aggregation::CountBy(Symbol::Value(Some(".status".to_string()))),
+        Aggregation::Distinct(Symbol::Key),
+        Aggregation::Max(Symbol::Offset),
+    ];
+    let mut aggregator = Aggregator::new(&aggregations);
+    for (offset, key, status) in [(1, "a", "OK"), (2, "b", "FAILED"), (3, "a", "OK")] {
+        aggregator.add(&KafkaRecord {
+            offset,
+            key: DataType::String(key.to_string()),
+            value: DataType::Json(json!({ "status": status })),
+            ..Default::default()
+        });
+    }
+    let row = |aggregation: &str, group: Option<&str>, value: &str| AggregationRow {
+        aggregation: aggregation.to_string(),
+        group: group.map(str::to_string),
+        value: value.to_string(),
+    };
+    assert_eq!(
+        aggregator.rows(),
+        vec![
+            row("count", None, "3"),
+            row("count by value.status", Some("FAILED"), "1"),
+            row("count by value.status", Some("OK"), "2"),
+            row("distinct key", None, "2"),
+            row("max(offset)", None, "3"),
+        ]
+    );
+}
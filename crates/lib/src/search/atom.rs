@@ -1,4 +1,5 @@
-//! Atoms are the smallest unit of an expression. They can be a symbol, a comparison, a filter or a parenthesized expression.
+//! Atoms are the smallest unit of an expression. They can be a symbol, a comparison, a filter, a reference to a saved query or a parenthesized expression.
+use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
 use nom::{
@@ -9,22 +10,28 @@ use super::{
     compare::{CompareExpression, parse_compare},
     expression::{Expression, parse_or_expression},
     filter::{Filter, parse_filter},
+    saved_query::parse_saved_query,
     symbol::Symbol,
     wsi::wsi,
 };
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
 pub enum Atom {
     Symbol(Symbol),
     Compare(CompareExpression),
     Filter(Filter),
     Parenthesis(Box<Expression>),
+    /// A reference to a saved query, such as `@failed-payments`
+    SavedQuery(String),
 }
 
 pub(crate) fn parse_atom(input: &str) -> IResult<&str, Atom> {
     alt((
-        map(wsi(parse_filter), Atom::Filter),
+        // Comparisons on built-in functions look like search filters, they must be parsed first.
         map(wsi(parse_compare), Atom::Compare),
+        map(wsi(parse_filter), Atom::Filter),
+        map(wsi(parse_saved_query), Atom::SavedQuery),
         map(
             delimited(wsi(tag("(")), parse_or_expression, wsi(tag(")"))),
             |expr: Expression| Atom::Parenthesis(Box::new(expr)),
@@ -38,8 +45,9 @@ impl Display for Atom {
         match self {
             Atom::Symbol(a) => write!(f, "{a}"),
             Atom::Compare(a) => write!(f, "{a}"),
-            Atom::Parenthesis(a) => write!(f, "{a}"),
+            Atom::Parenthesis(a) => write!(f, "({a})"),
             Atom::Filter(a) => write!(f, "{a}"),
+            Atom::SavedQuery(name) => write!(f, "@{name}"),
         }
     }
 }
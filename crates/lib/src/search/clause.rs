@@ -10,9 +10,15 @@ use nom::{
 
 use crate::search::number::parse_number;
 
+use super::aggregation::{Aggregation, parse_aggregation};
 use super::expression::{Expression, parse_or_expression};
+use super::filter::Filter;
+use super::latest::parse_latest_by_key;
 use super::offset::{FromOffset, parse_from_offset};
 use super::order::{Order, OrderKeyword, parse_order, parse_order_keyword};
+use super::sample::{Sample, parse_sample};
+use super::select::{Projection, parse_projection};
+use super::transform::parse_transform;
 use super::wsi::wsi;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -25,6 +31,16 @@ pub(crate) enum SearchClause {
     From(FromOffset),
     /// Clause defining how to sort the kafka records in the UI
     OrderBy(Order, Option<OrderKeyword>),
+    /// Clause listing the fields to output in the headless mode
+    Select(Projection),
+    /// Clause summarizing the matching kafka records
+    Aggregation(Aggregation),
+    /// Clause keeping only the newest kafka record of each key
+    LatestByKey,
+    /// Clause keeping only a fraction of the kafka records
+    Sample(Sample),
+    /// Clause rewriting the kafka records with a transformer
+    Transform(Filter),
 }
 
 pub(crate) fn parse_expression(input: &str) -> IResult<&str, SearchClause> {
@@ -51,6 +67,26 @@ pub(crate) fn parse_from_offset_clause(input: &str) -> IResult<&str, SearchClaus
     map(parse_from_offset, SearchClause::From).parse(input)
 }
 
+pub(crate) fn parse_select(input: &str) -> IResult<&str, SearchClause> {
+    map(parse_projection, SearchClause::Select).parse(input)
+}
+
+pub(crate) fn parse_aggregation_clause(input: &str) -> IResult<&str, SearchClause> {
+    map(parse_aggregation, SearchClause::Aggregation).parse(input)
+}
+
+pub(crate) fn parse_latest_by_key_clause(input: &str) -> IResult<&str, SearchClause> {
+    map(parse_latest_by_key, |()| SearchClause::LatestByKey).parse(input)
+}
+
+pub(crate) fn parse_sample_clause(input: &str) -> IResult<&str, SearchClause> {
+    map(parse_sample, SearchClause::Sample).parse(input)
+}
+
+pub(crate) fn parse_transform_clause(input: &str) -> IResult<&str, SearchClause> {
+    map(parse_transform, SearchClause::Transform).parse(input)
+}
+
 pub(crate) fn parse_limit(input: &str) -> IResult<&str, SearchClause> {
     map(
         preceded(wsi(tag_no_case("limit")), wsi(parse_number)),
@@ -3,7 +3,9 @@
 /// offset != 234
 /// key == "my-key"
 /// timestamp between "2 hours ago" and "1 hour ago"
+/// lower(value.email) == "john@example.com"
 /// ```
+use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
 #[cfg(feature = "native")]
@@ -19,9 +21,14 @@ use nom::{
 
 use super::number::NumberOperator;
 use super::string::StringOperator;
+#[cfg(feature = "native")]
+use crate::search::function::{Function, FunctionType, parse_function};
+#[cfg(feature = "native")]
+use crate::search::string::quote;
 
 #[cfg(feature = "native")]
-#[derive(Debug, PartialEq, Clone, Eq)]
+#[derive(Debug, PartialEq, Clone, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
 pub enum CompareExpression {
     Partition(NumberOperator, i32),
     OffsetTail(i64),
@@ -33,6 +40,10 @@ pub enum CompareExpression {
     Size(NumberOperator, usize),
     Timestamp(NumberOperator, DateTime<Local>),
     TimestampBetween(DateTime<Local>, DateTime<Local>),
+    /// Built-in function returning a string
+    StringFunction(Function, StringOperator, String),
+    /// Built-in function returning a number
+    NumberFunction(Function, NumberOperator, i64),
 }
 
 #[cfg(feature = "native")]
@@ -42,17 +53,17 @@ impl Display for CompareExpression {
             CompareExpression::Partition(op, r) => write!(f, "partition {op} {r}"),
             CompareExpression::OffsetTail(r) => write!(f, "offsetTail - {r}"),
             CompareExpression::Offset(op, r) => write!(f, "offset {op} {r}"),
-            CompareExpression::Topic(op, r) => write!(f, "topic {op} {r}"),
-            CompareExpression::Key(op, r) => write!(f, "key {op} {r}"),
+            CompareExpression::Topic(op, r) => write!(f, "topic {op} {}", quote(r)),
+            CompareExpression::Key(op, r) => write!(f, "key {op} {}", quote(r)),
             CompareExpression::Value(left, op, r) => write!(
                 f,
                 "value{} {} {}",
                 left.clone().unwrap_or(String::new()),
                 op,
-                r
+                quote(r)
             ),
             CompareExpression::Header(left, op, r) => {
-                write!(f, "headers.{} {} {}", left.clone(), op, r)
+                write!(f, "headers.{} {} {}", left.clone(), op, quote(r))
             }
             CompareExpression::Size(op, r) => write!(f, "size {op} {r}"),
             CompareExpression::Timestamp(op, r) => write!(
@@ -63,14 +74,55 @@ impl Display for CompareExpression {
             ),
             CompareExpression::TimestampBetween(l, r) => write!(
                 f,
-                r#"timestamp between "{}" and "{}"#,
+                r#"timestamp between "{}" and "{}""#,
                 l.to_rfc3339_opts(chrono::SecondsFormat::Millis, false),
                 r.to_rfc3339_opts(chrono::SecondsFormat::Millis, false)
             ),
+            CompareExpression::StringFunction(function, op, r) => {
+                write!(f, "{function} {op} {}", quote(r))
+            }
+            CompareExpression::NumberFunction(function, op, r) => write!(f, "{function} {op} {r}"),
         }
     }
 }
 
+/// Parses a comparison whose left operand is a built-in function.
+/// The operator and the right operand must match the type returned by the function.
+#[cfg(feature = "native")]
+fn parse_function_compare(input: &str) -> IResult<&str, CompareExpression> {
+    use crate::search::{
+        compare::string::parse_string_operator, number::parse_number, string::parse_string,
+        wsi::wsi,
+    };
+    use nom::combinator::verify;
+
+    use super::number::parse_number_operator;
+
+    alt((
+        map(
+            (
+                verify(wsi(parse_function), |f: &Function| {
+                    f.return_type() == FunctionType::String
+                }),
+                wsi(parse_string_operator),
+                wsi(parse_string),
+            ),
+            |(function, op, r)| CompareExpression::StringFunction(function, op, r),
+        ),
+        map(
+            (
+                verify(wsi(parse_function), |f: &Function| {
+                    f.return_type() == FunctionType::Number
+                }),
+                wsi(parse_number_operator),
+                wsi(parse_number),
+            ),
+            |(function, op, r)| CompareExpression::NumberFunction(function, op, r),
+        ),
+    ))
+    .parse(input)
+}
+
 #[cfg(feature = "native")]
 pub fn parse_compare(input: &str) -> IResult<&str, CompareExpression> {
     use crate::search::{
@@ -88,6 +140,7 @@ pub fn parse_compare(input: &str) -> IResult<&str, CompareExpression> {
     use super::number::parse_number_operator;
 
     alt((
+        parse_function_compare,
         map(
             (parse_offset, wsi(parse_number_operator), wsi(parse_number)),
             |(_, op, r)| CompareExpression::Offset(op, r),
@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
 use crate::search::wsi::wsi;
@@ -6,7 +7,8 @@ use nom::{IResult, branch::alt, bytes::complete::tag, combinator::value};
 
 use super::parse_equal;
 
-#[derive(Debug, PartialEq, Clone, Eq)]
+#[derive(Debug, PartialEq, Clone, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
 pub enum NumberOperator {
     GreaterThan,
     GreaterOrEqual,
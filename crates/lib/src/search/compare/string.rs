@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
 #[cfg(feature = "native")]
@@ -8,7 +9,8 @@ use nom::{
     combinator::value, sequence::pair,
 };
 
-#[derive(Debug, PartialEq, Clone, Eq)]
+#[derive(Debug, PartialEq, Clone, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
 pub enum StringOperator {
     Contain,
     Equal,
@@ -6,6 +6,7 @@
 /// key starts with "1234-" or offset < 100
 /// key starts with "1234-" || offset < 100
 /// ```
+use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
 use nom::Parser;
@@ -19,13 +20,15 @@ use super::wsi::wsi;
 
 // https://stackoverflow.com/questions/9509048/antlr-parser-for-and-or-logic-how-to-get-expressions-between-logic-operators
 pub type Expression = OrExpression;
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
 pub enum AndExpression {
     AndTerm(Term),
     AndExpression(Vec<Term>),
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
 pub enum OrExpression {
     OrTerm(AndExpression),
     OrExpression(Vec<AndExpression>),
@@ -13,6 +13,7 @@
 //!  - the wasm module must implement 2 functions:
 //!      - `fn matches(input: Input): bool` - this function returns `true` if the kafka record matches the condition.
 //!      - `fn parse_parameters(params: Vec<Value>): bool` -  this function is optional: it returns `true` when the parameters are valid. Parameters are serialized to an JSON array.
+//!  - the wasm module can also implement `fn describe(): FilterDescription`, it tells what the filter does and what parameters it takes.
 //!
 //! The library uses [Extism](https://extism.org/) to develop wasm modules.
 //! You can also find the source code of  the `contains` WebAssembly module written in different supported programming languages.
@@ -23,19 +24,25 @@ use nom::{
     branch::alt,
     bytes::complete::tag,
     character::complete::{alphanumeric1, one_of},
-    combinator::{map, recognize},
+    combinator::{eof, map, recognize},
     multi::{many1, separated_list0},
-    sequence::delimited,
+    sequence::{delimited, terminated},
 };
 use serde::{Deserialize, Serialize};
 use serde_json::{Number, Value};
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
-use crate::KafkaRecord;
+use crate::{KafkaRecord, error::SearchError};
 
-use super::{number::parse_number, string::parse_string, wsi::wsi};
+use super::{
+    number::parse_number,
+    parse_error::ParseError,
+    string::{parse_string, quote_with},
+    wsi::wsi,
+};
 
-#[derive(Debug, PartialEq, Clone, Default)]
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
 pub struct Filter {
     pub name: String,
     pub parameters: Vec<Parameter>,
@@ -55,7 +62,26 @@ impl Display for Filter {
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Eq)]
+/// Parses a filter call such as `decrypt('k1')`.
+impl FromStr for Filter {
+    type Err = SearchError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        terminated(parse_filter, wsi(eof))
+            .parse(input)
+            .map(|(_, filter)| filter)
+            .map_err(|e| {
+                let remaining = match e {
+                    nom::Err::Incomplete(_) => input,
+                    nom::Err::Error(s) | nom::Err::Failure(s) => s.input,
+                };
+                SearchError::Parse(ParseError::new(input, remaining))
+            })
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
 pub enum Parameter {
     Number(i64),
     String(String),
@@ -65,7 +91,7 @@ impl Display for Parameter {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             Parameter::Number(i) => write!(f, "{i}"),
-            Parameter::String(i) => write!(f, "'{i}'"),
+            Parameter::String(i) => write!(f, "{}", quote_with(i, '\'')),
         }
     }
 }
@@ -125,6 +151,59 @@ pub struct FilterInput {
     pub params: Vec<Value>,
 }
 
+/// Output of the optional `describe` function of a search filter.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+pub struct FilterDescription {
+    /// The name of the search filter.
+    pub name: String,
+    /// What the search filter does.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// A JSON schema describing the array of parameters.
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub parameters: Value,
+    /// Search queries using the search filter.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<String>,
+}
+
+impl FilterDescription {
+    /// Checks the description is complete enough to be displayed.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("the name of the search filter is empty".to_string());
+        }
+        if !(self.parameters.is_null() || self.parameters.is_object()) {
+            return Err("the parameters must be described with a JSON schema".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn generate_json_schema_for_filter_description() {
+    use schemars::schema_for;
+    let mut schema = schema_for!(FilterDescription);
+    schema.insert("$id".into(), "https://raw.githubusercontent.com/MAIF/yozefu/refs/heads/main/docs/json-schemas/filter-description.json".into());
+    std::fs::write(
+        std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .parent()
+            .unwrap()
+            .join("docs")
+            .join("json-schemas")
+            .join("filter-description.json"),
+        serde_json::to_string_pretty(&schema).unwrap(),
+    )
+    .unwrap();
+}
+
 #[test]
 fn generate_json_schema_for_filter_input() {
     use schemars::schema_for;
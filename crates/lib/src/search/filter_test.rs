@@ -1,4 +1,4 @@
-use crate::search::filter::{Filter, Parameter};
+use crate::search::filter::{Filter, FilterDescription, Parameter};
 
 #[test]
 fn test_filter_to_string() {
@@ -11,3 +11,47 @@ fn test_filter_to_string() {
         "my_filter(10, 'value')"
     )
 }
+
+#[test]
+fn test_filter_round_trip() {
+    for input in [
+        r#"myFilter("it's")"#,
+        r#"myFilter('say "hi"')"#,
+        r#"myFilter("say \"hi\", it's", 'C:\temp\\')"#,
+    ] {
+        let filter = input.parse::<Filter>().unwrap();
+        assert_eq!(filter.to_string().parse::<Filter>().unwrap(), filter);
+    }
+    assert_eq!(
+        r#"myFilter("it's")"#.parse::<Filter>().unwrap().to_string(),
+        r#"myFilter("it's")"#
+    );
+}
+
+#[test]
+fn test_filter_from_str() {
+    assert_eq!(
+        "decrypt('k1', 2)".parse::<Filter>().unwrap(),
+        Filter {
+            name: "decrypt".into(),
+            parameters: vec![Parameter::String("k1".into()), Parameter::Number(2)],
+        }
+    );
+    assert!("decrypt('k1') and".parse::<Filter>().is_err());
+}
+
+#[test]
+fn test_validate_filter_description() {
+    let description: FilterDescription = serde_json::from_str(
+        r#"{"name": "key-ends-with", "version": "1.0.0", "parameters": {"type": "array", "items": [{"type": "string"}]}}"#,
+    )
+    .unwrap();
+    assert_eq!(description.validate(), Ok(()));
+    assert_eq!(description.description, None);
+
+    let description: FilterDescription =
+        serde_json::from_str(r#"{"name": "key-ends-with", "parameters": ["string"]}"#).unwrap();
+    assert!(description.validate().is_err());
+    let description: FilterDescription = serde_json::from_str(r#"{"name": " "}"#).unwrap();
+    assert!(description.validate().is_err());
+}
@@ -0,0 +1,231 @@
+//! Built-in scalar functions that can be used in comparisons.
+//! Unlike search filters, they are implemented in Rust and don't need any WASM module.
+//!
+//! ```sql
+//! lower(value.email) == "john@example.com"
+//! length(value.items) > 10
+//! len(key) != 36
+//! json_type(value.amount) == "string"
+//! header_count() > 5
+//! ```
+//!
+//! Functions are type-checked at parse time: `length(offset)` or `lower(value) > 3` are rejected.
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+use nom::{
+    IResult, Parser,
+    branch::alt,
+    bytes::complete::tag_no_case,
+    character::complete::char,
+    combinator::{map, verify},
+    sequence::{delimited, pair, preceded},
+};
+
+use crate::KafkaRecord;
+
+use super::{
+    select::{field_name, field_value, parse_field},
+    symbol::Symbol,
+    wsi::wsi,
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+pub enum Function {
+    /// Lowercase string
+    Lower(Symbol),
+    /// Uppercase string
+    Upper(Symbol),
+    /// Number of characters of a string, number of items of an array or number of properties of an object
+    Length(Symbol),
+    /// Type of a JSON value: `null`, `boolean`, `number`, `string`, `array` or `object`
+    JsonType(Symbol),
+    /// Number of headers of the record
+    HeaderCount,
+}
+
+/// Type returned by a function.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FunctionType {
+    String,
+    Number,
+}
+
+/// Value returned by a function.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum FunctionValue {
+    String(String),
+    Number(i64),
+}
+
+impl Function {
+    pub fn return_type(&self) -> FunctionType {
+        match self {
+            Function::Lower(_) | Function::Upper(_) | Function::JsonType(_) => FunctionType::String,
+            Function::Length(_) | Function::HeaderCount => FunctionType::Number,
+        }
+    }
+
+    /// Evaluates the function on the kafka record.
+    /// It returns `None` when the function is not defined for the value, for instance the length of a number.
+    pub fn evaluate(&self, record: &KafkaRecord) -> Option<FunctionValue> {
+        match self {
+            Function::Lower(field) => as_string(&field_value(field, record))
+                .map(|s| FunctionValue::String(s.to_lowercase())),
+            Function::Upper(field) => as_string(&field_value(field, record))
+                .map(|s| FunctionValue::String(s.to_uppercase())),
+            Function::Length(field) => {
+                let length = match field_value(field, record) {
+                    serde_json::Value::String(s) => s.chars().count(),
+                    serde_json::Value::Array(a) => a.len(),
+                    serde_json::Value::Object(o) => o.len(),
+                    _ => return None,
+                };
+                i64::try_from(length).ok().map(FunctionValue::Number)
+            }
+            Function::JsonType(field) => {
+                let json_type = match field_value(field, record) {
+                    serde_json::Value::Null => "null",
+                    serde_json::Value::Bool(_) => "boolean",
+                    serde_json::Value::Number(_) => "number",
+                    serde_json::Value::String(_) => "string",
+                    serde_json::Value::Array(_) => "array",
+                    serde_json::Value::Object(_) => "object",
+                };
+                Some(FunctionValue::String(json_type.to_string()))
+            }
+            Function::HeaderCount => i64::try_from(record.headers.len())
+                .ok()
+                .map(FunctionValue::Number),
+        }
+    }
+}
+
+fn as_string(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(s) => Some(s.clone()),
+        value => Some(value.to_string()),
+    }
+}
+
+impl Display for Function {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Function::Lower(field) => write!(f, "lower({})", field_name(field)),
+            Function::Upper(field) => write!(f, "upper({})", field_name(field)),
+            Function::Length(field) => write!(f, "length({})", field_name(field)),
+            Function::JsonType(field) => write!(f, "json_type({})", field_name(field)),
+            Function::HeaderCount => write!(f, "header_count()"),
+        }
+    }
+}
+
+/// Number-like fields can't be passed to string functions.
+fn is_textual(field: &Symbol) -> bool {
+    matches!(
+        field,
+        Symbol::Key | Symbol::Topic | Symbol::Value(_) | Symbol::Header(_)
+    )
+}
+
+fn argument(input: &str) -> IResult<&str, Symbol> {
+    delimited(wsi(char('(')), parse_field, wsi(char(')'))).parse(input)
+}
+
+fn textual_argument(input: &str) -> IResult<&str, Symbol> {
+    verify(argument, is_textual).parse(input)
+}
+
+pub(crate) fn parse_function(input: &str) -> IResult<&str, Function> {
+    alt((
+        map(
+            preceded(wsi(tag_no_case("lower")), textual_argument),
+            Function::Lower,
+        ),
+        map(
+            preceded(wsi(tag_no_case("upper")), textual_argument),
+            Function::Upper,
+        ),
+        map(
+            preceded(
+                wsi(alt((tag_no_case("length"), tag_no_case("len")))),
+                textual_argument,
+            ),
+            Function::Length,
+        ),
+        map(
+            preceded(wsi(tag_no_case("json_type")), argument),
+            Function::JsonType,
+        ),
+        map(
+            preceded(
+                wsi(tag_no_case("header_count")),
+                pair(wsi(char('(')), wsi(char(')'))),
+            ),
+            |_| Function::HeaderCount,
+        ),
+    ))
+    .parse(input)
+}
+
+#[test]
+fn test_parse_function() {
+    assert_eq!(
+        parse_function("lower(value.email)"),
+        Ok((
+            "",
+            Function::Lower(Symbol::Value(Some(".email".to_string())))
+        ))
+    );
+    assert_eq!(
+        parse_function("len( key )"),
+        Ok(("", Function::Length(Symbol::Key)))
+    );
+    assert_eq!(
+        parse_function("header_count()"),
+        Ok(("", Function::HeaderCount))
+    );
+    assert!(parse_function("length(offset)").is_err());
+    assert!(parse_function("lower()").is_err());
+}
+
+#[test]
+fn test_evaluate_function() {
+    use crate::DataType;
+    use serde_json::json;
+
+    let record = KafkaRecord {
+        key: DataType::String("My-Key".to_string()),
+        value: DataType::Json(
+            json!({"email": "John@Example.com", "items": [1, 2, 3], "amount": "12.5"}),
+        ),
+        headers: [("a".to_string(), "1".to_string())].into(),
+        ..Default::default()
+    };
+    let evaluate = |input: &str| parse_function(input).unwrap().1.evaluate(&record);
+    assert_eq!(
+        evaluate("lower(value.email)"),
+        Some(FunctionValue::String("john@example.com".to_string()))
+    );
+    assert_eq!(
+        evaluate("upper(key)"),
+        Some(FunctionValue::String("MY-KEY".to_string()))
+    );
+    assert_eq!(
+        evaluate("length(value.items)"),
+        Some(FunctionValue::Number(3))
+    );
+    assert_eq!(evaluate("len(key)"), Some(FunctionValue::Number(6)));
+    assert_eq!(
+        evaluate("json_type(value.amount)"),
+        Some(FunctionValue::String("string".to_string()))
+    );
+    assert_eq!(
+        evaluate("json_type(value.missing)"),
+        Some(FunctionValue::String("null".to_string()))
+    );
+    assert_eq!(evaluate("header_count()"), Some(FunctionValue::Number(1)));
+    assert_eq!(evaluate("lower(value.missing)"), None);
+}
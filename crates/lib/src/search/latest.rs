@@ -0,0 +1,169 @@
+//! The `latest by key` clause keeps only the newest kafka record of each key.
+//! It is useful for compacted topics: the result is the state a consumer would end up with
+//! after consuming the topic from scratch. Keys whose latest record is a tombstone are dropped.
+//!
+//! ```sql
+//! from begin latest by key
+//! ```
+use std::collections::HashMap;
+
+use nom::{IResult, Parser, bytes::complete::tag_no_case, combinator::value};
+
+use crate::{DataType, KafkaRecord};
+
+use super::wsi::wsi;
+
+/// Keyed view of the kafka records, only the newest record of each key is kept.
+/// Every consumed record must be added, whether it matches the search query or not:
+/// a tombstone or a newer record that doesn't match hides the older records of its key.
+#[derive(Debug, Clone, Default)]
+pub struct LatestByKey {
+    records: HashMap<(String, DataType), Latest>,
+}
+
+/// The newest record of a key and whether it matches the search query.
+#[derive(Debug, Clone)]
+struct Latest {
+    record: KafkaRecord,
+    matches: bool,
+}
+
+impl LatestByKey {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of keys whose newest record matches the search query.
+    pub fn len(&self) -> usize {
+        self.records.values().filter(|l| l.matches).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Adds a kafka record, replacing the current record of the key if the new one is more recent.
+    /// `matches` tells whether the record matches the search query. A tombstone never matches, it removes the key.
+    pub fn add(&mut self, mut record: KafkaRecord, matches: bool) {
+        let id = (record.topic.clone(), record.key.clone());
+        if let Some(current) = self.records.get(&id) {
+            if !Self::is_newer(&record, &current.record) {
+                return;
+            }
+        }
+        let matches = matches && !record.tombstone;
+        if !matches {
+            // Only the position of the record is needed to compare it with the next records of the key
+            record.value = DataType::default();
+            record.value_as_string.clear();
+            record.headers.clear();
+        }
+        self.records.insert(id, Latest { record, matches });
+    }
+
+    /// Within a partition, offsets tell which record is the newest. Otherwise, timestamps do.
+    fn is_newer(record: &KafkaRecord, current: &KafkaRecord) -> bool {
+        match record.partition == current.partition {
+            true => record.offset > current.offset,
+            false => record.timestamp >= current.timestamp,
+        }
+    }
+
+    /// Returns the records matching the search query, sorted by timestamp.
+    pub fn records(&self) -> Vec<&KafkaRecord> {
+        let mut records = self
+            .records
+            .values()
+            .filter(|l| l.matches)
+            .map(|l| &l.record)
+            .collect::<Vec<_>>();
+        records.sort_by_key(|r| (r.timestamp, r.partition, r.offset));
+        records
+    }
+
+    /// Returns the records matching the search query, sorted by timestamp.
+    pub fn into_records(self) -> Vec<KafkaRecord> {
+        let mut records = self
+            .records
+            .into_values()
+            .filter(|l| l.matches)
+            .map(|l| l.record)
+            .collect::<Vec<_>>();
+        records.sort_by_key(|r| (r.timestamp, r.partition, r.offset));
+        records
+    }
+}
+
+pub(crate) fn parse_latest_by_key(input: &str) -> IResult<&str, ()> {
+    value(
+        (),
+        (
+            wsi(tag_no_case("latest")),
+            wsi(tag_no_case("by")),
+            wsi(tag_no_case("key")),
+        ),
+    )
+    .parse(input)
+}
+
+#[test]
+fn test_parse_latest_by_key() {
+    assert_eq!(parse_latest_by_key("latest by key"), Ok(("", ())));
+    assert_eq!(parse_latest_by_key(" LATEST  BY KEY"), Ok(("", ())));
+    assert!(parse_latest_by_key("latest by value").is_err());
+}
+
+#[test]
+fn test_latest_by_key() {
+    let record = |key: &str, offset: i64, value: &str, tombstone: bool| KafkaRecord {
+        topic: "customers".to_string(),
+        offset,
+        timestamp: Some(offset),
+        key: DataType::String(key.to_string()),
+        value: DataType::String(value.to_string()),
+        tombstone,
+        ..Default::default()
+    };
+    let mut latest = LatestByKey::new();
+    latest.add(record("alice", 1, "v1", false), true);
+    latest.add(record("bob", 2, "v1", false), true);
+    latest.add(record("alice", 3, "v2", false), true);
+    latest.add(record("carol", 4, "v1", false), true);
+    latest.add(record("bob", 5, "", true), false);
+    latest.add(record("alice", 0, "v0", false), true);
+
+    assert_eq!(latest.len(), 2);
+    assert_eq!(
+        latest.into_records(),
+        vec![
+            record("alice", 3, "v2", false),
+            record("carol", 4, "v1", false)
+        ]
+    );
+}
+
+#[test]
+fn test_latest_by_key_with_records_not_matching() {
+    let record = |key: &str, partition: i32, offset: i64, tombstone: bool| KafkaRecord {
+        topic: "customers".to_string(),
+        partition,
+        offset,
+        timestamp: Some(offset),
+        key: DataType::String(key.to_string()),
+        tombstone,
+        ..Default::default()
+    };
+    let mut latest = LatestByKey::new();
+    latest.add(record("alice", 0, 1, false), true);
+    latest.add(record("bob", 0, 2, false), true);
+    latest.add(record("carol", 0, 3, false), true);
+    // A tombstone deletes a key that matched
+    latest.add(record("alice", 0, 4, true), false);
+    // The newest version of bob doesn't match anymore
+    latest.add(record("bob", 0, 5, false), false);
+    // An older version of alice, from another partition, doesn't bring the key back
+    latest.add(record("alice", 1, 0, false), true);
+
+    assert_eq!(latest.len(), 1);
+    assert_eq!(latest.into_records(), vec![record("carol", 0, 3, false)]);
+}
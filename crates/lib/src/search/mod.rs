@@ -3,20 +3,25 @@
 //!
 //! ```bnf
 //! search-query      ::= clause+
-//! clause            ::= or-expression | limit-clause | from-clause | order-clause
+//! clause            ::= or-expression | limit-clause | from-clause | order-clause | select-clause | aggregation-clause | latest-clause | sample-clause | transform-clause
 //! or-expression     ::= And-expression | and-expression 'or' and-expression
 //! and-expression    ::= atom | atom 'and' atom
 //! term              ::= atom | '!' atom
-//! atom              ::= comparison  | filter | '(' expression ')'
+//! atom              ::= comparison  | filter | saved-query | '(' expression ')'
+//! saved-query       ::= '@' [a-zA-Z0-9_-]+
 //! number-symbol     ::= 'offset' | 'partition' | 'size'
 //! string-symbol     ::= 'topic' | 'key' | 'timestamp' | 'value'
 //! symbol            ::= number-symbol | string-symbol
-//! comparison        ::= number-comparison | string-comparison | time-comparison
+//! comparison        ::= number-comparison | string-comparison | time-comparison | function-comparison
 //! number-comparison ::= number-symbol number-operator number
 //! string-comparison ::= string-symbol string-operator string
-//! time-comparison   ::= 'between' string 'and' string
+//! time-comparison   ::= 'timestamp' number-operator timestamp | 'timestamp between' timestamp 'and' timestamp
+//! timestamp         ::= (string time-zone? | 'now') (('+' | '-') duration)*
+//! duration          ::= (number ('ms' | 's' | 'm' | 'h' | 'd' | 'w'))+
 //! number-operator   ::=  '==' | '!=' | '>' | '<' | '>=' | '<='
 //! string-operator   ::= 'starts with' | '==' | '!=' | '=~' | 'contains' | 'contain' | 'includes' | 'include'
+//! function          ::= 'lower(' field ')' | 'upper(' field ')' | ('length' | 'len') '(' field ')' | 'json_type(' field ')' | 'header_count()'
+//! function-comparison ::= function string-operator string | function number-operator number
 //! filter            ::= .+ '('filter-parameters')'
 //! filter-parameter  ::= string | number
 //! filter-parameters ::= filter-parameter  (',' filter-parameter)*
@@ -24,12 +29,20 @@
 //! order-clause      ::= 'order by' symbol order-keyword
 //! order-keyword     ::= 'asc' | 'desc'
 //! from-clause       ::= 'from' offset
-//! offset            ::= 'beginning' | 'begin' | 'end' | 'end' '-' number | string | number
+//! select-clause     ::= 'select' field (',' field)*
+//! field             ::= symbol | 'value' json-path | 'headers.' header-name
+//! aggregation-clause ::= 'count' | 'count by' field | 'group by' field | 'distinct' field | 'min(' field ')' | 'max(' field ')'
+//! latest-clause     ::= 'latest by key'
+//! sample-clause     ::= 'sample' (number '%' | number 'in' number) ('by key' | 'random')?
+//! transform-clause  ::= '|' 'transform' filter
+//! offset            ::= 'beginning' | 'begin' | 'end' | 'end' '-' number | timestamp | number
 //! number            ::= [0-9_]+
-//! string            ::= '"' [^"]+ '"' | "'" [^']+ "'"
+//! string            ::= '"' ([^"\] | '\"' | '\\')* '"' | "'" ([^'\] | "\'" | '\\')* "'"
 //! ```
 //! You can use <https://www.bottlecaps.de/rr/ui> to visualize it.
 
+#[cfg(feature = "native")]
+pub mod aggregation;
 #[cfg(feature = "native")]
 pub mod atom;
 #[cfg(feature = "native")]
@@ -39,14 +52,26 @@ pub mod expression;
 #[cfg(feature = "native")]
 pub mod filter;
 #[cfg(feature = "native")]
+pub mod function;
+#[cfg(feature = "native")]
+pub mod latest;
+#[cfg(feature = "native")]
 pub mod number;
 #[cfg(feature = "native")]
 pub mod offset;
 #[cfg(feature = "native")]
 pub mod order;
 #[cfg(feature = "native")]
+pub mod parse_error;
+#[cfg(feature = "native")]
+pub mod sample;
+#[cfg(feature = "native")]
+pub mod saved_query;
+#[cfg(feature = "native")]
 pub mod search_query;
 #[cfg(feature = "native")]
+pub mod select;
+#[cfg(feature = "native")]
 pub mod string;
 #[cfg(feature = "native")]
 pub mod symbol;
@@ -55,6 +80,8 @@ pub mod term;
 #[cfg(feature = "native")]
 pub mod timestamp;
 #[cfg(feature = "native")]
+pub mod transform;
+#[cfg(feature = "native")]
 pub mod wsi;
 
 pub mod compare;
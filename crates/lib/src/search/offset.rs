@@ -1,3 +1,5 @@
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
 use nom::{
@@ -17,7 +19,8 @@ use super::{
 };
 
 /// A kafka offset.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
 pub enum FromOffset {
     /// Start consuming from the beginning of the partition.
     Beginning,
@@ -38,7 +41,13 @@ impl Display for FromOffset {
             FromOffset::End => write!(f, "end"),
             FromOffset::Offset(o) => write!(f, "{o}"),
             FromOffset::OffsetTail(o) => write!(f, "end - {o}"),
-            FromOffset::Timestamp(_) => write!(f, ""),
+            FromOffset::Timestamp(t) => write!(
+                f,
+                r#""{}""#,
+                DateTime::from_timestamp_millis(*t)
+                    .unwrap_or_default()
+                    .to_rfc3339_opts(chrono::SecondsFormat::Millis, false)
+            ),
         }
     }
 }
@@ -76,3 +85,27 @@ pub(crate) fn parse_from_offset(input: &str) -> IResult<&str, FromOffset> {
     )
     .parse(input)
 }
+
+#[test]
+fn test_from_offset_round_trip() {
+    for input in [
+        "from beginning",
+        "from end",
+        "from 42",
+        "from end - 10",
+        "from now - 2h",
+        r#"from "2024-11-23T12:00:00.000+01:00""#,
+    ] {
+        let (_, offset) = parse_from_offset(input).unwrap();
+        let normalized = format!("from {offset}");
+        assert_eq!(
+            parse_from_offset(&normalized),
+            Ok(("", offset)),
+            "{normalized}"
+        );
+    }
+    assert_eq!(
+        FromOffset::Timestamp(1_732_359_600_000).to_string(),
+        r#""2024-11-23T11:00:00.000+00:00""#
+    );
+}
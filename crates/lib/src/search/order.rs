@@ -1,5 +1,6 @@
 use nom::Parser;
 use nom::{IResult, branch::alt, bytes::complete::tag, combinator::value};
+use serde::{Deserialize, Serialize};
 
 use super::symbol::{
     Symbol, parse_key, parse_offset, parse_partition, parse_size, parse_timestamp_symbol,
@@ -14,13 +15,15 @@ use super::wsi::wsi;
 /// order by key desc
 /// sort by partition asc
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
 pub struct OrderBy {
     pub order: Order,
     pub keyword: OrderKeyword,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
 pub enum OrderKeyword {
     Desc,
     #[default]
@@ -28,7 +31,8 @@ pub enum OrderKeyword {
 }
 
 /// You can order kafka records by the following fields.
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
 pub enum Order {
     #[default]
     Timestamp,
@@ -0,0 +1,425 @@
+//! Human-friendly errors for search queries that cannot be parsed.
+//!
+//! The parser only tells where the first clause it could not parse starts.
+//! This module looks at the tokens of that clause to find the culprit,
+//! tell what was expected and suggest a fix:
+//!
+//! ```text
+//! Cannot parse the search query at column 5: expected an operator such as `==`, `!=`, `contains` or `starts with`
+//!   key starts_with "foo"
+//!       ^^^^^^^^^^^
+//!   help: did you mean `starts with`?
+//! ```
+use std::{fmt::Display, ops::Range};
+
+/// Keywords, symbols, operators and functions of the query language, used to suggest fixes for typos.
+const VOCABULARY: [&str; 47] = [
+    "from",
+    "begin",
+    "beginning",
+    "end",
+    "limit",
+    "order by",
+    "sort by",
+    "asc",
+    "desc",
+    "where",
+    "and",
+    "or",
+    "not",
+    "contains",
+    "starts with",
+    "start with",
+    "select",
+    "count",
+    "count by",
+    "group by",
+    "distinct",
+    "min",
+    "max",
+    "latest by key",
+    "transform",
+    "sample",
+    "random",
+    "between",
+    "now",
+    "key",
+    "value",
+    "topic",
+    "partition",
+    "offset",
+    "timestamp",
+    "offsetTail",
+    "size",
+    "headers",
+    "lower",
+    "upper",
+    "length",
+    "len",
+    "json_type",
+    "header_count",
+    "include",
+    "includes",
+    "contain",
+];
+
+const STRING_SYMBOLS: [&str; 6] = ["key", "k", "topic", "t", "value", "v"];
+const NUMBER_SYMBOLS: [&str; 9] = [
+    "offset",
+    "o",
+    "partition",
+    "p",
+    "size",
+    "si",
+    "timestamp",
+    "ts",
+    "offsetTail",
+];
+const STRING_OPERATORS: [&str; 9] = [
+    "==", "=", "!=", "=~", "~=", "contains", "contain", "include", "includes",
+];
+const NUMBER_OPERATORS: [&str; 4] = [">", ">=", "<", "<="];
+
+/// A search query that cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The search query
+    pub query: String,
+    /// Columns, in characters, of the invalid part of the query
+    pub range: Range<usize>,
+    /// What the parser expected instead
+    pub expected: String,
+    /// A possible fix
+    pub suggestion: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    StringSymbol,
+    NumberSymbol,
+    StringOperator,
+    NumberOperator,
+    From,
+    Limit,
+    Known,
+    Unknown,
+}
+
+impl ParseError {
+    /// Creates an error given the query and the remaining input the parser could not consume.
+    pub fn new(query: &str, remaining: &str) -> Self {
+        let start = query.len().saturating_sub(remaining.len());
+        let tokens = tokenize(query, start);
+
+        let mut previous = TokenKind::Known;
+        for (range, token) in &tokens {
+            let kind = kind_of(token);
+            let invalid = match kind {
+                TokenKind::Unknown => true,
+                // A symbol must be followed by an operator
+                _ => {
+                    matches!(previous, TokenKind::StringSymbol | TokenKind::NumberSymbol)
+                        && !matches!(kind, TokenKind::StringOperator | TokenKind::NumberOperator)
+                }
+            };
+            if invalid {
+                return Self::build(query, range.clone(), previous, token);
+            }
+            previous = kind;
+        }
+
+        let range = match tokens.first() {
+            Some((range, _)) => range.clone(),
+            None => query.len()..query.len(),
+        };
+        Self::build(query, range, TokenKind::Known, "")
+    }
+
+    fn build(query: &str, range: Range<usize>, previous: TokenKind, token: &str) -> Self {
+        let expected = match previous {
+            TokenKind::StringSymbol => {
+                "an operator such as `==`, `!=`, `contains` or `starts with`"
+            }
+            TokenKind::NumberSymbol => "an operator such as `==`, `!=`, `>`, `>=`, `<` or `<=`",
+            TokenKind::StringOperator => "a quoted string such as \"my-key\"",
+            TokenKind::NumberOperator => "a number",
+            TokenKind::From => "an offset: `begin`, `end`, `end - 10`, a number or a timestamp",
+            TokenKind::Limit => "a number",
+            TokenKind::Known | TokenKind::Unknown => match token.is_empty() {
+                true => "a clause: an expression, `from`, `limit`, `order by`, `select`...",
+                false => "an expression, `from`, `limit`, `order by`, `select`...",
+            },
+        };
+        let suggestion = match previous {
+            TokenKind::StringOperator if !token.is_empty() && !is_quoted(token) => {
+                Some(format!("\"{token}\""))
+            }
+            _ => closest_keyword(token).map(str::to_string),
+        };
+
+        Self {
+            query: query.to_string(),
+            range: query[..range.start].chars().count()..query[..range.end].chars().count(),
+            expected: expected.to_string(),
+            suggestion,
+        }
+    }
+
+    /// A one-line summary of the error.
+    pub fn message(&self) -> String {
+        let mut message = format!(
+            "Cannot parse the search query at column {}: expected {}",
+            self.range.start + 1,
+            self.expected
+        );
+        if let Some(suggestion) = &self.suggestion {
+            message.push_str(&format!(". Did you mean `{suggestion}`?"));
+        }
+        message
+    }
+
+    /// A line with carets under the invalid part of the query.
+    pub fn caret(&self) -> String {
+        format!(
+            "{}{}",
+            " ".repeat(self.range.start),
+            "^".repeat(self.range.len().max(1))
+        )
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Cannot parse the search query at column {}: expected {}",
+            self.range.start + 1,
+            self.expected
+        )?;
+        writeln!(f, "  {}", self.query.replace('\n', " "))?;
+        write!(f, "  {}", self.caret())?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, "\n  help: did you mean `{suggestion}`?")?;
+        }
+        Ok(())
+    }
+}
+
+/// Splits the query, from the given byte offset, into tokens. Quoted strings are single tokens.
+fn tokenize(query: &str, start: usize) -> Vec<(Range<usize>, &str)> {
+    let mut tokens = vec![];
+    let mut chars = query[start..].char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        let mut end = index + c.len_utf8();
+        match c {
+            '"' | '\'' => {
+                // A backslash escapes the quote
+                let mut escaped = false;
+                for (i, next) in chars.by_ref() {
+                    end = i + next.len_utf8();
+                    if next == c && !escaped {
+                        break;
+                    }
+                    escaped = next == '\\' && !escaped;
+                }
+            }
+            '(' | ')' => (),
+            _ => {
+                while let Some((i, next)) = chars.peek() {
+                    if next.is_whitespace() || *next == ')' {
+                        break;
+                    }
+                    // A function call or a search filter is a single token
+                    if *next == '(' {
+                        for (i, next) in chars.by_ref() {
+                            end = i + next.len_utf8();
+                            if next == ')' {
+                                break;
+                            }
+                        }
+                        break;
+                    }
+                    end = i + next.len_utf8();
+                    chars.next();
+                }
+            }
+        }
+        let range = start + index..start + end;
+        tokens.push((range.clone(), &query[range]));
+    }
+    tokens
+}
+
+fn is_quoted(token: &str) -> bool {
+    token.len() >= 2
+        && (token.starts_with('"') && token.ends_with('"')
+            || token.starts_with('\'') && token.ends_with('\''))
+}
+
+fn kind_of(token: &str) -> TokenKind {
+    let lowercase = token.to_lowercase();
+    if STRING_SYMBOLS.contains(&token)
+        || ["value.", "v.", "headers.", "h."]
+            .iter()
+            .any(|prefix| token.starts_with(prefix))
+    {
+        return TokenKind::StringSymbol;
+    }
+    if NUMBER_SYMBOLS.contains(&token) {
+        return TokenKind::NumberSymbol;
+    }
+    if STRING_OPERATORS.contains(&lowercase.as_str())
+        || ["starts", "start"].contains(&lowercase.as_str())
+    {
+        return TokenKind::StringOperator;
+    }
+    if NUMBER_OPERATORS.contains(&token) {
+        return TokenKind::NumberOperator;
+    }
+    match lowercase.as_str() {
+        "from" => return TokenKind::From,
+        "limit" => return TokenKind::Limit,
+        _ => (),
+    }
+    let known = is_quoted(token)
+        || token.starts_with('@')
+        || token.contains('(')
+        || token
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == '_' || c == '%')
+        || ["(", ")", "-", "+", "!", "||", "&&", ",", "with"].contains(&token)
+        || VOCABULARY.contains(&lowercase.as_str());
+    match known {
+        true => TokenKind::Known,
+        false => TokenKind::Unknown,
+    }
+}
+
+/// Returns the keyword that looks the most like the token, if any.
+fn closest_keyword(token: &str) -> Option<&'static str> {
+    let token = token.to_lowercase().replace(['_', '-'], "");
+    if token.len() < 2 {
+        return None;
+    }
+    let threshold = (token.len() / 4).max(1);
+    VOCABULARY
+        .iter()
+        .map(|keyword| {
+            (
+                keyword,
+                levenshtein(&token, &keyword.replace([' ', '_'], "")),
+            )
+        })
+        .filter(|(_, distance)| *distance <= threshold)
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(keyword, _)| *keyword)
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            current.push(
+                (previous[j] + cost)
+                    .min(previous[j + 1] + 1)
+                    .min(current[j] + 1),
+            );
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[test]
+fn test_parse_error_suggests_operator() {
+    let query = r#"key starts_with "foo""#;
+    let error = ParseError::new(query, query);
+    assert_eq!(error.range, 4..15);
+    assert_eq!(error.suggestion.as_deref(), Some("starts with"));
+    assert_eq!(
+        error.to_string(),
+        r#"Cannot parse the search query at column 5: expected an operator such as `==`, `!=`, `contains` or `starts with`
+  key starts_with "foo"
+      ^^^^^^^^^^^
+  help: did you mean `starts with`?"#
+    );
+}
+
+#[test]
+fn test_parse_error_suggests_quotes() {
+    let query = "from begin key == my-key";
+    let error = ParseError::new(query, "key == my-key");
+    assert_eq!(error.range, 18..24);
+    assert_eq!(error.expected, "a quoted string such as \"my-key\"");
+    assert_eq!(error.suggestion.as_deref(), Some("\"my-key\""));
+}
+
+#[test]
+fn test_parse_error_suggests_keyword() {
+    let query = "from begin limt 10";
+    let error = ParseError::new(query, "limt 10");
+    assert_eq!(error.range, 11..15);
+    assert_eq!(error.suggestion.as_deref(), Some("limit"));
+    assert_eq!(error.caret(), "           ^^^^");
+}
+
+#[test]
+fn test_parse_error_at_the_end() {
+    let query = "from begin key ==";
+    let error = ParseError::new(query, "key ==");
+    assert_eq!(error.range, 11..14);
+    assert_eq!(error.suggestion, None);
+}
+
+/// Every keyword of the grammar must be known, otherwise a valid query could be reported as a typo.
+/// Keywords are read from the `tag` and `tag_no_case` parsers of the search modules, aliases of one or two letters are left out.
+#[test]
+fn test_vocabulary_contains_grammar_keywords() {
+    fn sources(directory: &std::path::Path, files: &mut Vec<std::path::PathBuf>) {
+        for entry in std::fs::read_dir(directory).unwrap().flatten() {
+            let path = entry.path();
+            match path.is_dir() {
+                true => sources(&path, files),
+                false => files.push(path),
+            }
+        }
+    }
+    let mut files = vec![];
+    sources(
+        &std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src/search"),
+        &mut files,
+    );
+    let words = VOCABULARY
+        .iter()
+        .flat_map(|entry| entry.split(' '))
+        .collect::<Vec<_>>();
+    for file in files.iter().filter(|f| !f.ends_with("parse_error.rs")) {
+        let content = std::fs::read_to_string(file).unwrap();
+        for line in content
+            .lines()
+            .filter(|l| !l.trim_start().starts_with("//"))
+        {
+            for (_, rest) in line
+                .match_indices("tag(\"")
+                .chain(line.match_indices("tag_no_case(\""))
+                .map(|(i, m)| (i, &line[i + m.len()..]))
+            {
+                let keyword = rest.split('"').next().unwrap_or_default();
+                if keyword.len() > 2 && keyword.chars().all(|c| c.is_ascii_alphabetic() || c == '_')
+                {
+                    assert!(
+                        words.contains(&keyword),
+                        "'{keyword}' of {} is missing from VOCABULARY",
+                        file.display()
+                    );
+                }
+            }
+        }
+    }
+}
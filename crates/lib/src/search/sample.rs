@@ -0,0 +1,191 @@
+//! The `sample` clause keeps only a fraction of the kafka records, so you can get a feel
+//! for the data of a huge topic without reading all of it.
+//! Records are sampled at random, or deterministically from the hash of their key with `by key`.
+//! The key is hashed with FNV-1a, so a key is sampled the same way on every run.
+//! It must not be murmur2, the hash of Kafka's default partitioner: the sample would then only contain keys of a few partitions.
+//!
+//! ```sql
+//! sample 1%
+//! sample 1 in 1000 by key
+//! ```
+use serde::{Deserialize, Serialize};
+use std::{collections::hash_map::RandomState, hash::BuildHasher, sync::LazyLock};
+
+use nom::{
+    IResult, Parser,
+    branch::alt,
+    bytes::complete::tag_no_case,
+    character::complete::char,
+    combinator::{map, opt, verify},
+    sequence::{pair, preceded, separated_pair, terminated},
+};
+
+use super::{number::parse_number, wsi::wsi};
+
+/// Seed of the random sampling, it changes every time the program starts.
+static RANDOM_STATE: LazyLock<RandomState> = LazyLock::new(RandomState::new);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+pub enum SampleRate {
+    /// `sample 1%`
+    Percent(u64),
+    /// `sample 1 in 1000`
+    OneIn(u64, u64),
+}
+
+impl SampleRate {
+    /// Returns the rate as a fraction `(numerator, denominator)`.
+    fn fraction(&self) -> (u64, u64) {
+        match self {
+            SampleRate::Percent(percent) => (*percent, 100),
+            SampleRate::OneIn(numerator, denominator) => (*numerator, *denominator),
+        }
+    }
+}
+
+impl std::fmt::Display for SampleRate {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SampleRate::Percent(percent) => write!(f, "{percent}%"),
+            SampleRate::OneIn(numerator, denominator) => write!(f, "{numerator} in {denominator}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+pub struct Sample {
+    pub rate: SampleRate,
+    /// When `true`, all the records of a key are either kept or skipped.
+    pub by_key: bool,
+}
+
+impl Sample {
+    pub fn new(rate: SampleRate, by_key: bool) -> Self {
+        Self { rate, by_key }
+    }
+
+    /// Decides whether the kafka record is part of the sample.
+    /// It only needs the raw key so it can be called before the record is deserialized.
+    pub fn keep(&self, key: Option<&[u8]>, partition: i32, offset: i64) -> bool {
+        let hash = match self.by_key {
+            true => fnv1a(key.unwrap_or_default()),
+            false => RANDOM_STATE.hash_one((partition, offset)),
+        };
+        let (numerator, denominator) = self.rate.fraction();
+        hash % denominator < numerator
+    }
+
+    /// Estimates the number of records of the sample, given the total number of records.
+    pub fn estimate(&self, total: i64) -> i64 {
+        let (numerator, denominator) = self.rate.fraction();
+        (i128::from(total) * i128::from(numerator) / i128::from(denominator)) as i64
+    }
+}
+
+/// The 64-bit FNV-1a hash, stable across runs and platforms.
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+impl std::fmt::Display for Sample {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "sample {}", self.rate)?;
+        if self.by_key {
+            write!(f, " by key")?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_sample_rate(input: &str) -> IResult<&str, SampleRate> {
+    verify(
+        alt((
+            map(terminated(wsi(parse_number), wsi(char('%'))), |percent| {
+                SampleRate::Percent(percent)
+            }),
+            map(
+                separated_pair(wsi(parse_number), wsi(tag_no_case("in")), wsi(parse_number)),
+                |(numerator, denominator)| SampleRate::OneIn(numerator, denominator),
+            ),
+        )),
+        |rate: &SampleRate| {
+            let (numerator, denominator) = rate.fraction();
+            denominator > 0 && numerator <= denominator
+        },
+    )
+    .parse(input)
+}
+
+pub(crate) fn parse_sample(input: &str) -> IResult<&str, Sample> {
+    map(
+        preceded(
+            wsi(tag_no_case("sample")),
+            pair(
+                parse_sample_rate,
+                opt(alt((
+                    map(
+                        pair(wsi(tag_no_case("by")), wsi(tag_no_case("key"))),
+                        |_| true,
+                    ),
+                    map(wsi(tag_no_case("random")), |_| false),
+                ))),
+            ),
+        ),
+        |(rate, by_key)| Sample::new(rate, by_key.unwrap_or(false)),
+    )
+    .parse(input)
+}
+
+#[test]
+fn test_parse_sample() {
+    assert_eq!(
+        parse_sample("sample 1%"),
+        Ok(("", Sample::new(SampleRate::Percent(1), false)))
+    );
+    assert_eq!(
+        parse_sample("sample 1 in 1_000 by key"),
+        Ok(("", Sample::new(SampleRate::OneIn(1, 1000), true)))
+    );
+    assert_eq!(
+        parse_sample("sample 5 % random"),
+        Ok(("", Sample::new(SampleRate::Percent(5), false)))
+    );
+    assert!(parse_sample("sample 120%").is_err());
+    assert!(parse_sample("sample 1 in 0").is_err());
+}
+
+#[test]
+fn test_sample() {
+    let sample = Sample::new(SampleRate::OneIn(1, 10), false);
+    let kept = (0..100_000)
+        .filter(|offset| sample.keep(None, 0, *offset))
+        .count();
+    assert!((9_000..11_000).contains(&kept));
+    assert_eq!(sample.estimate(100_000), 10_000);
+
+    let sample = Sample::new(SampleRate::Percent(50), true);
+    let key = b"my-key".as_slice();
+    let kept = sample.keep(Some(key), 0, 0);
+    assert!(
+        (1..100).all(|offset: i64| sample.keep(Some(key), (offset % 3) as i32, offset) == kept)
+    );
+    assert_eq!(sample.to_string(), "sample 50% by key");
+
+    // The hash of a key doesn't change between runs: fnv1a("foobar") % 100 == 68
+    let key = Some(b"foobar".as_slice());
+    assert!(Sample::new(SampleRate::Percent(69), true).keep(key, 0, 0));
+    assert!(!Sample::new(SampleRate::Percent(68), true).keep(key, 0, 0));
+
+    // Sampled keys are spread over all the partitions
+    let sample = Sample::new(SampleRate::OneIn(1, 10), true);
+    let partitions = (0..1000)
+        .map(|i| format!("key-{i}"))
+        .filter(|key| sample.keep(Some(key.as_bytes()), 0, 0))
+        .map(|key| crate::kafka::partitioner::default_partition(key.as_bytes(), 10))
+        .collect::<std::collections::HashSet<_>>();
+    assert_eq!(partitions.len(), 10);
+}
@@ -0,0 +1,275 @@
+//! Saved queries are named search queries defined in the configuration.
+//! They are referenced with `@` and can be combined with other clauses:
+//!
+//! ```sql
+//! @failed-payments and partition == 2 limit 10
+//! ```
+//!
+//! The expression of the saved query replaces the reference, between parentheses.
+//! Its other clauses (`from`, `limit`, `select`...) apply unless the query defines them as well.
+use nom::{
+    IResult, Parser,
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{alphanumeric1, one_of},
+    combinator::{map, recognize},
+    multi::many1,
+    sequence::preceded,
+};
+
+use crate::error::SearchError;
+
+use super::{
+    SearchQuery,
+    atom::Atom,
+    expression::{AndExpression, Expression, OrExpression},
+    term::Term,
+};
+
+/// Parses a reference to a saved query, such as `@failed-payments`.
+pub(crate) fn parse_saved_query(input: &str) -> IResult<&str, String> {
+    map(
+        preceded(
+            tag("@"),
+            recognize(many1(alt((alphanumeric1, recognize(one_of("_-")))))),
+        ),
+        |name: &str| name.to_string(),
+    )
+    .parse(input)
+}
+
+impl SearchQuery {
+    /// Returns the names of the saved queries referenced by the search query.
+    pub fn saved_queries(&self) -> Vec<String> {
+        let mut names = vec![];
+        visit_expression(&self.expression, &mut |name| names.push(name.to_string()));
+        names
+    }
+
+    /// Combines the expression of the search query with a reference to the saved query.
+    pub fn and_saved_query(self, name: &str) -> SearchQuery {
+        let saved_query = Term::Atom(Atom::SavedQuery(name.to_string()));
+        let expression = match self.expression.is_empty() {
+            true => AndExpression::AndTerm(saved_query),
+            false => AndExpression::AndExpression(vec![
+                saved_query,
+                Term::Atom(Atom::Parenthesis(Box::new(self.expression))),
+            ]),
+        };
+        SearchQuery {
+            expression: OrExpression::OrTerm(expression),
+            ..self
+        }
+    }
+
+    /// Replaces references to saved queries with their definition.
+    /// `lookup` returns the search query of a given saved query name.
+    pub fn expand<F>(self, lookup: &F) -> Result<SearchQuery, SearchError>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        self.expand_with_stack(lookup, &mut vec![])
+    }
+
+    fn expand_with_stack<F>(
+        self,
+        lookup: &F,
+        stack: &mut Vec<String>,
+    ) -> Result<SearchQuery, SearchError>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut saved_queries = vec![];
+        let mut resolve = |name: &str| -> Result<Expression, SearchError> {
+            if stack.iter().any(|n| n == name) {
+                return Err(SearchError::SavedQuery(format!(
+                    "Saved query '@{name}' references itself: @{} -> @{name}",
+                    stack.join(" -> @")
+                )));
+            }
+            let Some(input) = lookup(name) else {
+                return Err(SearchError::SavedQuery(format!(
+                    "Unknown saved query '@{name}'"
+                )));
+            };
+            let (_, query) = SearchQuery::parse(&input)?;
+            stack.push(name.to_string());
+            let query = query.expand_with_stack(lookup, stack)?;
+            stack.pop();
+            let expression = query.expression.clone();
+            saved_queries.push(query);
+            Ok(expression)
+        };
+
+        let mut query = SearchQuery {
+            expression: expand_expression(self.expression, &mut resolve)?,
+            ..self
+        };
+        for saved in saved_queries {
+            query.inherit(saved);
+        }
+        Ok(query)
+    }
+
+    /// Takes the clauses of the saved query this query doesn't define.
+    fn inherit(&mut self, saved: SearchQuery) {
+        self.from = self.from.take().or(saved.from);
+        self.limit = self.limit.or(saved.limit);
+        self.select = self.select.take().or(saved.select);
+        self.sample = self.sample.take().or(saved.sample);
+        self.latest_by_key |= saved.latest_by_key;
+        if self.aggregations.is_empty() {
+            self.aggregations = saved.aggregations;
+        }
+        if self.transforms.is_empty() {
+            self.transforms = saved.transforms;
+        }
+        if self.order_by == SearchQuery::default().order_by {
+            self.order_by = saved.order_by;
+        }
+    }
+}
+
+fn visit_expression(expression: &Expression, visit: &mut impl FnMut(&str)) {
+    let and_expressions = match expression {
+        OrExpression::OrTerm(e) => std::slice::from_ref(e),
+        OrExpression::OrExpression(e) => e.as_slice(),
+    };
+    for and_expression in and_expressions {
+        let terms = match and_expression {
+            AndExpression::AndTerm(t) => std::slice::from_ref(t),
+            AndExpression::AndExpression(t) => t.as_slice(),
+        };
+        for term in terms {
+            match term {
+                Term::Not(atom) | Term::Atom(atom) => match atom {
+                    Atom::SavedQuery(name) => visit(name),
+                    Atom::Parenthesis(e) => visit_expression(e, visit),
+                    _ => (),
+                },
+            }
+        }
+    }
+}
+
+fn expand_expression<F>(expression: Expression, resolve: &mut F) -> Result<Expression, SearchError>
+where
+    F: FnMut(&str) -> Result<Expression, SearchError>,
+{
+    Ok(match expression {
+        OrExpression::OrTerm(e) => OrExpression::OrTerm(expand_and_expression(e, resolve)?),
+        OrExpression::OrExpression(e) => OrExpression::OrExpression(
+            e.into_iter()
+                .map(|e| expand_and_expression(e, resolve))
+                .collect::<Result<_, _>>()?,
+        ),
+    })
+}
+
+fn expand_and_expression<F>(
+    expression: AndExpression,
+    resolve: &mut F,
+) -> Result<AndExpression, SearchError>
+where
+    F: FnMut(&str) -> Result<Expression, SearchError>,
+{
+    Ok(match expression {
+        AndExpression::AndTerm(t) => AndExpression::AndTerm(expand_term(t, resolve)?),
+        AndExpression::AndExpression(t) => AndExpression::AndExpression(
+            t.into_iter()
+                .map(|t| expand_term(t, resolve))
+                .collect::<Result<_, _>>()?,
+        ),
+    })
+}
+
+fn expand_term<F>(term: Term, resolve: &mut F) -> Result<Term, SearchError>
+where
+    F: FnMut(&str) -> Result<Expression, SearchError>,
+{
+    Ok(match term {
+        Term::Not(atom) => Term::Not(expand_atom(atom, resolve)?),
+        Term::Atom(atom) => Term::Atom(expand_atom(atom, resolve)?),
+    })
+}
+
+fn expand_atom<F>(atom: Atom, resolve: &mut F) -> Result<Atom, SearchError>
+where
+    F: FnMut(&str) -> Result<Expression, SearchError>,
+{
+    Ok(match atom {
+        Atom::SavedQuery(name) => Atom::Parenthesis(Box::new(resolve(&name)?)),
+        Atom::Parenthesis(e) => Atom::Parenthesis(Box::new(expand_expression(*e, resolve)?)),
+        atom => atom,
+    })
+}
+
+#[cfg(test)]
+fn lookup(name: &str) -> Option<String> {
+    match name {
+        "failed-payments" => Some(r#"from begin value.status == "FAILED" limit 100"#.to_string()),
+        "eu" => Some(r#"value.region == "eu" or value.region == "uk""#.to_string()),
+        "failed-eu" => Some("@failed-payments and @eu".to_string()),
+        "loop" => Some("@loop2".to_string()),
+        "loop2" => Some("@loop".to_string()),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_parse_saved_query() {
+    let (_, query) = SearchQuery::parse("@failed-payments and partition == 2").unwrap();
+    assert_eq!(query.saved_queries(), vec!["failed-payments"]);
+    assert_eq!(
+        query.to_string(),
+        "@failed-payments && partition == 2 order by timestamp asc"
+    );
+}
+
+#[test]
+fn test_expand_saved_query() {
+    let (_, query) = SearchQuery::parse("@failed-payments and partition == 2 limit 10").unwrap();
+    let query = query.expand(&lookup).unwrap();
+    assert!(query.saved_queries().is_empty());
+    assert_eq!(
+        query.to_string(),
+        r#"from beginning (value.status == "FAILED") && partition == 2 order by timestamp asc limit 10"#
+    );
+}
+
+#[test]
+fn test_and_saved_query() {
+    let (_, query) = SearchQuery::parse("partition == 2 or offset < 10 limit 10").unwrap();
+    assert_eq!(
+        query.and_saved_query("failed-payments").to_string(),
+        "@failed-payments && (partition == 2 || offset < 10) order by timestamp asc limit 10"
+    );
+    let (_, query) = SearchQuery::parse("").unwrap();
+    assert_eq!(
+        query.and_saved_query("failed-payments").to_string(),
+        "@failed-payments order by timestamp asc"
+    );
+}
+
+#[test]
+fn test_expand_nested_saved_queries() {
+    let (_, query) = SearchQuery::parse("@failed-eu").unwrap();
+    assert_eq!(
+        query.expand(&lookup).unwrap().to_string(),
+        r#"from beginning ((value.status == "FAILED") && (value.region == "eu" || value.region == "uk")) order by timestamp asc limit 100"#
+    );
+}
+
+#[test]
+fn test_expand_unknown_or_recursive_saved_query() {
+    let (_, query) = SearchQuery::parse("@unknown").unwrap();
+    assert_eq!(
+        query.expand(&lookup).unwrap_err().to_string(),
+        "Unknown saved query '@unknown'"
+    );
+    let (_, query) = SearchQuery::parse("@loop").unwrap();
+    assert_eq!(
+        query.expand(&lookup).unwrap_err().to_string(),
+        "Saved query '@loop' references itself: @loop -> @loop2 -> @loop"
+    );
+}
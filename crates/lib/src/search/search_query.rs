@@ -5,26 +5,43 @@ use nom::{
     combinator::{eof, map},
     multi::many_till,
 };
+use serde::{Deserialize, Serialize};
 
 use crate::error::SearchError;
 
 use super::{
+    aggregation::Aggregation,
     clause::{
-        SearchClause, parse_expression, parse_from_offset_clause, parse_limit, parse_order_by,
+        SearchClause, parse_aggregation_clause, parse_expression, parse_from_offset_clause,
+        parse_latest_by_key_clause, parse_limit, parse_order_by, parse_sample_clause, parse_select,
+        parse_transform_clause,
     },
     expression::Expression,
+    filter::Filter,
     offset::FromOffset,
     order::{Order, OrderBy, OrderKeyword},
+    parse_error::ParseError,
+    sample::Sample,
+    select::Projection,
     wsi::wsi,
 };
 
-/// A `SearchQuery` is a combination of an expression, a limit, an offset and an order by clause.
-#[derive(Debug, Clone, PartialEq)]
+/// A `SearchQuery` is a combination of an expression, a limit, an offset, an order by, a select clause, aggregations, a `latest by key` clause, a sample and transformers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
 pub struct SearchQuery {
     pub expression: Expression,
     pub limit: Option<usize>,
     pub from: Option<FromOffset>,
     pub order_by: OrderBy,
+    pub select: Option<Projection>,
+    pub aggregations: Vec<Aggregation>,
+    /// Keep only the newest kafka record of each key
+    pub latest_by_key: bool,
+    pub sample: Option<Sample>,
+    /// Transformers rewriting the kafka records before they are matched, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transforms: Vec<Filter>,
     //pub group_by_key: bool,
 }
 
@@ -37,6 +54,11 @@ impl SearchQuery {
         map(
             many_till(
                 alt((
+                    parse_select,
+                    parse_latest_by_key_clause,
+                    parse_sample_clause,
+                    parse_transform_clause,
+                    parse_aggregation_clause,
                     parse_from_offset_clause,
                     parse_limit,
                     parse_expression,
@@ -53,7 +75,13 @@ impl SearchQuery {
                         SearchClause::Expression(u) => s.expression = u,
                         SearchClause::OrderBy(order, k) => {
                             s.order_by = OrderBy::new(order, k.unwrap_or(OrderKeyword::Asc));
-                        } //SearchClause::GroupByKey => s.group_by_key = true,
+                        }
+                        SearchClause::Select(projection) => s.select = Some(projection),
+                        SearchClause::Aggregation(aggregation) => s.aggregations.push(aggregation),
+                        SearchClause::LatestByKey => s.latest_by_key = true,
+                        SearchClause::Sample(sample) => s.sample = Some(sample),
+                        SearchClause::Transform(transform) => s.transforms.push(transform),
+                        //SearchClause::GroupByKey => s.group_by_key = true,
                     }
                 }
                 s
@@ -62,11 +90,11 @@ impl SearchQuery {
         .parse(input)
         .map_err(|e| {
             let remaining = match e {
-                nom::Err::Incomplete(_) => input.to_string(),
-                nom::Err::Error(s) => s.input.to_string(),
-                nom::Err::Failure(s) => s.input.to_string(),
+                nom::Err::Incomplete(_) => input,
+                nom::Err::Error(s) => s.input,
+                nom::Err::Failure(s) => s.input,
             };
-            SearchError::Parse(remaining)
+            SearchError::Parse(ParseError::new(input, remaining))
         })
     }
 }
@@ -83,10 +111,21 @@ impl std::fmt::Display for SearchQuery {
             Some(i) => format!("limit {i}"),
             None => String::new(),
         };
+        if let Some(projection) = &self.select {
+            clauses.push(projection.to_string());
+        }
         clauses.push(from.to_string());
         clauses.push(format!("{}", self.expression));
         clauses.push(format!("{}", self.order_by));
+        if self.latest_by_key {
+            clauses.push("latest by key".to_string());
+        }
+        if let Some(sample) = &self.sample {
+            clauses.push(sample.to_string());
+        }
+        clauses.extend(self.aggregations.iter().map(ToString::to_string));
         clauses.push(limit.to_string());
+        clauses.extend(self.transforms.iter().map(|t| format!("| transform {t}")));
         let clauses = clauses.into_iter().filter(|e| !e.is_empty()).collect_vec();
         write!(f, "{}", clauses.join(" "))
     }
@@ -99,6 +138,11 @@ impl Default for SearchQuery {
             limit: None,
             from: None,
             order_by: OrderBy::new(Order::Timestamp, OrderKeyword::Asc),
+            select: None,
+            aggregations: vec![],
+            latest_by_key: false,
+            sample: None,
+            transforms: vec![],
             //group_by_key: false,
         }
     }
@@ -113,3 +157,79 @@ fn test_parse_search_query() {
 fn test_parse_search_query_with_json_path() {
     assert!(SearchQuery::parse(r#"from end - 10 value.sequenceNum == "115568969""#).is_ok());
 }
+
+#[test]
+fn test_parse_search_query_with_sample() {
+    let query =
+        SearchQuery::parse(r#"from begin sample 1 in 1000 by key value.status == "FAILED""#)
+            .unwrap()
+            .1;
+    assert!(query.sample.is_some_and(|s| s.by_key));
+    assert!(!query.expression.is_empty());
+}
+
+#[test]
+fn test_parse_search_query_with_relative_timestamps() {
+    use super::compare::CompareExpression;
+    use super::{atom::Atom, expression::AndExpression, term::Term};
+
+    let query = SearchQuery::parse("from now - 2h timestamp between now - 1d and now - 23h")
+        .unwrap()
+        .1;
+    assert!(matches!(query.from, Some(FromOffset::Timestamp(_))));
+    let Expression::OrTerm(AndExpression::AndTerm(Term::Atom(Atom::Compare(
+        CompareExpression::TimestampBetween(from, to),
+    )))) = query.expression
+    else {
+        panic!("Expected a timestamp between expression, got {query:?}");
+    };
+    assert_eq!((to - from).num_minutes(), 60);
+}
+
+#[test]
+fn test_serialize_search_query() {
+    let input =
+        r#"from begin (key == "a" || !offset > 10) && lower(value.name) starts with "j" limit 5"#;
+    let query = SearchQuery::parse(input).unwrap().1;
+    let json = serde_json::to_string(&query).unwrap();
+    assert_eq!(serde_json::from_str::<SearchQuery>(&json).unwrap(), query);
+}
+
+#[test]
+fn test_display_search_query_can_be_parsed() {
+    for input in [
+        r#"from begin (key == "a" || !offset > 10) && lower(value.name) starts with "j" limit 5"#,
+        r#"from end - 10 value.user.name contains 'say "hello"' order by offset desc"#,
+        r#"select key, value.name from begin headers.trace-id == "1" sample 1 in 10 by key"#,
+        r#"from begin key_ends_with("1", 2) latest by key"#,
+        r#"from begin count by partition limit 100"#,
+        r#"from begin value.status == "FAILED" | transform decrypt('k1', 2) | transform flatten()"#,
+    ] {
+        let query = SearchQuery::parse(input).unwrap().1;
+        let normalized = query.to_string();
+        assert_eq!(
+            SearchQuery::parse(&normalized).map(|(_, q)| q).ok(),
+            Some(query),
+            "'{normalized}' should be equivalent to '{input}'"
+        );
+    }
+}
+
+#[test]
+fn generate_json_schema_for_search_query() {
+    use schemars::schema_for;
+    let mut schema = schema_for!(SearchQuery);
+    schema.insert("$id".into(), "https://raw.githubusercontent.com/MAIF/yozefu/refs/heads/main/docs/json-schemas/search-query.json".into());
+    std::fs::write(
+        std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .parent()
+            .unwrap()
+            .join("docs")
+            .join("json-schemas")
+            .join("search-query.json"),
+        serde_json::to_string_pretty(&schema).unwrap(),
+    )
+    .unwrap();
+}
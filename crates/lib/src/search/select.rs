@@ -0,0 +1,218 @@
+//! The `select` clause projects kafka records on a list of fields.
+//! It is used by the headless mode to output only the fields you are interested in.
+//!
+//! ```sql
+//! select key, value.customer.id, headers.traceparent, timestamp
+//! ```
+use nom::{
+    IResult, Parser,
+    branch::alt,
+    bytes::complete::{tag, tag_no_case, take_while1},
+    character::complete::{char, one_of, satisfy},
+    combinator::{map, not, opt, recognize},
+    multi::separated_list1,
+    sequence::{preceded, terminated},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{DataType, KafkaRecord};
+
+use super::{
+    symbol::{
+        Symbol, parse_key, parse_offset, parse_partition, parse_size, parse_timestamp_symbol,
+        parse_topic,
+    },
+    wsi::wsi,
+};
+
+/// List of fields selected by the `select` clause.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
+pub struct Projection {
+    pub fields: Vec<Symbol>,
+}
+
+impl Projection {
+    pub fn new(fields: Vec<Symbol>) -> Self {
+        Self { fields }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Returns the name and the value of each selected field of the kafka record.
+    /// A field that doesn't exist in the record is `null`.
+    pub fn project(&self, record: &KafkaRecord) -> Vec<(String, serde_json::Value)> {
+        self.fields
+            .iter()
+            .map(|field| (field_name(field), field_value(field, record)))
+            .collect()
+    }
+}
+
+/// Returns the name of the field as written in the `select` clause.
+pub fn field_name(field: &Symbol) -> String {
+    match field {
+        Symbol::Offset => "offset".to_string(),
+        Symbol::Topic => "topic".to_string(),
+        Symbol::Partition => "partition".to_string(),
+        Symbol::OffsetTail => "offset".to_string(),
+        Symbol::Key => "key".to_string(),
+        Symbol::Size => "size".to_string(),
+        Symbol::Timestamp => "timestamp".to_string(),
+        Symbol::Value(None) => "value".to_string(),
+        Symbol::Value(Some(path)) => format!("value{path}"),
+        Symbol::Header(name) => format!("headers.{name}"),
+    }
+}
+
+pub(crate) fn field_value(field: &Symbol, record: &KafkaRecord) -> serde_json::Value {
+    match field {
+        Symbol::Offset | Symbol::OffsetTail => record.offset.into(),
+        Symbol::Topic => record.topic.clone().into(),
+        Symbol::Partition => record.partition.into(),
+        Symbol::Key => record.key.clone().into(),
+        Symbol::Size => record.size.into(),
+        Symbol::Timestamp => record.timestamp.into(),
+        Symbol::Value(None) => record.value.clone().into(),
+        Symbol::Value(Some(path)) => match &record.value {
+            DataType::Json(value) => value
+                .pointer(&DataType::json_pointer(path))
+                .cloned()
+                .unwrap_or_default(),
+            DataType::String(_) => serde_json::Value::Null,
+        },
+        Symbol::Header(name) => record.headers.get(name).cloned().into(),
+    }
+}
+
+impl std::fmt::Display for Projection {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let fields = self.fields.iter().map(field_name).collect::<Vec<_>>();
+        write!(f, "select {}", fields.join(", "))
+    }
+}
+
+fn parse_path(input: &str) -> IResult<&str, &str> {
+    take_while1(|c: char| !c.is_whitespace() && c != ',' && c != ')').parse(input)
+}
+
+/// A path in the value starts with `.` or `[`: `value.customer.id`, `value[0]`.
+fn parse_value_path(input: &str) -> IResult<&str, &str> {
+    recognize(preceded(one_of(".["), opt(parse_path))).parse(input)
+}
+
+fn parse_value_field(input: &str) -> IResult<&str, Symbol> {
+    map(
+        terminated(
+            preceded(alt((tag("value"), tag("v"))), opt(parse_value_path)),
+            not(satisfy(|c: char| {
+                c.is_alphanumeric() || c == '_' || c == '-'
+            })),
+        ),
+        |path| Symbol::Value(path.map(str::to_string)),
+    )
+    .parse(input)
+}
+
+fn parse_header_field(input: &str) -> IResult<&str, Symbol> {
+    map(
+        preceded(alt((tag("headers."), tag("h."))), wsi(parse_path)),
+        |name| Symbol::Header(name.to_string()),
+    )
+    .parse(input)
+}
+
+pub(crate) fn parse_field(input: &str) -> IResult<&str, Symbol> {
+    wsi(alt((
+        parse_header_field,
+        parse_size,
+        parse_timestamp_symbol,
+        parse_offset,
+        parse_key,
+        parse_value_field,
+        parse_topic,
+        parse_partition,
+    )))
+    .parse(input)
+}
+
+pub(crate) fn parse_projection(input: &str) -> IResult<&str, Projection> {
+    map(
+        preceded(
+            wsi(tag_no_case("select")),
+            separated_list1(wsi(char(',')), parse_field),
+        ),
+        Projection::new,
+    )
+    .parse(input)
+}
+
+#[test]
+fn test_parse_projection() {
+    assert_eq!(
+        parse_projection("select key, value.customer.id, headers.traceparent, timestamp"),
+        Ok((
+            "",
+            Projection::new(vec![
+                Symbol::Key,
+                Symbol::Value(Some(".customer.id".to_string())),
+                Symbol::Header("traceparent".to_string()),
+                Symbol::Timestamp,
+            ])
+        ))
+    );
+    assert_eq!(
+        parse_projection("select value,topic"),
+        Ok((
+            "",
+            Projection::new(vec![Symbol::Value(None), Symbol::Topic])
+        ))
+    );
+    assert_eq!(
+        parse_projection("select v[0], value"),
+        Ok((
+            "",
+            Projection::new(vec![
+                Symbol::Value(Some("[0]".to_string())),
+                Symbol::Value(None)
+            ])
+        ))
+    );
+    assert!(parse_projection("select").is_err());
+    assert!(parse_projection("select valuefoo").is_err());
+    assert!(parse_projection("select vfoo").is_err());
+}
+
+#[test]
+fn test_project() {
+    let record = KafkaRecord {
+        topic: "orders".to_string(),
+        offset: 42,
+        key: DataType::String("my-key".to_string()),
+        value: DataType::Json(serde_json::json!({"customer": {"id": 7}})),
+        headers: [("traceparent".to_string(), "00-abc".to_string())].into(),
+        ..Default::default()
+    };
+    let projection =
+        parse_projection("select key, value.customer.id, headers.traceparent, headers.missing")
+            .unwrap()
+            .1;
+    assert_eq!(
+        projection.project(&record),
+        vec![
+            ("key".to_string(), serde_json::json!("my-key")),
+            ("value.customer.id".to_string(), serde_json::json!(7)),
+            (
+                "headers.traceparent".to_string(),
+                serde_json::json!("00-abc")
+            ),
+            ("headers.missing".to_string(), serde_json::Value::Null),
+        ]
+    );
+    assert_eq!(
+        projection.to_string(),
+        "select key, value.customer.id, headers.traceparent, headers.missing"
+    );
+}
@@ -1,29 +1,136 @@
 use nom::{
     IResult, Parser,
     branch::alt,
-    bytes::complete::{tag, take_until},
-    combinator::map,
-    sequence::delimited,
+    error::{Error, ErrorKind},
 };
 
 /// A string is delimited by single or double quotes.
+/// Backslashes are only special right before the quote delimiting the string:
+/// `\"` is a quote and `\\"` is a backslash followed by the end of the string.
+/// Other backslashes are kept as is, `"C:\temp\\logs"` is `C:\temp\\logs`.
 /// ```text
 /// "this is a string"
 /// 'this a another string'
+/// "both \" and ' in a string"
 /// ```
 pub(crate) fn parse_string(input: &str) -> IResult<&str, String> {
-    map(
-        alt((
-            delimited(tag("\""), take_until("\""), tag("\"")),
-            delimited(tag("'"), take_until("'"), tag("'")),
-        )),
-        |d: &str| d.to_string(),
-    )
-    .parse(input)
+    alt((delimited_by('"'), delimited_by('\''))).parse(input)
+}
+
+fn delimited_by(delimiter: char) -> impl Fn(&str) -> IResult<&str, String> {
+    move |input: &str| {
+        let Some(rest) = input.strip_prefix(delimiter) else {
+            return Err(nom::Err::Error(Error::new(input, ErrorKind::Tag)));
+        };
+        let mut string = String::new();
+        let mut backslashes = 0;
+        for (index, c) in rest.char_indices() {
+            match c {
+                '\\' => backslashes += 1,
+                c if c == delimiter => {
+                    string.extend(std::iter::repeat_n('\\', backslashes / 2));
+                    if backslashes % 2 == 0 {
+                        return Ok((&rest[index + c.len_utf8()..], string));
+                    }
+                    string.push(c);
+                    backslashes = 0;
+                }
+                c => {
+                    string.extend(std::iter::repeat_n('\\', backslashes));
+                    string.push(c);
+                    backslashes = 0;
+                }
+            }
+        }
+        Err(nom::Err::Error(Error::new(input, ErrorKind::TakeUntil)))
+    }
+}
+
+/// Quotes a string so it can be parsed by [`parse_string`].
+pub(crate) fn quote(s: &str) -> String {
+    quote_with(s, '"')
+}
+
+/// Quotes a string with the `preferred` delimiter, unless the string only contains this one.
+pub(crate) fn quote_with(s: &str, preferred: char) -> String {
+    let other = match preferred {
+        '"' => '\'',
+        _ => '"',
+    };
+    let delimiter = match (s.contains(preferred), s.contains(other)) {
+        (true, false) => other,
+        _ => preferred,
+    };
+    let mut quoted = String::from(delimiter);
+    let mut backslashes = 0;
+    for c in s.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            c => {
+                // Backslashes are doubled only when they precede the delimiter
+                let escaped = usize::from(c == delimiter);
+                quoted.extend(std::iter::repeat_n(
+                    '\\',
+                    backslashes * (1 + escaped) + escaped,
+                ));
+                quoted.push(c);
+                backslashes = 0;
+            }
+        }
+    }
+    quoted.extend(std::iter::repeat_n('\\', backslashes * 2));
+    quoted.push(delimiter);
+    quoted
 }
 
 #[test]
 fn test_parse_string() {
     assert_eq!(parse_string(r#"'halo'"#), Ok(("", "halo".to_string())));
     assert_eq!(parse_string(r#""hola""#), Ok(("", "hola".to_string())));
+    assert_eq!(
+        parse_string(r#""say \"hi\" it's" and"#),
+        Ok((" and", r#"say "hi" it's"#.to_string()))
+    );
+    assert_eq!(
+        parse_string(r#"'C:\temp\\'"#),
+        Ok(("", r#"C:\temp\"#.to_string()))
+    );
+    assert!(parse_string(r#""unterminated"#).is_err());
+}
+
+#[test]
+fn test_parse_string_written_before_escapes() {
+    // Backslashes that don't precede the delimiter keep their meaning
+    assert_eq!(parse_string(r#""a\\b""#), Ok(("", r#"a\\b"#.to_string())));
+    assert_eq!(
+        parse_string(r#"'C:\temp\logs'"#),
+        Ok(("", r#"C:\temp\logs"#.to_string()))
+    );
+    assert_eq!(parse_string(r#""\d+""#), Ok(("", r#"\d+"#.to_string())));
+    // A backslash right before the closing quote now escapes it, `"C:\\"` is the string `C:\`
+    assert!(parse_string(r#""C:\""#).is_err());
+    assert_eq!(parse_string(r#""C:\\""#), Ok(("", r#"C:\"#.to_string())));
+}
+
+#[test]
+fn test_quote_round_trip() {
+    for s in [
+        "hola",
+        r#"say "hi""#,
+        "it's",
+        r#"say "hi", it's"#,
+        r#"C:\temp"#,
+        r#"ends with \"#,
+        r#"\" and ' and \\"#,
+        r#"a\\"b"#,
+        r#"a\\b"#,
+        r#"\"#,
+        "",
+    ] {
+        let quoted = quote(s);
+        assert_eq!(parse_string(&quoted), Ok(("", s.to_string())), "{quoted}");
+    }
+    assert_eq!(quote("hola"), r#""hola""#);
+    assert_eq!(quote(r#"say "hi""#), r#"'say "hi"'"#);
+    assert_eq!(quote(r#"say "hi", it's"#), r#""say \"hi\", it's""#);
 }
@@ -8,11 +8,13 @@ use nom::{
     error::ErrorKind,
     sequence::preceded,
 };
+use serde::{Deserialize, Serialize};
 use strum::Display;
 
 use super::wsi::wsi;
 
-#[derive(Debug, Display, PartialEq, Eq, Clone)]
+#[derive(Debug, Display, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
 pub enum Symbol {
     Offset,
     Topic,
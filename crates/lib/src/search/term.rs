@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
 use nom::{
@@ -16,7 +17,8 @@ use super::{
 /// !(offset > 50)
 /// offset > 50
 /// ```
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(schemars::JsonSchema))]
 pub enum Term {
     Not(Atom),
     Atom(Atom),
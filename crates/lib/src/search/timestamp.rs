@@ -1,39 +1,165 @@
-use chrono::{DateTime, Local};
+use std::str::FromStr;
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeDelta, TimeZone};
+use chrono_tz::Tz;
 use fuzzydate::parse;
 use nom::{
     IResult, Parser,
     branch::alt,
-    bytes::complete::tag_no_case,
-    combinator::{map_res, value},
+    bytes::complete::{tag, tag_no_case, take_while1},
+    character::complete::{alpha1, char},
+    combinator::{map, map_opt, map_res, not, opt, peek},
+    multi::{many0, many1},
+    sequence::terminated,
 };
 
-use super::{string::parse_string, wsi::wsi};
+use super::{number::parse_number, string::parse_string, wsi::wsi};
 
 /// Parses a timestamp.
 /// It can be a RFC3339 date time
 /// or a fuzzy date (`3 hours ago`) or the 'now' keyword.
+/// A date without offset can be followed by a time zone.
+/// Durations can be added to or subtracted from the timestamp.
 ///
 /// ```text
 /// "3 hours ago"
 /// '2024-09-19T17:59:25.815+02:00'
+/// '2024-05-01 10:00' Europe/Paris
 /// now
+/// now - 15m
+/// now - 1d + 30m
 /// ```
 pub(crate) fn parse_timestamp(input: &str) -> IResult<&str, DateTime<Local>> {
+    map_opt(
+        (
+            parse_base_timestamp,
+            many0((wsi(alt((char('+'), char('-')))), wsi(parse_duration))),
+        ),
+        |(timestamp, operations)| {
+            operations
+                .into_iter()
+                .try_fold(
+                    timestamp,
+                    |timestamp, (operator, duration)| match operator {
+                        '+' => timestamp.checked_add_signed(duration),
+                        _ => timestamp.checked_sub_signed(duration),
+                    },
+                )
+        },
+    )
+    .parse(input)
+}
+
+fn parse_base_timestamp(input: &str) -> IResult<&str, DateTime<Local>> {
     alt((
-        map_res(parse_string, |s| {
-            DateTime::parse_from_rfc3339(&s).map(|d| d.with_timezone(&Local))
+        map_res((parse_string, opt(parse_time_zone)), |(s, time_zone)| {
+            parse_date(&s, time_zone)
         }),
-        map_res(parse_string, |s| {
-            parse(s).map(|d| d.and_local_timezone(Local).unwrap())
-        }),
-        value(Local::now(), wsi(tag_no_case("now"))),
+        map(wsi(tag_no_case("now")), |_| Local::now()),
     ))
     .parse(input)
 }
 
+fn parse_date(input: &str, time_zone: Option<Tz>) -> Result<DateTime<Local>, String> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(input) {
+        return Ok(date.with_timezone(&Local));
+    }
+    let date: NaiveDateTime = parse(input).map_err(|e| e.to_string())?;
+    let date = match time_zone {
+        Some(time_zone) => time_zone
+            .from_local_datetime(&date)
+            .earliest()
+            .map(|d| d.with_timezone(&Local)),
+        None => date.and_local_timezone(Local).earliest(),
+    };
+    date.ok_or(format!("'{input}' does not exist in this time zone"))
+}
+
+/// Parses a time zone of the IANA database, for instance `Europe/Paris` or `UTC`.
+fn parse_time_zone(input: &str) -> IResult<&str, Tz> {
+    map_res(
+        wsi(take_while1(|c: char| {
+            c.is_ascii_alphanumeric() || matches!(c, '/' | '_' | '-' | '+')
+        })),
+        Tz::from_str,
+    )
+    .parse(input)
+}
+
+/// Parses a duration, for instance `15m`, `2h` or `1d12h`.
+/// Supported units are `ms`, `s`, `m`, `h`, `d` and `w`.
+pub(crate) fn parse_duration(input: &str) -> IResult<&str, TimeDelta> {
+    map_opt(many1((parse_number::<i64>, parse_duration_unit)), |parts| {
+        parts
+            .into_iter()
+            .try_fold(TimeDelta::zero(), |duration, (value, unit)| {
+                duration.checked_add(&unit(value)?)
+            })
+    })
+    .parse(input)
+}
+
+fn parse_duration_unit(input: &str) -> IResult<&str, fn(i64) -> Option<TimeDelta>> {
+    terminated(
+        alt((
+            map(tag("ms"), |_| TimeDelta::try_milliseconds as fn(i64) -> _),
+            map(tag("s"), |_| TimeDelta::try_seconds as fn(i64) -> _),
+            map(tag("m"), |_| TimeDelta::try_minutes as fn(i64) -> _),
+            map(tag("h"), |_| TimeDelta::try_hours as fn(i64) -> _),
+            map(tag("d"), |_| TimeDelta::try_days as fn(i64) -> _),
+            map(tag("w"), |_| TimeDelta::try_weeks as fn(i64) -> _),
+        )),
+        not(peek(alpha1)),
+    )
+    .parse(input)
+}
+
 #[test]
 fn test_parse_timestamp() {
     assert!(parse_timestamp(r#"'3 hours ago'"#).is_ok());
     assert!(parse_timestamp(r#"now"#).is_ok());
     assert!(parse_timestamp(r#""2024-09-17T06:44:59Z""#).is_ok());
 }
+
+#[test]
+fn test_parse_timestamp_with_durations() {
+    let (remaining, timestamp) = parse_timestamp(r#""2024-09-17T06:44:59Z" - 15m"#).unwrap();
+    assert_eq!(remaining, "");
+    assert_eq!(timestamp.to_utc().to_rfc3339(), "2024-09-17T06:29:59+00:00");
+
+    let (remaining, timestamp) =
+        parse_timestamp(r#""2024-09-17T06:44:59Z" - 1d + 2h30m and"#).unwrap();
+    assert_eq!(remaining, " and");
+    assert_eq!(timestamp.to_utc().to_rfc3339(), "2024-09-16T09:14:59+00:00");
+
+    let before = Local::now();
+    let (_, timestamp) = parse_timestamp("now - 2h").unwrap();
+    assert!(timestamp <= before - TimeDelta::hours(2) + TimeDelta::seconds(5));
+
+    // Without a unit, it's not a duration
+    assert_eq!(parse_timestamp("now - 10").unwrap().0, " - 10");
+}
+
+#[test]
+fn test_parse_timestamp_with_time_zone() {
+    let (remaining, timestamp) = parse_timestamp(r#"'2024-05-01 10:00' Europe/Paris"#).unwrap();
+    assert_eq!(remaining, "");
+    assert_eq!(timestamp.to_utc().to_rfc3339(), "2024-05-01T08:00:00+00:00");
+
+    let (_, timestamp) = parse_timestamp(r#"'2024-01-01 10:00' UTC - 30m"#).unwrap();
+    assert_eq!(timestamp.to_utc().to_rfc3339(), "2024-01-01T09:30:00+00:00");
+}
+
+#[test]
+fn test_parse_duration() {
+    assert_eq!(parse_duration("15m"), Ok(("", TimeDelta::minutes(15))));
+    assert_eq!(
+        parse_duration("500ms"),
+        Ok(("", TimeDelta::milliseconds(500)))
+    );
+    assert_eq!(
+        parse_duration("1d12h"),
+        Ok(("", TimeDelta::days(1) + TimeDelta::hours(12)))
+    );
+    assert!(parse_duration("2months").is_err());
+}
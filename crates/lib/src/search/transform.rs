@@ -0,0 +1,38 @@
+//! The `transform` clause rewrites the kafka records with a transformer,
+//! a wasm module exporting a `transform` function, before they are matched and displayed.
+//! Transformers are chained in the order they are written:
+//!
+//! ```sql
+//! from begin value.status == "FAILED" | transform decrypt('k1') | transform flatten()
+//! ```
+use nom::{
+    IResult, Parser, bytes::complete::tag, bytes::complete::tag_no_case, sequence::preceded,
+};
+
+use super::{filter::Filter, filter::parse_filter, wsi::wsi};
+
+pub(crate) fn parse_transform(input: &str) -> IResult<&str, Filter> {
+    preceded(
+        (wsi(tag("|")), wsi(tag_no_case("transform"))),
+        wsi(parse_filter),
+    )
+    .parse(input)
+}
+
+#[test]
+fn test_parse_transform() {
+    use super::filter::Parameter;
+
+    assert_eq!(
+        parse_transform(" | transform decrypt('k1')"),
+        Ok((
+            "",
+            Filter {
+                name: "decrypt".to_string(),
+                parameters: vec![Parameter::String("k1".to_string())]
+            }
+        ))
+    );
+    assert!(parse_transform("|| transform decrypt()").is_err());
+    assert!(parse_transform("| decrypt()").is_err());
+}
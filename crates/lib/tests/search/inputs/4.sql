@@ -0,0 +1,4 @@
+select key, value.customer.id, headers.traceparent, timestamp
+from begin
+key == "my-key"
+limit 10
@@ -0,0 +1,6 @@
+from begin
+value.status != "OK"
+count
+count by value.status
+distinct key
+min(timestamp) max(timestamp)
@@ -1,7 +1,14 @@
-use app::search::ValidSearchQuery;
-use std::collections::HashSet;
+use app::{
+    configuration::HistoryEntry,
+    search::{ValidSearchQuery, filter::FilterMetrics},
+};
+use std::collections::{BTreeSet, HashSet};
 
-use lib::{KafkaRecord, TopicDetail, kafka::SchemaId, search::OrderBy};
+use lib::{
+    KafkaRecord, TopicDetail,
+    kafka::SchemaId,
+    search::{OrderBy, aggregation::AggregationRow},
+};
 
 use crate::schema_detail::SchemaDetail;
 
@@ -45,6 +52,8 @@ pub(crate) enum Action {
     Topics(Vec<String>),
     /// Request the list of kafka records to be sorted in a specific way
     OrderBy(OrderBy),
+    /// Request the list of kafka records to keep only the newest record of each key
+    LatestByKey(bool),
     /// List of topics to consume
     SelectedTopics(Vec<String>),
     /// Copy the given record to the clipboard
@@ -59,8 +68,16 @@ pub(crate) enum Action {
     TopicDetails(Vec<TopicDetail>),
     /// Notify the UI that the user typed a new search query
     NewSearchPrompt(String),
+    /// Notify the search bar the history of search queries
+    History(Vec<HistoryEntry>),
     /// Notify the progress bar an estimate of the kafka records to consume in total according to the search query
     RecordsToRead(usize),
+    /// Notify the UI the current results of the aggregations of the search query
+    Aggregations(Vec<AggregationRow>),
+    /// Notify the search bar the JSON paths learned from the kafka records, for the autocompletion
+    JsonPaths(BTreeSet<String>),
+    /// Notify the UI the metrics of the search filters used by the search query
+    FilterMetrics(Vec<FilterMetrics>),
 }
 
 /// A notification is a message displayed in the bottom-right corner of the TUI.
@@ -0,0 +1,95 @@
+//! Component showing, in real time, the results of the aggregations of the search query.
+use lib::search::aggregation::AggregationRow;
+use ratatui::{
+    Frame,
+    layout::{Alignment, Constraint, Rect},
+    style::Stylize,
+    text::Text,
+    widgets::{Block, BorderType, Borders, Cell, Clear, Padding, Row, Table},
+};
+
+use crate::{Action, error::TuiError};
+
+use super::{Component, ComponentName, State};
+
+#[derive(Default)]
+pub(crate) struct AggregationsComponent {
+    rows: Vec<AggregationRow>,
+}
+
+impl Component for AggregationsComponent {
+    fn id(&self) -> ComponentName {
+        ComponentName::Aggregations
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>, TuiError> {
+        match action {
+            Action::NewConsumer() => self.rows.clear(),
+            Action::Aggregations(rows) => self.rows = rows,
+            _ => (),
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, f: &mut Frame<'_>, rect: Rect, state: &State) -> Result<(), TuiError> {
+        f.render_widget(Clear, rect);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .padding(Padding::horizontal(2))
+            .title(" Aggregations ");
+        let block = self.make_block_focused_with_state(state, block);
+
+        let header = Row::new(vec![
+            Cell::new(Text::from("Aggregation")).bold(),
+            Cell::new(Text::from("Group")).bold(),
+            Cell::new(Text::from("Value").alignment(Alignment::Right)).bold(),
+        ])
+        .bottom_margin(1);
+
+        let rows = self.rows.iter().map(|row| {
+            Row::new(vec![
+                Cell::new(Text::from(row.aggregation.clone())),
+                Cell::new(Text::from(row.group.clone().unwrap_or_default())),
+                Cell::new(Text::from(row.value.clone()).alignment(Alignment::Right)),
+            ])
+        });
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(30),
+                Constraint::Percentage(40),
+                Constraint::Percentage(30),
+            ],
+        )
+        .header(header)
+        .block(block);
+        f.render_widget(table, rect);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+use crate::assert_draw;
+
+#[test]
+fn test_draw() {
+    let mut component = AggregationsComponent::default();
+
+    component
+        .update(Action::Aggregations(vec![
+            AggregationRow {
+                aggregation: "count by value.status".to_string(),
+                group: Some("FAILED".to_string()),
+                value: "12".to_string(),
+            },
+            AggregationRow {
+                aggregation: "count by value.status".to_string(),
+                group: Some("OK".to_string()),
+                value: "1_304".to_string(),
+            },
+        ]))
+        .unwrap();
+    assert_draw!(component, 90, 8)
+}
@@ -0,0 +1,348 @@
+//! Context-aware completion of the search query.
+//! Candidates depend on what precedes the word being typed:
+//!  - offsets after `from`,
+//!  - operators after a symbol,
+//!  - topic names after `topic ==`,
+//!  - JSON paths learned from the records for `value.` and `headers.`,
+//!  - saved queries after `@`,
+//!  - otherwise keywords, symbols, built-in functions and search filters.
+
+use std::{collections::BTreeSet, path::Path};
+
+const KEYWORDS: [&str; 12] = [
+    "from",
+    "limit",
+    "order by",
+    "select",
+    "where",
+    "and",
+    "or",
+    "not",
+    "count",
+    "group by",
+    "latest by key",
+    "sample",
+];
+
+const SYMBOLS: [&str; 8] = [
+    "key",
+    "value",
+    "topic",
+    "partition",
+    "offset",
+    "timestamp",
+    "size",
+    "headers",
+];
+
+const FUNCTIONS: [&str; 5] = [
+    "lower(",
+    "upper(",
+    "length(",
+    "json_type(",
+    "header_count()",
+];
+
+const OFFSETS: [&str; 4] = ["beginning", "begin", "end", "now"];
+
+const STRING_OPERATORS: [&str; 5] = ["==", "!=", "contains", "starts with", "=~"];
+
+const NUMBER_OPERATORS: [&str; 6] = ["==", "!=", ">", ">=", "<", "<="];
+
+const ORDER_KEYWORDS: [&str; 2] = ["asc", "desc"];
+
+/// Candidates for the word being typed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Completion {
+    /// Byte offset of the word being completed
+    pub start: usize,
+    pub candidates: Vec<String>,
+}
+
+impl Completion {
+    /// Replaces the word being completed with the candidate.
+    /// It returns the new input and the new position of the cursor.
+    pub fn apply(&self, input: &str, cursor: usize, index: usize) -> Option<(String, usize)> {
+        let candidate = self.candidates.get(index)?;
+        let separator = match candidate.ends_with('(') {
+            true => "",
+            false => " ",
+        };
+        let before = format!("{}{candidate}{separator}", &input[..self.start]);
+        let after = input[cursor..].trim_start();
+        Some((format!("{before}{after}"), before.chars().count()))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Completer {
+    pub topics: Vec<String>,
+    pub filters: Vec<String>,
+    /// Paths such as `value.customer.id` or `headers.trace-id`
+    pub paths: BTreeSet<String>,
+    /// Names of the saved queries
+    pub saved_queries: Vec<String>,
+}
+
+impl Completer {
+    /// Lists the search filters available in the directory.
+    pub fn filters_of(directory: &Path) -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(directory) else {
+            return vec![];
+        };
+        let mut filters = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|e| e == "wasm"))
+            .filter_map(|path| path.file_stem().map(|s| s.to_string_lossy().to_string()))
+            .collect::<Vec<_>>();
+        filters.sort();
+        filters
+    }
+
+    /// Returns the candidates for the word ending at the end of the input.
+    pub fn complete(&self, input: &str) -> Option<Completion> {
+        let start = input
+            .rfind(|c: char| c.is_whitespace() || c == '(')
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        let word = &input[start..];
+        let mut previous = input[..start].split_whitespace().rev();
+        let previous_token = previous.next().unwrap_or_default().to_lowercase();
+        let before_previous_token = previous.next().unwrap_or_default().to_lowercase();
+
+        let (candidates, prefix): (Vec<String>, &str) = match word {
+            w if w.starts_with("value.") || w.starts_with("headers.") => {
+                (self.paths.iter().cloned().collect(), "")
+            }
+            w if w.starts_with("v.") || w.starts_with("h.") => {
+                let alias = &w[..1];
+                let paths = self
+                    .paths
+                    .iter()
+                    .filter(|p| p.starts_with(alias))
+                    .filter_map(|p| p.split_once('.').map(|(_, path)| format!("{alias}.{path}")))
+                    .collect();
+                (paths, "")
+            }
+            w if w.starts_with('@') => (
+                self.saved_queries
+                    .iter()
+                    .map(|name| format!("@{name}"))
+                    .collect(),
+                "",
+            ),
+            _ if is_string_operator(&previous_token)
+                && ["topic", "t"].contains(&before_previous_token.as_str()) =>
+            {
+                let topics = self.topics.iter().map(|t| format!("\"{t}\"")).collect();
+                (topics, "\"")
+            }
+            _ if previous_token == "from" => (to_strings(&OFFSETS), ""),
+            _ if previous_token == "by"
+                && ["order", "sort"].contains(&before_previous_token.as_str()) =>
+            {
+                (to_strings(&SYMBOLS[..7]), "")
+            }
+            _ if before_previous_token == "by" && SYMBOLS.contains(&previous_token.as_str()) => {
+                (to_strings(&ORDER_KEYWORDS), "")
+            }
+            _ if is_string_symbol(&previous_token) => (to_strings(&STRING_OPERATORS), ""),
+            _ if is_number_symbol(&previous_token) => (to_strings(&NUMBER_OPERATORS), ""),
+            "" => return None,
+            _ => {
+                let mut candidates = to_strings(&KEYWORDS);
+                candidates.extend(to_strings(&SYMBOLS));
+                candidates.extend(to_strings(&FUNCTIONS));
+                candidates.extend(self.filters.iter().map(|f| format!("{f}(")));
+                (candidates, "")
+            }
+        };
+
+        let typed = word.trim_start_matches(['"', '\'']).to_lowercase();
+        let candidates = candidates
+            .into_iter()
+            .filter(|c| {
+                let c = c.trim_start_matches(prefix).to_lowercase();
+                c.starts_with(&typed) && c != typed
+            })
+            .collect::<Vec<_>>();
+
+        match candidates.is_empty() {
+            true => None,
+            false => Some(Completion { start, candidates }),
+        }
+    }
+}
+
+fn to_strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+fn is_string_operator(token: &str) -> bool {
+    ["==", "=", "!=", "=~", "~=", "contains", "with"].contains(&token)
+}
+
+fn is_string_symbol(token: &str) -> bool {
+    ["key", "k", "topic", "t", "value", "v"].contains(&token)
+        || ["value.", "v.", "headers.", "h."]
+            .iter()
+            .any(|prefix| token.starts_with(prefix))
+        || ["lower(", "upper(", "json_type("]
+            .iter()
+            .any(|function| token.starts_with(function))
+}
+
+fn is_number_symbol(token: &str) -> bool {
+    [
+        "offset",
+        "o",
+        "partition",
+        "p",
+        "size",
+        "si",
+        "timestamp",
+        "ts",
+    ]
+    .contains(&token)
+        || ["length(", "len(", "header_count("]
+            .iter()
+            .any(|function| token.starts_with(function))
+}
+
+/// Collects the JSON paths of the value, for instance `value.customer.id`.
+pub(crate) fn learn_paths(
+    value: &serde_json::Value,
+    prefix: &str,
+    paths: &mut BTreeSet<String>,
+    limit: usize,
+) {
+    if let serde_json::Value::Object(object) = value {
+        for (key, value) in object {
+            if paths.len() >= limit {
+                return;
+            }
+            // The query language has no syntax for properties containing spaces
+            if key.contains(' ') {
+                continue;
+            }
+            let path = format!("{prefix}.{key}");
+            learn_paths(value, &path, paths, limit);
+            paths.insert(path);
+        }
+    }
+}
+
+#[cfg(test)]
+fn completer() -> Completer {
+    Completer {
+        topics: vec!["public-french-addresses".to_string(), "orders".to_string()],
+        filters: vec!["key_ends_with".to_string()],
+        paths: [
+            "value.customer",
+            "value.customer.id",
+            "value.amount",
+            "headers.trace-id",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect(),
+        saved_queries: vec!["failed-payments".to_string(), "from-france".to_string()],
+    }
+}
+
+#[cfg(test)]
+fn candidates(input: &str) -> Vec<String> {
+    completer()
+        .complete(input)
+        .map(|c| c.candidates)
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_complete_keywords_and_filters() {
+    assert_eq!(candidates("fr"), vec!["from"]);
+    assert_eq!(candidates("key_"), vec!["key_ends_with("]);
+    assert_eq!(candidates("from begin li"), vec!["limit"]);
+    assert!(candidates("from begin ").is_empty());
+}
+
+#[test]
+fn test_complete_after_from() {
+    assert_eq!(
+        candidates("from "),
+        vec!["beginning", "begin", "end", "now"]
+    );
+    assert_eq!(candidates("from b"), vec!["beginning", "begin"]);
+}
+
+#[test]
+fn test_complete_operators() {
+    assert_eq!(
+        candidates("key "),
+        vec!["==", "!=", "contains", "starts with", "=~"]
+    );
+    assert_eq!(candidates("offset >"), vec![">="]);
+    assert_eq!(candidates("value.customer.id s"), vec!["starts with"]);
+}
+
+#[test]
+fn test_complete_topics() {
+    assert_eq!(
+        candidates("topic == "),
+        vec!["\"public-french-addresses\"", "\"orders\""]
+    );
+    assert_eq!(candidates(r#"topic == "or"#), vec!["\"orders\""]);
+}
+
+#[test]
+fn test_complete_json_paths() {
+    assert_eq!(
+        candidates("value.cust"),
+        vec!["value.customer", "value.customer.id"]
+    );
+    assert_eq!(candidates("v.a"), vec!["v.amount"]);
+    assert_eq!(candidates("h.t"), vec!["h.trace-id"]);
+}
+
+#[test]
+fn test_complete_saved_queries() {
+    assert_eq!(candidates("@"), vec!["@failed-payments", "@from-france"]);
+    assert_eq!(
+        candidates("partition == 2 and @fa"),
+        vec!["@failed-payments"]
+    );
+}
+
+#[test]
+fn test_apply_completion() {
+    let input = "from begin key sta and partition == 2";
+    let completion = completer().complete(&input[..18]).unwrap();
+    assert_eq!(
+        completion.apply(input, 18, 0),
+        Some((
+            "from begin key starts with and partition == 2".to_string(),
+            27
+        ))
+    );
+}
+
+#[test]
+fn test_learn_paths() {
+    let mut paths = BTreeSet::new();
+    learn_paths(
+        &serde_json::json!({"customer": {"id": 1, "name": "John"}, "items": [1, 2]}),
+        "value",
+        &mut paths,
+        100,
+    );
+    assert_eq!(
+        paths.into_iter().collect::<Vec<_>>(),
+        vec![
+            "value.customer",
+            "value.customer.id",
+            "value.customer.name",
+            "value.items"
+        ]
+    );
+}
@@ -1,6 +1,9 @@
 //! Component showing the help
 
-use app::configuration::{Configuration, SENSITIVE_KAFKA_PROPERTIES};
+use app::{
+    configuration::{Configuration, SENSITIVE_KAFKA_PROPERTIES},
+    search::imported_filter::{ImportedFilter, imported_filters},
+};
 use crossterm::event::{KeyCode, KeyEvent};
 use itertools::Itertools;
 use ratatui::{
@@ -28,6 +31,8 @@ const REPOSITORY_URL: &str = concat!(
 pub(crate) struct HelpComponent {
     scroll: ScrollState,
     rendered: usize,
+    /// Search filters of the workspace, listed the first time the help is drawn
+    filters: Option<Vec<ImportedFilter>>,
 }
 
 impl HelpComponent {
@@ -222,6 +227,34 @@ impl Component for HelpComponent {
             Line::from(""),
         ]);
 
+        text.push(Line::from("                                                 Search filter      Usage                                   Description").bold());
+        let filters = self
+            .filters
+            .get_or_insert_with(|| imported_filters(&state.workspace().filters_dir()));
+        if filters.is_empty() {
+            text.push(Line::from(format!(
+                "{:>62}      No search filter imported yet, run `yozf import-filter --help`",
+                ""
+            )));
+        }
+        for filter in filters.iter() {
+            let description = filter
+                .description
+                .as_ref()
+                .and_then(|d| d.description.clone())
+                .unwrap_or_default();
+            for (i, example) in filter.examples().into_iter().enumerate() {
+                let name = if i == 0 { filter.name.as_str() } else { "" };
+                let description = if i == 0 { description.as_str() } else { "" };
+                text.push(Line::from(vec![
+                    Span::raw(format!("{name:>62}      ")),
+                    Span::from(format!("{example:<40}")).fg(state.theme.yellow),
+                    Span::from(description.to_string()),
+                ]));
+            }
+        }
+        text.push(Line::from(""));
+
         let number_of_lines = text.len();
         let paragraph = Paragraph::new(text)
             .wrap(Wrap { trim: false })
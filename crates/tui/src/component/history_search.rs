@@ -0,0 +1,124 @@
+//! Fuzzy search in the history of search queries, opened with `Ctrl + R` in the search bar.
+//! By default, only the search queries run against the current cluster are listed.
+
+use app::configuration::HistoryEntry;
+use itertools::Itertools;
+use tui_input::Input;
+
+/// State of the history search popup.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HistorySearch {
+    /// The pattern typed by the user
+    pub pattern: Input,
+    /// List the search queries of every cluster
+    pub all_clusters: bool,
+    pub selected: usize,
+}
+
+impl HistorySearch {
+    /// Returns the entries matching the pattern, the best matches first.
+    /// A search query run several times is listed once, with its most recent run.
+    pub fn matches<'a>(&self, history: &'a [HistoryEntry], cluster: &str) -> Vec<&'a HistoryEntry> {
+        let pattern = self.pattern.value();
+        history
+            .iter()
+            .rev()
+            .filter(|entry| self.all_clusters || entry.is_of_cluster(cluster))
+            .unique_by(|entry| entry.query.as_str())
+            .filter_map(|entry| fuzzy_score(pattern, &entry.query).map(|score| (score, entry)))
+            // The sort is stable, most recent entries come first among equal scores
+            .sorted_by_key(|(score, _)| std::cmp::Reverse(*score))
+            .map(|(_, entry)| entry)
+            .collect()
+    }
+}
+
+/// Returns a score if all the characters of the pattern appear, in order, in the text.
+/// Consecutive characters and characters at the start of a word score higher.
+pub(crate) fn fuzzy_score(pattern: &str, text: &str) -> Option<i64> {
+    let mut score = 0;
+    let mut text = text.chars().enumerate();
+    let mut before: Option<char> = None;
+    let mut last_match: Option<usize> = None;
+    for p in pattern.chars().filter(|c| !c.is_whitespace()) {
+        let (index, word_start) = loop {
+            let (index, c) = text.next()?;
+            let word_start = before.is_none_or(|b| !b.is_alphanumeric());
+            before = Some(c);
+            if c.to_lowercase().eq(p.to_lowercase()) {
+                break (index, word_start);
+            }
+        };
+        let consecutive = last_match.is_some_and(|last| last + 1 == index);
+        score += match (consecutive, word_start) {
+            (true, _) => 3,
+            (false, true) => 2,
+            (false, false) => 1,
+        };
+        last_match = Some(index);
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+fn history() -> Vec<HistoryEntry> {
+    vec![
+        HistoryEntry::from("from end - 10"),
+        HistoryEntry::new(r#"key == "my-key""#, "prod", &[]),
+        HistoryEntry::new("from begin partition == 2", "dev", &[]),
+        HistoryEntry::new(r#"value.status == "FAILED""#, "prod", &[]),
+        HistoryEntry::new(r#"key == "my-key""#, "dev", &[]),
+    ]
+}
+
+#[cfg(test)]
+fn queries(search: &HistorySearch, cluster: &str) -> Vec<String> {
+    let history = history();
+    search
+        .matches(&history, cluster)
+        .into_iter()
+        .map(|e| e.query.clone())
+        .collect()
+}
+
+#[test]
+fn test_fuzzy_score() {
+    assert_eq!(fuzzy_score("", "key"), Some(0));
+    assert!(fuzzy_score("kmk", r#"key == "my-key""#).is_some());
+    assert_eq!(fuzzy_score("yek", r#"key == "my-key""#), None);
+    assert!(fuzzy_score("key", "key == 1") > fuzzy_score("key", "k e y"));
+}
+
+#[test]
+fn test_history_search_filters_by_cluster() {
+    let search = HistorySearch::default();
+    assert_eq!(
+        queries(&search, "dev"),
+        vec![
+            r#"key == "my-key""#,
+            "from begin partition == 2",
+            "from end - 10"
+        ]
+    );
+    assert_eq!(
+        queries(&search, "prod"),
+        vec![
+            r#"value.status == "FAILED""#,
+            r#"key == "my-key""#,
+            "from end - 10"
+        ]
+    );
+}
+
+#[test]
+fn test_history_search_all_clusters() {
+    let search = HistorySearch {
+        pattern: Input::from("from"),
+        all_clusters: true,
+        selected: 0,
+    };
+    assert_eq!(
+        queries(&search, "prod"),
+        vec!["from begin partition == 2", "from end - 10"]
+    );
+}
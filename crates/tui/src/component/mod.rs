@@ -1,6 +1,9 @@
+mod aggregations_component;
+mod completion;
 mod footer_component;
 mod header_component;
 mod help_component;
+mod history_search;
 mod issue_component;
 // mod progress_bar_component;
 mod record_details_component;
@@ -21,6 +24,7 @@ mod vertical_scrollable_block;
 
 #[cfg(test)]
 use app::configuration::{GlobalConfig, InternalConfig, Workspace};
+pub(crate) use completion::learn_paths;
 use crossterm::event::{KeyEvent, MouseEvent};
 use ratatui::{
     Frame,
@@ -60,6 +64,7 @@ pub(crate) enum ComponentName {
     Dialog,
     Help,
     Schemas,
+    Aggregations,
 }
 
 impl ComponentName {
@@ -155,6 +160,8 @@ pub fn default_workspace() -> Workspace {
             clusters: indexmap::IndexMap::default(),
             default_kafka_config: indexmap::IndexMap::default(),
             history: vec![],
+            filter_limits: indexmap::IndexMap::default(),
+            saved_queries: indexmap::IndexMap::default(),
             show_shortcuts: true,
             export_directory: std::path::PathBuf::from(""),
             consumer: ConsumerConfig::default(),
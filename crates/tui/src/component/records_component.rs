@@ -1,14 +1,18 @@
 //! Component showing in real time incoming kafka records.
 
-use app::{configuration::TimestampFormat, search::ValidSearchQuery};
+use app::{
+    configuration::TimestampFormat,
+    search::{ValidSearchQuery, filter::FilterMetrics},
+};
 use copypasta::{ClipboardContext, ClipboardProvider};
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use itertools::Itertools;
 use lib::ExportedKafkaRecord;
 use ratatui::{
     Frame,
     layout::{Alignment, Constraint, Layout, Rect},
     style::{Style, Stylize},
-    text::{Span, Text},
+    text::{Line, Span, Text},
     widgets::{Block, BorderType, Borders, Cell, Row, Table, TableState},
 };
 use thousands::Separable;
@@ -38,6 +42,7 @@ pub(crate) struct RecordsComponent {
     key_events_buffer: Vec<KeyEvent>,
     column_size: u16,
     timestamp_format: TimestampFormat,
+    filter_metrics: Vec<FilterMetrics>,
 }
 
 impl RecordsComponent {
@@ -55,6 +60,7 @@ impl RecordsComponent {
             key_events_buffer: Vec::default(),
             column_size: 0,
             timestamp_format,
+            filter_metrics: vec![],
         }
     }
 
@@ -67,6 +73,7 @@ impl RecordsComponent {
     }
 
     pub fn poll_new_records(&mut self) {
+        let known_paths = self.records.paths().len();
         for _i in 0..500 {
             match self.receiver.try_recv() {
                 Err(_) => break,
@@ -78,6 +85,11 @@ impl RecordsComponent {
         }
 
         self.records.sort(&self.search_query.query().order_by);
+        if self.records.paths().len() != known_paths {
+            if let Some(action_tx) = &self.action_tx {
+                let _ = action_tx.send(Action::JsonPaths(self.records.paths().clone()));
+            }
+        }
     }
 
     fn next(&mut self) {
@@ -317,11 +329,14 @@ impl Component for RecordsComponent {
             Action::Consuming => {
                 self.consuming = true;
                 self.records.reset();
+                self.filter_metrics.clear();
             }
+            Action::FilterMetrics(metrics) => self.filter_metrics = metrics,
             Action::StopConsuming() => {
                 self.consuming = false;
                 self.records.reset();
             }
+            Action::LatestByKey(enabled) => self.records.latest_by_key(enabled),
             Action::Search(search_query) => {
                 self.state.select(None);
                 self.search_query = search_query;
@@ -338,6 +353,12 @@ impl Component for RecordsComponent {
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
             .title(" Records ");
+        let block = match self.filter_metrics.is_empty() {
+            true => block,
+            false => block.title_bottom(
+                Line::from(format!(" {} ", self.filter_metrics.iter().join(" · "))).right_aligned(),
+            ),
+        };
 
         let block = self.make_block_focused_with_state(state, block);
 
@@ -37,10 +37,38 @@ fn test_draw() {
             }"#
             )),
             value_as_string: String::default(),
+            tombstone: false,
         }],
+        superseding: vec![],
         read: 1,
     })
     .unwrap();
 
     assert_draw!(component, 120, 5)
 }
+
+#[test]
+fn test_draw_filter_metrics() {
+    use std::time::Duration;
+
+    use app::search::filter::{FilterMetrics, ModuleKind};
+    use tokio::sync::mpsc::unbounded_channel;
+
+    use crate::Action;
+
+    let (_tx, rx) = unbounded_channel();
+    let mut component = RecordsComponent::new(rx, Default::default());
+    component
+        .update(Action::FilterMetrics(vec![FilterMetrics {
+            name: "key-ends-with".into(),
+            kind: ModuleKind::SearchFilter,
+            calls: 12_034,
+            errors: 2,
+            mean_latency: Duration::from_micros(215),
+            instances: 4,
+            disabled: None,
+        }]))
+        .unwrap();
+
+    assert_draw!(component, 80, 5)
+}
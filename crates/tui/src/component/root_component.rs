@@ -21,11 +21,11 @@ use crate::{
 };
 
 use super::{
-    Component, ComponentName, State, footer_component::FooterComponent,
-    header_component::HeaderComponent, help_component::HelpComponent,
-    record_details_component::RecordDetailsComponent, records_component::RecordsComponent,
-    schemas_component::SchemasComponent, search_component::SearchComponent,
-    topic_details_component::TopicDetailsComponent,
+    Component, ComponentName, State, aggregations_component::AggregationsComponent,
+    footer_component::FooterComponent, header_component::HeaderComponent,
+    help_component::HelpComponent, record_details_component::RecordDetailsComponent,
+    records_component::RecordsComponent, schemas_component::SchemasComponent,
+    search_component::SearchComponent, topic_details_component::TopicDetailsComponent,
     topics_and_records_component::TopicsAndRecordsComponent, topics_component::TopicsComponent,
 };
 
@@ -54,7 +54,7 @@ impl RootComponent {
 
         let highlighter = Highlighter::new(state.highlighter_theme.clone());
 
-        let mut components: [Arc<Mutex<dyn Component>>; 10] = [
+        let mut components: [Arc<Mutex<dyn Component>>; 11] = [
             topics_component.clone(),
             Arc::new(Mutex::new(RecordsComponent::new(
                 records_receiver,
@@ -62,15 +62,21 @@ impl RootComponent {
             ))),
             Arc::new(Mutex::new(TopicDetailsComponent::default())),
             Arc::new(Mutex::new(RecordDetailsComponent::new(highlighter.clone()))),
-            Arc::new(Mutex::new(SearchComponent::new(
-                query,
-                config.history.clone(),
-                state.workspace().filters_dir(),
-            ))),
+            Arc::new(Mutex::new(
+                SearchComponent::new(
+                    query,
+                    config.history.clone(),
+                    state.workspace().filters_dir(),
+                )
+                .with_cluster(&state.cluster)
+                .with_saved_queries(state.config.saved_queries())
+                .with_filter_limits(state.config.filter_limits()),
+            )),
             Arc::new(Mutex::new(footer)),
             Arc::new(Mutex::new(HelpComponent::default())),
             Arc::new(Mutex::new(SchemasComponent::new(highlighter))),
             Arc::new(Mutex::new(HeaderComponent::default())),
+            Arc::new(Mutex::new(AggregationsComponent::default())),
             Arc::new(Mutex::new(FooterComponent::default())),
         ];
 
@@ -247,7 +253,11 @@ impl Component for RootComponent {
                 let new_focus = self.focus_previous(&self.state.focused.clone());
                 self.focus(new_focus)?;
             }
-            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            // In the search bar, `Ctrl + R` searches the history
+            KeyCode::Char('r')
+                if key.modifiers.contains(KeyModifiers::CONTROL)
+                    && self.state.focused != ComponentName::Search =>
+            {
                 self.action_tx.as_ref().unwrap().send(Action::Refresh)?;
             }
             KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
@@ -290,7 +300,10 @@ impl Component for RootComponent {
                 self.toggle_view(ComponentName::Help)?;
                 return Ok(None);
             }
-            KeyCode::Esc => self.close(),
+            // In the main view, the search bar uses `Esc` to close its popups
+            KeyCode::Esc if self.state.focused != ComponentName::Search || self.views.len() > 1 => {
+                self.close();
+            }
             KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 self.quit();
             }
@@ -444,6 +457,7 @@ pub fn focus_order_of(component: &ComponentName) -> Vec<ComponentName> {
         ],
         ComponentName::TopicDetails => vec![ComponentName::TopicDetails, ComponentName::Search],
         ComponentName::Help => vec![ComponentName::Help, ComponentName::Search],
+        ComponentName::Aggregations => vec![ComponentName::Aggregations, ComponentName::Search],
         ComponentName::Dialog
         | ComponentName::Search
         | ComponentName::Main
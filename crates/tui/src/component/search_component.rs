@@ -1,20 +1,27 @@
 //! This component renders the search bar.
 //! It comes with the following features:
 //!  - all queries are stored into a history.
-//!  - The component suggests queries based on your history.
+//!  - The component suggests queries based on the history of the current cluster.
+//!  - `Ctrl + R` opens a fuzzy search in the history.
+//!  - `Ctrl + Space` opens a popup listing context-aware completions: keywords, operators, topics, search filters, saved queries and JSON paths.
+//!    While the popup is open, `↑`, `↓` and `→` select and accept completions instead of browsing the history.
 
 use std::{path::PathBuf, time::Duration};
 
-use app::search::ValidSearchQuery;
+use app::{
+    configuration::{FilterLimits, HistoryEntry, SavedQuery},
+    search::ValidSearchQuery,
+};
 use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use indexmap::IndexMap;
 use itertools::Itertools;
-use lib::{Error, error::SearchError};
+use lib::{Error, error::SearchError, search::parse_error::ParseError};
 use ratatui::{
     Frame,
-    layout::{Position, Rect},
+    layout::{Constraint, Layout, Position, Rect},
     style::Style,
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, Padding, Paragraph, Wrap},
+    widgets::{Block, BorderType, Borders, Clear, List, ListState, Padding, Paragraph, Wrap},
 };
 use tokio::{select, sync::mpsc::UnboundedSender, time::Instant};
 use tokio_util::sync::CancellationToken;
@@ -22,43 +29,101 @@ use tracing::error;
 use tui_input::{Input, backend::crossterm::EventHandler};
 
 use crate::action::Level;
+use crate::highlighter::query::{highlight_query, invalid_offset};
 use crate::{
     error::TuiError,
     {Action, Notification},
 };
 
-use super::{Component, ComponentName, Shortcut, State};
+use super::{
+    Component, ComponentName, Shortcut, State,
+    completion::{Completer, Completion},
+    history_search::HistorySearch,
+};
+
+/// Maximum number of completions visible in the popup
+const MAX_VISIBLE_COMPLETIONS: usize = 8;
+
+/// Maximum number of search queries visible in the history search popup
+const MAX_VISIBLE_HISTORY_ENTRIES: usize = 10;
 
 #[derive(Default)]
 pub(crate) struct SearchComponent {
     input: Input,
     index_history: usize,
-    history: Vec<String>,
+    history: Vec<HistoryEntry>,
+    /// The cluster the history is filtered by
+    cluster: String,
+    history_search: Option<HistorySearch>,
     compiler_worker: CancellationToken,
     filters_directory: PathBuf,
-    remaining_input: Option<String>,
+    saved_queries: IndexMap<String, SavedQuery>,
+    /// The selected topics, they are recorded in the history and only the saved queries about them are suggested
+    topics: Vec<String>,
+    filter_limits: IndexMap<String, FilterLimits>,
+    parse_error: Option<ParseError>,
+    /// The last highlighted input and the byte offset from which it cannot be parsed
+    highlighted: Option<(String, Option<usize>)>,
     action_tx: Option<UnboundedSender<Action>>,
     autocomplete: Option<String>,
+    completer: Completer,
+    completion: Option<Completion>,
+    selected_completion: usize,
     // A hack to detect copy-paste events and replace \n with a space
     entered: Option<Instant>,
 }
 
 impl SearchComponent {
-    pub fn new(input: &str, history: Vec<String>, filters_directory: PathBuf) -> Self {
+    pub fn new(input: &str, history: Vec<HistoryEntry>, filters_directory: PathBuf) -> Self {
         Self {
             input: Input::from(input),
             index_history: history.len().saturating_sub(1),
             history,
+            completer: Completer {
+                filters: Completer::filters_of(&filters_directory),
+                ..Completer::default()
+            },
             filters_directory,
             ..Self::default()
         }
     }
 
+    /// Only the history of this cluster is suggested.
+    pub fn with_cluster(mut self, cluster: &str) -> Self {
+        self.cluster = cluster.to_string();
+        self
+    }
+
+    /// Saved queries that can be referenced in the search query with `@name`.
+    pub fn with_saved_queries(mut self, saved_queries: IndexMap<String, SavedQuery>) -> Self {
+        self.saved_queries = saved_queries;
+        self.update_saved_query_completions();
+        self
+    }
+
+    /// Only the saved queries about the selected topics are suggested.
+    fn update_saved_query_completions(&mut self) {
+        self.completer.saved_queries = self
+            .saved_queries
+            .iter()
+            .filter(|(_, saved_query)| saved_query.applies_to(&self.topics))
+            .map(|(name, _)| name.clone())
+            .collect();
+    }
+
+    /// Resource limits of the search filters, by name.
+    pub fn with_filter_limits(mut self, filter_limits: IndexMap<String, FilterLimits>) -> Self {
+        self.filter_limits = filter_limits;
+        self
+    }
+
     fn parse_input(&mut self) {
         let input = self.input.value().to_string();
         let tt = self.action_tx.clone();
 
         let filters_dir = self.filters_directory.clone();
+        let saved_queries = self.saved_queries.clone();
+        let filter_limits = self.filter_limits.clone();
         self.compiler_worker.cancel();
         self.compiler_worker = CancellationToken::new();
         let token = self.compiler_worker.clone();
@@ -70,9 +135,13 @@ impl SearchComponent {
                 _ = token.cancelled() => {  },
                 _ = tokio::time::sleep(Duration::from_millis(700)) => {
                     if input.len() > 5 {
-                        if let Err(e) = ValidSearchQuery::from(&input, &filters_dir) {
+                        // Loading search filters instantiates their wasm modules, it may wait for a busy instance
+                        let compiled = tokio::task::spawn_blocking(move || {
+                            ValidSearchQuery::with_filter_limits(&input, &filters_dir, &saved_queries, &filter_limits)
+                        }).await;
+                        if let Ok(Err(e)) = compiled {
                             error!("{e}");
-                            tt.as_ref().unwrap().send(Action::Notification(Notification::new(Level::Error, e.to_string()))).unwrap();
+                            tt.as_ref().unwrap().send(Action::Notification(Notification::new(Level::Error, Self::error_message(&e)))).unwrap();
                         }
                     }
                  }
@@ -86,6 +155,8 @@ impl SearchComponent {
         let mut possibilities = self
             .history
             .iter()
+            .filter(|e| e.is_of_cluster(&self.cluster))
+            .map(|e| &e.query)
             .filter(|e| e.starts_with(prompt))
             .collect_vec();
         possibilities.dedup();
@@ -109,27 +180,61 @@ impl SearchComponent {
             .map(|e| e.split_at(prompt.len()).1.to_string());
     }
 
+    /// Updates the completions of the word under the cursor.
+    fn complete(&mut self) {
+        let input = self.input.value();
+        let cursor = input
+            .char_indices()
+            .nth(self.input.cursor())
+            .map_or(input.len(), |(i, _)| i);
+        self.completion = self.completer.complete(&input[..cursor]);
+        self.selected_completion = 0;
+    }
+
+    /// Replaces the word under the cursor with the selected completion.
+    fn apply_completion(&mut self) {
+        let Some(completion) = self.completion.take() else {
+            return;
+        };
+        let input = self.input.value();
+        let cursor = input
+            .char_indices()
+            .nth(self.input.cursor())
+            .map_or(input.len(), |(i, _)| i);
+        if let Some((value, cursor)) = completion.apply(input, cursor, self.selected_completion) {
+            self.input = self.input.clone().with_value(value).with_cursor(cursor);
+            self.autocomplete = None;
+        }
+    }
+
     fn update_history(&mut self, prompt: &str) -> Result<(), TuiError> {
         // Do not accept empty prompts in the history
         if prompt.trim().is_empty() {
             return Ok(());
         }
         let prompt = prompt.trim().to_string();
-        if !self.history.contains(&prompt) {
-            self.history.push(prompt.to_string());
-            self.index_history = self.history.len() - 1;
-            self.action_tx
-                .as_ref()
-                .unwrap()
-                .send(Action::NewSearchPrompt(prompt))?;
-        }
+        let cluster = self.cluster.clone();
+        self.history
+            .retain(|e| e.query != prompt || e.cluster.as_deref() != Some(cluster.as_str()));
+        self.history
+            .push(HistoryEntry::new(&prompt, &self.cluster, &self.topics));
+        self.index_history = self.history.len() - 1;
+        self.action_tx
+            .as_ref()
+            .unwrap()
+            .send(Action::NewSearchPrompt(prompt))?;
         Ok(())
     }
 
     fn search(&mut self) -> Result<(), TuiError> {
         let o = self.input.value().to_string();
 
-        match ValidSearchQuery::from(o.as_str(), &self.filters_directory) {
+        match ValidSearchQuery::with_filter_limits(
+            o.as_str(),
+            &self.filters_directory,
+            &self.saved_queries,
+            &self.filter_limits,
+        ) {
             Ok(search_query) => {
                 self.update_history(&o)?;
                 self.action_tx
@@ -140,7 +245,7 @@ impl SearchComponent {
 
             Err(e) => {
                 if let Error::Search(SearchError::Parse(ee)) = &e {
-                    self.remaining_input = Some(ee.to_string());
+                    self.parse_error = Some(ee.clone());
                 }
 
                 self.action_tx
@@ -148,23 +253,186 @@ impl SearchComponent {
                     .unwrap()
                     .send(Action::Notification(Notification::new(
                         Level::Error,
-                        e.to_string(),
+                        Self::error_message(&e),
                     )))?;
             }
         }
         Ok(())
     }
 
-    #[allow(dead_code)]
-    fn pretty_error_message(error: &nom::Err<nom::error::Error<&str>>) -> String {
+    /// Handles the keys while the history search is open.
+    fn handle_history_search_key(&mut self, key: KeyEvent) {
+        let Some(search) = self.history_search.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.history_search = None,
+            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                search.all_clusters = !search.all_clusters;
+                search.selected = 0;
+            }
+            KeyCode::Up => search.selected = search.selected.saturating_sub(1),
+            KeyCode::Down => {
+                let last = search
+                    .matches(&self.history, &self.cluster)
+                    .len()
+                    .saturating_sub(1);
+                search.selected = (search.selected + 1).min(last);
+            }
+            KeyCode::Enter => {
+                let query = search
+                    .matches(&self.history, &self.cluster)
+                    .get(search.selected)
+                    .map(|e| e.query.clone());
+                self.history_search = None;
+                if let Some(query) = query {
+                    let cursor = query.chars().count();
+                    self.input = self.input.clone().with_value(query).with_cursor(cursor);
+                    self.autocomplete = None;
+                    self.parse_input();
+                }
+            }
+            _ => {
+                search.pattern.handle_event(&Event::Key(key));
+                search.selected = 0;
+            }
+        }
+    }
+
+    /// Notifications are a single line, the caret is drawn under the search input instead.
+    fn error_message(error: &Error) -> String {
         match error {
-            nom::Err::Incomplete(_) => "Cannot parse query".to_string(),
-            nom::Err::Error(s) => format!("unexpected token '{}'", s.input),
-            nom::Err::Failure(s) => format!("unexpected token '{}'", s.input),
+            Error::Search(SearchError::Parse(e)) => e.message(),
+            e => e.to_string(),
         }
     }
 }
 
+impl SearchComponent {
+    /// Draws the completions in a popup above the search bar, aligned with the word being completed.
+    fn draw_completion(&self, f: &mut Frame<'_>, rect: Rect, state: &State) {
+        let Some(completion) = &self.completion else {
+            return;
+        };
+        let visible = completion.candidates.len().min(MAX_VISIBLE_COMPLETIONS);
+        let width = completion
+            .candidates
+            .iter()
+            .map(|c| c.chars().count())
+            .max()
+            .unwrap_or_default()
+            + 4;
+        let column = self.input.value()[..completion.start].chars().count();
+        let height = (visible + 2) as u16;
+        let x = (rect.x + 1 + column as u16).min(rect.right().saturating_sub(width as u16));
+        let area = Rect::new(
+            x,
+            rect.y.saturating_sub(height),
+            width as u16,
+            height.min(rect.y),
+        )
+        .intersection(f.area());
+
+        let list = List::new(completion.candidates.iter().map(String::as_str))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(state.theme.dialog_border))
+                    .padding(Padding::horizontal(1)),
+            )
+            .highlight_style(
+                Style::default()
+                    .bg(state.theme.bg_focused_selected)
+                    .fg(state.theme.fg_focused_selected)
+                    .bold(),
+            );
+        let mut list_state = ListState::default().with_selected(Some(self.selected_completion));
+        f.render_widget(Clear, area);
+        f.render_stateful_widget(list, area, &mut list_state);
+    }
+}
+
+impl SearchComponent {
+    /// Draws the fuzzy search in the history in a popup above the search bar.
+    fn draw_history_search(&self, f: &mut Frame<'_>, rect: Rect, state: &State) {
+        let Some(search) = &self.history_search else {
+            return;
+        };
+        let entries = search.matches(&self.history, &self.cluster);
+        let visible = entries.len().clamp(1, MAX_VISIBLE_HISTORY_ENTRIES);
+        let height = (visible + 3) as u16;
+        let area = Rect::new(
+            rect.x,
+            rect.y.saturating_sub(height),
+            rect.width,
+            height.min(rect.y),
+        )
+        .intersection(f.area());
+
+        let title = match search.all_clusters {
+            true => " History of all clusters ".to_string(),
+            false => format!(" History of {} ", self.cluster),
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(state.theme.dialog_border))
+            .padding(Padding::horizontal(1))
+            .title(title)
+            .title_bottom(" CTRL+R: all clusters ");
+        let [pattern_area, list_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(block.inner(area));
+
+        let metadata_style = Style::default().fg(state.theme.autocomplete).not_bold();
+        let items = entries.iter().map(|entry| {
+            let mut line = highlight_query(&entry.query, None, &state.theme);
+            let mut metadata = vec![];
+            if search.all_clusters {
+                metadata.extend(entry.cluster.clone());
+            }
+            if let Some(results) = entry.results {
+                metadata.push(format!("{results} results"));
+            }
+            if let Some(timestamp) = entry.timestamp {
+                let elapsed = (chrono::Utc::now() - timestamp)
+                    .to_std()
+                    .unwrap_or_default();
+                metadata.push(timeago::Formatter::new().convert(elapsed));
+            }
+            if !metadata.is_empty() {
+                line.push_span(Span::styled(
+                    format!("  {}", metadata.join(" · ")),
+                    metadata_style,
+                ));
+            }
+            line
+        });
+        let list = List::new(items).highlight_style(
+            Style::default()
+                .bg(state.theme.bg_focused_selected)
+                .fg(state.theme.fg_focused_selected),
+        );
+        let mut list_state = ListState::default()
+            .with_selected(Some(search.selected.min(entries.len().saturating_sub(1))));
+
+        f.render_widget(Clear, area);
+        f.render_widget(block, area);
+        f.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::styled("> ", metadata_style),
+                Span::raw(search.pattern.value()),
+            ])),
+            pattern_area,
+        );
+        f.render_stateful_widget(list, list_area, &mut list_state);
+        f.set_cursor_position(Position {
+            x: pattern_area.x + 2 + search.pattern.visual_cursor() as u16,
+            y: pattern_area.y,
+        });
+    }
+}
+
 impl Component for SearchComponent {
     fn register_action_handler(&mut self, tx: UnboundedSender<Action>) {
         self.action_tx = Some(tx);
@@ -175,8 +443,26 @@ impl Component for SearchComponent {
     }
 
     fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>, TuiError> {
-        self.remaining_input = None;
+        self.parse_error = None;
+        if self.history_search.is_some() {
+            self.handle_history_search_key(key);
+            return Ok(None);
+        }
         match key.code {
+            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.history_search = Some(HistorySearch::default());
+                self.completion = None;
+            }
+            KeyCode::Char(' ') if key.modifiers.contains(KeyModifiers::CONTROL) => self.complete(),
+            KeyCode::Esc => self.completion = None,
+            KeyCode::Right if self.completion.is_some() => self.apply_completion(),
+            KeyCode::Up if self.completion.is_some() => {
+                self.selected_completion = self.selected_completion.saturating_sub(1);
+            }
+            KeyCode::Down if self.completion.is_some() => {
+                let last = self.completion.as_ref().unwrap().candidates.len() - 1;
+                self.selected_completion = (self.selected_completion + 1).min(last);
+            }
             KeyCode::Right => {
                 if self.input.value().len() == self.input.cursor() {
                     if let Some(a) = &self.autocomplete {
@@ -199,6 +485,7 @@ impl Component for SearchComponent {
                 self.entered = Some(Instant::now());
                 self.search()?;
                 self.autocomplete = None;
+                self.completion = None;
             }
             _ if !key.modifiers.contains(KeyModifiers::CONTROL) => {
                 if let Some(e) = self.entered {
@@ -213,10 +500,29 @@ impl Component for SearchComponent {
                 self.input.handle_event(&Event::Key(key));
                 self.parse_input();
                 self.autocomplete(KeyCode::Backspace);
+                // The popup is only opened with `Ctrl + Space`, typing refines its completions
+                if self.completion.is_some() {
+                    self.complete();
+                }
             }
             _ => {
                 self.input.handle_event(&Event::Key(key));
+                self.completion = None;
+            }
+        }
+        Ok(None)
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>, TuiError> {
+        match action {
+            Action::Topics(topics) => self.completer.topics = topics,
+            Action::JsonPaths(paths) => self.completer.paths = paths,
+            Action::History(history) => self.history = history,
+            Action::SelectedTopics(topics) => {
+                self.topics = topics;
+                self.update_saved_query_completions();
             }
+            _ => (),
         }
         Ok(None)
     }
@@ -224,24 +530,21 @@ impl Component for SearchComponent {
     fn draw(&mut self, f: &mut Frame<'_>, rect: Rect, state: &State) -> Result<(), TuiError> {
         let padding = 1;
         let input: &str = self.input.value();
-        let mut line: Line = match &self.remaining_input {
-            Some(e) => {
-                let parts = input.split_at(input.len() - e.len());
-                Line::from(vec![
-                    Span::raw(parts.0),
-                    Span::styled(
-                        parts.1,
-                        Style::default()
-                            .fg(state.theme.orange)
-                            .not_bold()
-                            .underlined(),
-                    ),
-                ])
-            }
-            None => Line::from(vec![input.into()]),
-        };
-
-        if let Some(a) = &self.autocomplete {
+        if self
+            .highlighted
+            .as_ref()
+            .is_none_or(|(highlighted, _)| highlighted != input)
+        {
+            self.highlighted = Some((input.to_string(), invalid_offset(input)));
+        }
+        let invalid = self.highlighted.as_ref().and_then(|(_, invalid)| *invalid);
+        let mut line = highlight_query(input, invalid, &state.theme);
+
+        if let Some(a) = self
+            .autocomplete
+            .as_ref()
+            .filter(|_| self.completion.is_none())
+        {
             line.push_span(Span::styled(
                 a,
                 Style::default().fg(state.theme.autocomplete).not_bold(),
@@ -270,6 +573,17 @@ impl Component for SearchComponent {
             .border_type(BorderType::Rounded)
             .padding(Padding::left(1))
             .title(" Search ");
+        let block = match &self.parse_error {
+            // The caret is aligned with the input, that starts after the border and the padding
+            Some(e) => block.title_bottom(Line::from(vec![
+                Span::raw(" ".repeat(usize::from(padding))),
+                Span::styled(
+                    format!("{} expected {} ", e.caret(), e.expected),
+                    Style::default().fg(state.theme.orange).not_bold(),
+                ),
+            ])),
+            None => block,
+        };
         let block = self.make_block_focused_with_state(state, block);
 
         let selected_style = Paragraph::new(line)
@@ -282,12 +596,19 @@ impl Component for SearchComponent {
         }
         //f.render_widget(Clear, rect);
         f.render_widget(paragraph, rect);
+        if state.is_focused(&self.id()) {
+            self.draw_completion(f, rect, state);
+            self.draw_history_search(f, rect, state);
+        }
         Ok(())
     }
 
     fn shortcuts(&self) -> Vec<Shortcut> {
         vec![
             Shortcut::new("↑↓", "History"),
+            Shortcut::new("→", "Complete"),
+            Shortcut::new("CTRL+SPACE", "Completions"),
+            Shortcut::new("CTRL+R", "Search history"),
             Shortcut::new("ENTER", "Search"),
         ]
     }
@@ -302,6 +623,120 @@ fn test_draw() {
     assert_draw!(component, 60, 3)
 }
 
+#[test]
+fn test_draw_parse_error() {
+    let query = r#"key starts_with "foo""#;
+    let mut component = SearchComponent::new(query, vec![], PathBuf::from("."));
+    component.parse_error = Some(ParseError::new(query, query));
+    assert_draw!(component, 100, 3)
+}
+
+#[test]
+fn test_complete_topic_name() {
+    let mut component = SearchComponent::new("topic == ", vec![], PathBuf::from("."));
+    component
+        .update(Action::Topics(vec![
+            "orders".to_string(),
+            "customers".to_string(),
+        ]))
+        .unwrap();
+    component.complete();
+    component.selected_completion = 1;
+    component.apply_completion();
+    assert_eq!(component.input.value(), r#"topic == "customers" "#);
+    assert_eq!(component.completion, None);
+}
+
+#[tokio::test]
+async fn test_completion_popup_opens_on_demand() {
+    let history = vec![HistoryEntry::new("from begin offset > 10", "prod", &[])];
+    let mut component = SearchComponent::new("", history, PathBuf::from(".")).with_cluster("prod");
+    component
+        .update(Action::Topics(vec!["orders".to_string()]))
+        .unwrap();
+    for c in "from".chars() {
+        component
+            .handle_key_events(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))
+            .unwrap();
+    }
+    assert_eq!(component.completion, None);
+    // Arrows keep browsing the history and accepting its suggestion
+    component
+        .handle_key_events(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE))
+        .unwrap();
+    component
+        .handle_key_events(KeyEvent::new(KeyCode::Right, KeyModifiers::NONE))
+        .unwrap();
+    assert_eq!(component.input.value(), "from begin offset > 10");
+
+    component.input = Input::from("topic == ");
+    component
+        .handle_key_events(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::CONTROL))
+        .unwrap();
+    assert!(component.completion.is_some());
+    component
+        .handle_key_events(KeyEvent::new(KeyCode::Right, KeyModifiers::NONE))
+        .unwrap();
+    assert_eq!(component.input.value(), r#"topic == "orders" "#);
+    assert_eq!(component.completion, None);
+}
+
+#[test]
+fn test_saved_queries_of_selected_topics() {
+    let saved_query = |topics: &[&str]| SavedQuery {
+        query: "partition == 0".to_string(),
+        description: None,
+        topics: topics.iter().map(ToString::to_string).collect(),
+    };
+    let saved_queries = IndexMap::from([
+        ("failed-payments".to_string(), saved_query(&["payments"])),
+        ("late-orders".to_string(), saved_query(&["orders"])),
+        ("first-partition".to_string(), saved_query(&[])),
+    ]);
+    let mut component =
+        SearchComponent::new("", vec![], PathBuf::from(".")).with_saved_queries(saved_queries);
+    assert_eq!(component.completer.saved_queries.len(), 3);
+    component
+        .update(Action::SelectedTopics(vec!["payments".to_string()]))
+        .unwrap();
+    assert_eq!(
+        component.completer.saved_queries,
+        vec!["failed-payments", "first-partition"]
+    );
+}
+
+#[tokio::test]
+async fn test_history_search() {
+    let history = vec![
+        HistoryEntry::new("from begin partition == 2", "dev", &[]),
+        HistoryEntry::new(r#"key == "my-key""#, "prod", &[]),
+        HistoryEntry::new("from begin offset > 10", "prod", &[]),
+    ];
+    let mut component = SearchComponent::new("", history, PathBuf::from(".")).with_cluster("prod");
+    component
+        .handle_key_events(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL))
+        .unwrap();
+    for c in "begin".chars() {
+        component
+            .handle_key_events(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))
+            .unwrap();
+    }
+    let search = component.history_search.as_ref().unwrap();
+    assert_eq!(
+        search
+            .matches(&component.history, &component.cluster)
+            .into_iter()
+            .map(|e| e.query.as_str())
+            .collect::<Vec<_>>(),
+        vec!["from begin offset > 10"]
+    );
+    component
+        .handle_key_events(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+        .unwrap();
+    assert!(component.history_search.is_none());
+    assert_eq!(component.input.value(), "from begin offset > 10");
+}
+
 #[test]
 fn test_empty_history_underflow() {
     let component = SearchComponent::new("from begin", vec![], PathBuf::from("."));
@@ -93,6 +93,7 @@ impl Component for TopicDetailsComponent {
         Ok(None)
     }
 
+    #[allow(clippy::collapsible_match)]
     fn update(&mut self, action: Action) -> Result<Option<Action>, TuiError> {
         match action {
             Action::Tick => self.throbber_state.calc_next(),
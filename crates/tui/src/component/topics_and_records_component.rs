@@ -111,7 +111,9 @@ fn test_draw() {
             }"#
             )),
             value_as_string: String::default(),
+            tombstone: false,
         }],
+        superseding: vec![],
         read: 1,
     })
     .unwrap();
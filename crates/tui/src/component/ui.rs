@@ -1,24 +1,29 @@
 //! Module gathering the code to run the terminal user interface.
 
 use app::App;
-use app::search::{Search, SearchContext};
+use app::search::Search;
+use app::search::engine::SearchEngine;
+use app::search::filter::{ModuleKind, filters_metrics, reset_filters_metrics};
 use chrono::DateTime;
 use crossterm::event::KeyEvent;
 use futures::{StreamExt, future};
 use futures_batch::TryChunksTimeoutStreamExt;
 use itertools::Itertools;
 use lib::KafkaRecord;
+use lib::search::aggregation::Aggregator;
 use ratatui::prelude::Rect;
 use rdkafka::Message;
-use rdkafka::consumer::{Consumer, StreamConsumer};
-use rdkafka::message::OwnedMessage;
+use rdkafka::consumer::Consumer;
 use std::collections::HashSet;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
+use thousands::Separable;
 use tokio::select;
 use tokio::sync::mpsc::{self, UnboundedSender};
 use tokio::time::Instant;
 use tokio_util::sync::CancellationToken;
-use tracing::{error, info, trace_span, warn};
+use tracing::{error, info, warn};
 
 use crate::action::{Action, Level, Notification};
 use crate::component::{Component, RootComponent};
@@ -27,7 +32,7 @@ use crate::records_buffer::RecordsAndStats;
 use crate::schema_detail::SchemaDetail;
 use crate::tui;
 
-use super::{RecordsSender, State};
+use super::{ComponentName, RecordsSender, State};
 
 pub struct Ui {
     app: App,
@@ -37,6 +42,10 @@ pub struct Ui {
     topics: Vec<String>,
     last_tick_key_events: Vec<KeyEvent>,
     records_sender: RecordsSender,
+    /// The last search query typed by the user
+    last_prompt: Option<String>,
+    /// Number of kafka records matching the current search query
+    matched: Arc<AtomicUsize>,
 }
 
 impl Ui {
@@ -51,6 +60,16 @@ impl Ui {
             root: RootComponent::new(query, selected_topics, records_receiver, state),
             records_sender,
             last_tick_key_events: Vec::new(),
+            last_prompt: None,
+            matched: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Records in the history the number of kafka records that matched the last search query.
+    fn record_results(&mut self) {
+        if let Some(prompt) = self.last_prompt.take() {
+            let results = self.matched.load(Ordering::Relaxed);
+            self.app.config.set_history_results(&prompt, results);
         }
     }
 
@@ -58,8 +77,8 @@ impl Ui {
         app: &App,
         topics: Vec<String>,
         tx: UnboundedSender<Action>,
-    ) -> Result<StreamConsumer, TuiError> {
-        match app.create_consumer(&topics) {
+    ) -> Result<app::consumer::Consumer, TuiError> {
+        match app.create_consumer_2(&topics) {
             Ok(c) => Ok(c),
             Err(e) => {
                 tx.send(Action::Notification(Notification::new(
@@ -93,55 +112,153 @@ impl Ui {
         )))?;
         self.worker = CancellationToken::new();
 
+        let plugins = self
+            .app
+            .transforms()
+            .and_then(|t| self.app.deserializers().map(|d| (t, d)));
+        let (transforms, deserializers) = match plugins {
+            Ok(plugins) => plugins,
+            Err(e) => {
+                error!("{e}");
+                tx.send(Action::Notification(Notification::new(
+                    Level::Error,
+                    e.to_string(),
+                )))?;
+                tx.send(Action::StopConsuming())?;
+                return Ok(());
+            }
+        };
+
         let query = self.app.search_query.query().clone();
         let order_by = query.order_by.clone();
         tx.send(Action::OrderBy(order_by.clone()))?;
+        tx.send(Action::LatestByKey(query.latest_by_key))?;
         tx.send(Action::NewConsumer())?;
         tx.send(Action::Consuming)?;
+        if !query.aggregations.is_empty() {
+            tx.send(Action::NewView(ComponentName::Aggregations))?;
+        }
 
         let _token = self.worker.clone();
         let token = self.worker.clone();
-        let search_query = self.app.search_query.query().clone();
+        let search_query = self.app.search_query.clone();
+        let filters = search_query
+            .filters()
+            .into_iter()
+            .map(|f| f.name)
+            .chain(transforms.names().map(str::to_string))
+            .collect::<HashSet<_>>();
+        reset_filters_metrics();
         let app = self.app.clone();
         let txx = tx.clone();
         let topics = self.topics.clone();
 
-        let (tx_dd, mut rx_dd) = mpsc::unbounded_channel::<OwnedMessage>();
-        let mut schema_registry = app.schema_registry().clone();
         let token_cloned = token.clone();
 
         let filters_directory = self.app.config.workspace().filters_dir();
+        let engine = SearchEngine::start(
+            search_query,
+            filters_directory,
+            app.schema_registry(),
+            transforms,
+            deserializers,
+            app.consumer_config().workers(),
+        );
+        let (engine, mut evaluations) = match engine {
+            Ok(engine) => engine,
+            Err(e) => {
+                error!("{e}");
+                tx.send(Action::Notification(Notification::new(
+                    Level::Error,
+                    e.to_string(),
+                )))?;
+                tx.send(Action::StopConsuming())?;
+                return Ok(());
+            }
+        };
+        let throughput = engine.throughput();
+        let workers = engine.workers();
         let records_sender = self.records_sender.clone();
+        let mut aggregator = Aggregator::new(&query.aggregations);
+        let aggregations_tx = tx.clone();
+        let matched_counter = self.matched.clone();
+        matched_counter.store(0, Ordering::Relaxed);
         tokio::task::Builder::new()
             .name("search-engine")
         .spawn(async move {
             let (mut read, mut matched) = (0, 0);
+            let mut refresh_metrics = tokio::time::interval(Duration::from_millis(500));
+            let mut disabled_filters = HashSet::new();
+            let mut failed_transformers = HashSet::new();
             loop {
                 select! {
                     _ = token_cloned.cancelled() => {
+                        if !filters.is_empty() {
+                            let metrics = filters_metrics().into_iter().filter(|m| filters.contains(&m.name)).collect();
+                            let _ = aggregations_tx.send(Action::FilterMetrics(metrics));
+                        }
+                        info!(
+                            "Search engine: {} records evaluated by {} workers in {}ms ({} rec/s)",
+                            throughput.read().separate_with_underscores(),
+                            workers,
+                            throughput.elapsed().as_millis().separate_with_underscores(),
+                            throughput.records_per_second().separate_with_underscores()
+                        );
                         return;
                      },
-                    Some(message) = rx_dd.recv() => {
-                        let record = KafkaRecord::parse_with_schema_registry(message, &mut schema_registry).await;
-                        let context = SearchContext::new(&record, &filters_directory);
-                        let span = trace_span!("matching", offset = %record.offset, partition = %record.partition, topic = %record.topic);
-                        let search_span = span.enter();
-                        let matches = search_query.matches(&context);
-                        drop(search_span);
+                    _ = refresh_metrics.tick(), if !aggregator.is_empty() || !filters.is_empty() => {
+                        if !aggregator.is_empty() {
+                            let _ = aggregations_tx.send(Action::Aggregations(aggregator.rows()));
+                        }
+                        if !filters.is_empty() {
+                            let metrics = filters_metrics().into_iter().filter(|m| filters.contains(&m.name)).collect::<Vec<_>>();
+                            // Notify once that a search filter exceeded its resource limits
+                            for (name, reason) in metrics.iter().filter_map(|m| m.disabled.as_ref().map(|r| (&m.name, r))) {
+                                if disabled_filters.insert(name.clone()) {
+                                    let _ = aggregations_tx.send(Action::Notification(Notification::new(Level::Error, reason.clone())));
+                                }
+                            }
+                            // Notify once that records are excluded because a transformer failed on them
+                            for metrics in metrics.iter().filter(|m| m.errors > 0 && m.disabled.is_none() && m.kind == ModuleKind::Transformer) {
+                                if failed_transformers.insert(metrics.name.clone()) {
+                                    let reason = format!("Transformer '{}' failed, records it cannot transform are excluded from the results", metrics.name);
+                                    let _ = aggregations_tx.send(Action::Notification(Notification::new(Level::Error, reason)));
+                                }
+                            }
+                            let _ = aggregations_tx.send(Action::FilterMetrics(metrics));
+                        }
+                    },
+                    Some(evaluation) = evaluations.recv() => {
                         read += 1;
+                        let matches = evaluation.matches;
                         // Pushing to a locked buffer replaced by sending over channel.
-                        if matches {
-                            matched += 1;
-                            records_sender.send(RecordsAndStats {
-                                records: vec![record],
-                                read
-                            }).unwrap();
+                        match (evaluation.record, matches) {
+                            (Some(record), true) => {
+                                matched += 1;
+                                matched_counter.fetch_add(1, Ordering::Relaxed);
+                                aggregator.add(&record);
+                                records_sender.send(RecordsAndStats {
+                                    records: vec![record],
+                                    superseding: vec![],
+                                    read
+                                }).unwrap();
+                            }
+                            // With `latest by key`, a record that doesn't match may replace the latest record of its key
+                            (Some(record), false) => {
+                                records_sender.send(RecordsAndStats {
+                                    records: vec![],
+                                    superseding: vec![record],
+                                    read
+                                }).unwrap();
+                            }
+                            (None, _) => (),
                         }
 
                         if !matches && read % 200 == 0 {
                             // Send stats update even if no match found to update the UI
                             records_sender.send(RecordsAndStats {
                                 records: vec![],
+                                superseding: vec![],
                                 read
                             }).unwrap();
                         }
@@ -149,6 +266,9 @@ impl Ui {
 
                         if let Some(limit) = query.limit {
                             if Some(matched) >= Some(limit) {
+                                if !aggregator.is_empty() {
+                                    let _ = aggregations_tx.send(Action::Aggregations(aggregator.rows()));
+                                }
                                 token_cloned.cancel();
                             }
                         }
@@ -171,6 +291,9 @@ impl Ui {
                     }
                 };
                 let _ = tx.send(Action::Consuming);
+                let sample = app.search_query.query().sample.clone();
+                let mut stop_offsets = consumer.stop_offsets();
+                let consumer = consumer.stream_consumer();
                 let assignments = consumer.assignment().unwrap();
                 let txx = tx.clone();
                 tokio::task::Builder::new()
@@ -208,7 +331,19 @@ impl Ui {
                             .and_then(|r| r.timestamp().to_millis())
                             .unwrap_or(0);
                         for record in bulk_of_records {
-                            if tx_dd.send(record.detach()).is_err() {
+                            if stop_offsets.stop(&consumer, &record) {
+                                if stop_offsets.all_stopped() {
+                                    token.cancel();
+                                }
+                                continue;
+                            }
+                            // Sampling happens before the record is deserialized and matched against the search query.
+                            if sample.as_ref().is_some_and(|s| {
+                                !s.keep(record.key(), record.partition(), record.offset())
+                            }) {
+                                continue;
+                            }
+                            if engine.send(record.detach()).is_err() {
                                 token.cancel();
                                 break;
                             }
@@ -336,8 +471,11 @@ impl Ui {
                 // Possible place to poll or forward any pending records from channel, if needed
                 match action {
                     Action::NewSearchPrompt(ref prompt) => {
-                        self.app.config.push_history(prompt);
+                        self.record_results();
+                        self.app.config.push_history(prompt, &self.topics);
                         self.app.config.save_config()?;
+                        self.last_prompt = Some(prompt.clone());
+                        action_tx.send(Action::History(self.app.config.history().to_vec()))?;
                     }
                     Action::RequestTopicDetails(ref topics) => {
                         self.topics_details(topics.clone(), action_tx.clone());
@@ -353,6 +491,10 @@ impl Ui {
                         )))?;
                     }
                     Action::Quit => {
+                        self.record_results();
+                        if let Err(e) = self.app.config.save_config() {
+                            warn!("Cannot save the history: {e}");
+                        }
                         self.worker.cancel();
                         self.should_quit = true;
                     }
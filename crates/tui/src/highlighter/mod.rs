@@ -12,6 +12,8 @@ use syntect::{
 };
 use tracing::warn;
 
+pub(crate) mod query;
+
 static SYNTAX_SET: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
 static THEMES: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
 pub const HIGHLIGHTER_DEFAULT_THEME: &str = "base16-ocean.dark";
@@ -0,0 +1,286 @@
+//! Syntax highlighting of the search query in `SearchComponent`.
+//! Tokens are colored by role, parentheses by nesting depth,
+//! and the part of the query that cannot be parsed is underlined in red.
+use std::ops::Range;
+
+use lib::{error::SearchError, search::SearchQuery};
+use ratatui::{
+    style::Style,
+    text::{Line, Span},
+};
+
+use crate::Theme;
+
+const CLAUSES: [&str; 23] = [
+    "from",
+    "limit",
+    "order",
+    "sort",
+    "by",
+    "asc",
+    "desc",
+    "select",
+    "where",
+    "latest",
+    "sample",
+    "random",
+    "count",
+    "group",
+    "distinct",
+    "min",
+    "max",
+    "begin",
+    "beginning",
+    "end",
+    "now",
+    "as",
+    "in",
+];
+
+const SYMBOLS: [&str; 17] = [
+    "key",
+    "k",
+    "value",
+    "v",
+    "topic",
+    "t",
+    "partition",
+    "p",
+    "offset",
+    "o",
+    "timestamp",
+    "ts",
+    "size",
+    "si",
+    "headers",
+    "h",
+    "offsettail",
+];
+
+const OPERATORS: [&str; 9] = [
+    "contains", "contain", "includes", "include", "starts", "start", "with", "and", "or",
+];
+
+/// Role of a token in the search query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Role {
+    Clause,
+    Symbol,
+    Operator,
+    String,
+    Number,
+    /// A search filter, a built-in function or a saved query
+    Filter,
+    /// A parenthesis and its nesting depth
+    Parenthesis(usize),
+    Text,
+}
+
+impl Role {
+    fn style(&self, theme: &Theme) -> Style {
+        let style = Style::default();
+        match self {
+            Role::Clause => style.fg(theme.magenta).bold(),
+            Role::Symbol => style.fg(theme.blue),
+            Role::Operator => style.fg(theme.cyan),
+            Role::String => style.fg(theme.green),
+            Role::Number => style.fg(theme.yellow),
+            Role::Filter => style.fg(theme.orange),
+            Role::Parenthesis(depth) => {
+                let colors = [theme.yellow, theme.magenta, theme.cyan, theme.blue];
+                style.fg(colors[depth % colors.len()]).bold()
+            }
+            Role::Text => style,
+        }
+    }
+}
+
+/// Splits the search query into tokens and gives them a role.
+pub(crate) fn tokenize(input: &str) -> Vec<(Range<usize>, Role)> {
+    let mut tokens = vec![];
+    let mut depth = 0;
+    let mut chars = input.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let mut end = start + c.len_utf8();
+        let role = match c {
+            c if c.is_whitespace() => continue,
+            '"' | '\'' => {
+                // A backslash escapes the quote
+                let mut escaped = false;
+                for (i, next) in chars.by_ref() {
+                    end = i + next.len_utf8();
+                    if next == c && !escaped {
+                        break;
+                    }
+                    escaped = next == '\\' && !escaped;
+                }
+                Role::String
+            }
+            '(' => {
+                depth += 1;
+                Role::Parenthesis(depth - 1)
+            }
+            ')' => {
+                depth = depth.saturating_sub(1);
+                Role::Parenthesis(depth)
+            }
+            c if is_operator(c) => {
+                while let Some((i, next)) = chars.next_if(|(_, next)| is_operator(*next)) {
+                    end = i + next.len_utf8();
+                }
+                Role::Operator
+            }
+            _ => {
+                while let Some((i, next)) = chars.next_if(|(_, next)| is_word(*next)) {
+                    end = i + next.len_utf8();
+                }
+                let word = input[start..end].to_lowercase();
+                match word.as_str() {
+                    w if c.is_ascii_digit() && !w.contains('.') => Role::Number,
+                    _ if chars.peek().is_some_and(|(_, next)| *next == '(') => Role::Filter,
+                    w if w.starts_with('@') => Role::Filter,
+                    w if CLAUSES.contains(&w) => Role::Clause,
+                    w if OPERATORS.contains(&w) => Role::Operator,
+                    w if SYMBOLS.contains(&w.split('.').next().unwrap_or_default()) => Role::Symbol,
+                    _ => Role::Text,
+                }
+            }
+        };
+        tokens.push((start..end, role));
+    }
+    tokens
+}
+
+fn is_operator(c: char) -> bool {
+    matches!(c, '=' | '!' | '<' | '>' | '~' | '&' | '|' | '+' | '-' | ',')
+}
+
+fn is_word(c: char) -> bool {
+    !c.is_whitespace() && !matches!(c, '(' | ')' | '"' | '\'') && !is_operator(c) || c == '-'
+}
+
+/// Returns the byte offset from which the search query cannot be parsed.
+pub(crate) fn invalid_offset(input: &str) -> Option<usize> {
+    match SearchQuery::parse(input) {
+        Err(SearchError::Parse(e)) => Some(
+            input
+                .char_indices()
+                .nth(e.range.start)
+                .map_or(input.len(), |(i, _)| i),
+        ),
+        Err(SearchError::SavedQuery(_)) | Ok(_) => None,
+    }
+}
+
+/// Highlights the search query. Everything from `invalid` is underlined in red.
+pub(crate) fn highlight_query(input: &str, invalid: Option<usize>, theme: &Theme) -> Line<'static> {
+    let invalid_style = |start: usize, style: Style| match invalid {
+        Some(invalid) if start >= invalid => style.underlined().underline_color(theme.red),
+        _ => style,
+    };
+    let mut spans = vec![];
+    let mut position = 0;
+    for (range, role) in tokenize(input) {
+        if position < range.start {
+            spans.push(Span::styled(
+                input[position..range.start].to_string(),
+                invalid_style(position, Style::default()),
+            ));
+        }
+        spans.push(Span::styled(
+            input[range.clone()].to_string(),
+            invalid_style(range.start, role.style(theme)),
+        ));
+        position = range.end;
+    }
+    if position < input.len() {
+        spans.push(Span::styled(
+            input[position..].to_string(),
+            invalid_style(position, Style::default()),
+        ));
+    }
+    Line::from(spans)
+}
+
+#[cfg(test)]
+fn roles(input: &str) -> Vec<(&str, Role)> {
+    tokenize(input)
+        .into_iter()
+        .map(|(range, role)| (&input[range], role))
+        .collect()
+}
+
+#[test]
+fn test_tokenize() {
+    assert_eq!(
+        roles(r#"from begin value.name contains "rust" && partition >= 2 limit 10"#),
+        vec![
+            ("from", Role::Clause),
+            ("begin", Role::Clause),
+            ("value.name", Role::Symbol),
+            ("contains", Role::Operator),
+            ("\"rust\"", Role::String),
+            ("&&", Role::Operator),
+            ("partition", Role::Symbol),
+            (">=", Role::Operator),
+            ("2", Role::Number),
+            ("limit", Role::Clause),
+            ("10", Role::Number),
+        ]
+    );
+}
+
+#[test]
+fn test_tokenize_parentheses_and_filters() {
+    assert_eq!(
+        roles(r#"(key_ends_with("1") || (headers.trace-id == 'a'))"#),
+        vec![
+            ("(", Role::Parenthesis(0)),
+            ("key_ends_with", Role::Filter),
+            ("(", Role::Parenthesis(1)),
+            ("\"1\"", Role::String),
+            (")", Role::Parenthesis(1)),
+            ("||", Role::Operator),
+            ("(", Role::Parenthesis(1)),
+            ("headers.trace-id", Role::Symbol),
+            ("==", Role::Operator),
+            ("'a'", Role::String),
+            (")", Role::Parenthesis(1)),
+            (")", Role::Parenthesis(0)),
+        ]
+    );
+}
+
+#[test]
+fn test_tokenize_saved_query() {
+    assert_eq!(
+        roles("@failed-payments and p == 2"),
+        vec![
+            ("@failed-payments", Role::Filter),
+            ("and", Role::Operator),
+            ("p", Role::Symbol),
+            ("==", Role::Operator),
+            ("2", Role::Number),
+        ]
+    );
+}
+
+#[test]
+fn test_invalid_offset() {
+    assert_eq!(invalid_offset(r#"from begin key == "a""#), None);
+    assert_eq!(invalid_offset(r#"from begin key stats with "a""#), Some(15));
+    assert_eq!(
+        invalid_offset(r#"key == "é" && key stats with "a""#),
+        Some(19)
+    );
+}
+
+#[test]
+fn test_highlight_query() {
+    let theme = Theme::light();
+    let line = highlight_query("key == 'a' limi", Some(11), &theme);
+    assert_eq!(line.to_string(), "key == 'a' limi");
+    let invalid = line.spans.last().unwrap();
+    assert_eq!(invalid.content, "limi");
+    assert_eq!(invalid.style.underline_color, Some(theme.red));
+}
@@ -3,14 +3,21 @@
 //!
 //! This should be possible to increase the size but the more you display events,
 //! the more the tool gets laggy. I need to work on it.
+//!
+//! When the search query contains `latest by key`, records are kept in a keyed structure
+//! and the ring buffer only shows the `[BUFFER_SIZE]` most recent keys.
+
+use std::collections::BTreeSet;
 
 use circular_buffer::{CircularBuffer, Iter};
 use lib::{
-    KafkaRecord,
-    search::{Order, OrderBy, order::OrderKeyword},
+    DataType, KafkaRecord,
+    search::{Order, OrderBy, latest::LatestByKey, order::OrderKeyword},
 };
 use rayon::prelude::*;
 
+use crate::component::learn_paths;
+
 /// Size of the ring buffer
 #[cfg(not(target_family = "windows"))]
 pub const BUFFER_SIZE: usize = 500;
@@ -19,11 +26,19 @@ pub const BUFFER_SIZE: usize = 500;
 #[cfg(target_family = "windows")]
 pub const BUFFER_SIZE: usize = 120;
 
+/// Maximum number of JSON paths learned from the records for the autocompletion
+const MAX_PATHS: usize = 500;
+
 /// Wrapper around [`CircularBuffer`]
 pub(crate) struct RecordsBuffer {
     buffer: CircularBuffer<BUFFER_SIZE, KafkaRecord>,
     stats: Stats,
     last_time_sorted: usize,
+    latest: Option<LatestByKey>,
+    /// `true` when the keyed records have changed since the ring buffer was last refreshed
+    compacted: bool,
+    /// JSON paths of the values and headers of the records, used by the autocompletion
+    paths: BTreeSet<String>,
 }
 
 macro_rules! sort_records {
@@ -50,13 +65,26 @@ impl RecordsBuffer {
             buffer: CircularBuffer::<BUFFER_SIZE, KafkaRecord>::new(),
             stats: Stats::default(),
             last_time_sorted: 0,
+            latest: None,
+            compacted: true,
+            paths: BTreeSet::new(),
         }
     }
 
+    /// Keeps only the newest record of each key.
+    pub fn latest_by_key(&mut self, enabled: bool) {
+        self.latest = enabled.then(LatestByKey::new);
+        self.compacted = true;
+    }
+
     /// Empty the buffer and reset metrics
     pub fn reset(&mut self) {
         self.buffer.clear();
         self.stats = Stats::default();
+        self.paths.clear();
+        if let Some(latest) = self.latest.as_mut() {
+            *latest = LatestByKey::new();
+        }
     }
 
     /// Returns the metrics of the number of records matched and read.
@@ -89,18 +117,65 @@ impl RecordsBuffer {
         for record in payload.records {
             let _ = self.push(record);
         }
+        if let Some(latest) = self.latest.as_mut() {
+            for record in payload.superseding {
+                latest.add(record, false);
+                self.compacted = false;
+            }
+        }
         self.stats.read = payload.read;
         self.stats.matched
     }
 
+    /// Returns the JSON paths learned from the records, for instance `value.customer.id`.
+    pub fn paths(&self) -> &BTreeSet<String> {
+        &self.paths
+    }
+
     pub fn push(&mut self, kafka_record: KafkaRecord) -> usize {
-        self.buffer.push_back(kafka_record);
+        self.learn_paths(&kafka_record);
+        match self.latest.as_mut() {
+            Some(latest) => {
+                latest.add(kafka_record, true);
+                self.compacted = false;
+            }
+            None => {
+                self.buffer.push_back(kafka_record);
+            }
+        }
         self.stats.matched += 1;
         self.stats.matched
     }
 
+    fn learn_paths(&mut self, kafka_record: &KafkaRecord) {
+        if self.paths.len() >= MAX_PATHS {
+            return;
+        }
+        if let DataType::Json(value) = &kafka_record.value {
+            learn_paths(value, "value", &mut self.paths, MAX_PATHS);
+        }
+        for header in kafka_record.headers.keys() {
+            self.paths.insert(format!("headers.{header}"));
+        }
+    }
+
+    /// Refreshes the ring buffer with the most recent records of the keyed structure.
+    fn compact(&mut self) {
+        if self.compacted {
+            return;
+        }
+        if let Some(latest) = &self.latest {
+            let records = latest.records();
+            let skip = records.len().saturating_sub(BUFFER_SIZE);
+            self.buffer.clear();
+            self.buffer.extend(records.into_iter().skip(skip).cloned());
+        }
+        self.compacted = true;
+    }
+
     /// Sort the buffer by the given order
     pub fn sort(&mut self, order_by: &OrderBy) {
+        self.compact();
         if self.stats.read == self.last_time_sorted {
             return;
         }
@@ -151,5 +226,39 @@ impl Stats {}
 
 pub struct RecordsAndStats {
     pub records: Vec<KafkaRecord>,
+    /// Records that don't match the search query but replace the latest record of their key, with `latest by key`.
+    pub superseding: Vec<KafkaRecord>,
     pub read: usize,
 }
+
+#[test]
+fn test_latest_by_key() {
+    use lib::DataType;
+
+    let record = |key: &str, offset: i64, tombstone: bool| KafkaRecord {
+        offset,
+        timestamp: Some(offset),
+        key: DataType::String(key.to_string()),
+        tombstone,
+        ..Default::default()
+    };
+    let mut buffer = RecordsBuffer::new();
+    buffer.latest_by_key(true);
+    buffer.extend(RecordsAndStats {
+        records: vec![
+            record("alice", 1, false),
+            record("bob", 2, false),
+            record("alice", 3, false),
+            record("carol", 4, false),
+        ],
+        superseding: vec![record("bob", 5, true), record("carol", 6, false)],
+        read: 6,
+    });
+    buffer.sort(&OrderBy::new(Order::Timestamp, OrderKeyword::Asc));
+
+    assert_eq!(buffer.stats().matched, 4);
+    assert_eq!(
+        buffer.iter().collect::<Vec<_>>(),
+        vec![&record("alice", 3, false)]
+    );
+}
@@ -0,0 +1,59 @@
+#![no_main]
+/// This transformer replaces the value of the kafka record
+/// with the user-specified field of the JSON value, an envelope for instance
+///
+/// ```sql
+/// from begin | transform unwrap-value("payload")
+/// ```
+///
+/// This WebAssembly module relies on the [Extism SDK](https://extism.org/docs/quickstart/plugin-quickstart/)
+use extism_pdk::*;
+use json::Value;
+
+use yozefu_wasm_types::{DataType, FilterInput, KafkaRecord};
+
+#[plugin_fn]
+pub fn transform(input: Json<FilterInput>) -> FnResult<Json<KafkaRecord>> {
+    // TODO - Edit the code as per your requirements
+    let field = input.0.params.first().unwrap().as_str().unwrap();
+    let mut record = input.0.record;
+    let value = match &record.value {
+        DataType::Json(value) => value.get(field).cloned(),
+        DataType::String(_) => None,
+    };
+    let Some(value) = value else {
+        return Err(WithReturnCode::new(
+            Error::msg(format!("The value has no field '{field}'")),
+            1,
+        ));
+    };
+    // The string representation of the value is updated by yozefu
+    record.value = DataType::Json(value);
+    Ok(Json(record))
+}
+
+#[plugin_fn]
+/// This function checks if the input parameters are valid
+pub fn parse_parameters(params: Json<Vec<Value>>) -> FnResult<()> {
+    // TODO - Edit the code as per your requirements
+    let length = params.0.len();
+    if length != 1 {
+        return Err(WithReturnCode::new(
+            Error::msg(format!(
+                "This transformer expects a string argument. Found {} arguments",
+                &length.to_string()
+            )),
+            1,
+        ));
+    }
+    if params.0.first().unwrap().is_string() {
+        return Ok(());
+    }
+    return Err(WithReturnCode::new(
+        Error::msg(format!(
+            "This transformer expects argument 1 to be a string, found {}",
+            json::to_string(params.0.first().unwrap()).unwrap()
+        )),
+        2,
+    ));
+}
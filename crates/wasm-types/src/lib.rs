@@ -1,6 +1,5 @@
-//! Crate containing the types used to define search filters.
-pub use lib::FilterResult;
-use lib::KafkaRecord;
+//! Crate containing the types used to define search filters and transformers.
+pub use lib::{DataType, FilterResult, KafkaRecord};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 